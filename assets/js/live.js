@@ -0,0 +1,10 @@
+// refreshes the list of stories of the index when a report is generated or updated
+const events = new EventSource("/events");
+events.addEventListener("report", async () => {
+  const response = await fetch(window.location.href, { cache: "no-cache" });
+  if (!response.ok) return;
+  const page = new DOMParser().parseFromString(await response.text(), "text/html");
+  const groups = page.getElementById("groups");
+  const current = document.getElementById("groups");
+  if (groups && current) current.replaceWith(groups);
+});
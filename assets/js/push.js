@@ -0,0 +1,44 @@
+// subscribes the browser to notifications of breaking news, see sw.js for how they are shown
+const button = document.getElementById("push");
+
+const urlBase64ToUint8Array = (base64) => {
+  const padding = "=".repeat((4 - (base64.length % 4)) % 4);
+  const raw = atob((base64 + padding).replace(/-/g, "+").replace(/_/g, "/"));
+  return Uint8Array.from(raw, (char) => char.charCodeAt(0));
+};
+
+const render = (subscription) => {
+  button.textContent = subscription
+    ? "Stop notifying me of breaking news"
+    : "Notify me of breaking news";
+  button.hidden = false;
+};
+
+if (button && "serviceWorker" in navigator && "PushManager" in window) {
+  navigator.serviceWorker.register("/sw.js").then(async (registration) => {
+    render(await registration.pushManager.getSubscription());
+    button.addEventListener("click", async () => {
+      const current = await registration.pushManager.getSubscription();
+      if (current) {
+        await fetch("/push/subscriptions", {
+          method: "DELETE",
+          headers: { "Content-Type": "application/json" },
+          body: JSON.stringify({ endpoint: current.endpoint }),
+        });
+        await current.unsubscribe();
+        render(null);
+        return;
+      }
+      const subscription = await registration.pushManager.subscribe({
+        userVisibleOnly: true,
+        applicationServerKey: urlBase64ToUint8Array(button.dataset.vapidPublicKey),
+      });
+      const response = await fetch("/push/subscriptions", {
+        method: "POST",
+        headers: { "Content-Type": "application/json" },
+        body: JSON.stringify(subscription),
+      });
+      render(response.ok ? subscription : null);
+    });
+  });
+}
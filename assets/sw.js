@@ -0,0 +1,16 @@
+// shows notifications of breaking news pushed by the server, and opens their stories on click
+self.addEventListener("push", (event) => {
+  const notification = event.data ? event.data.json() : null;
+  if (!notification) return;
+  event.waitUntil(
+    self.registration.showNotification(notification.title, {
+      body: notification.body,
+      data: { url: notification.url },
+    }),
+  );
+});
+
+self.addEventListener("notificationclick", (event) => {
+  event.notification.close();
+  event.waitUntil(self.clients.openWindow(event.notification.data.url));
+});
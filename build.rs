@@ -0,0 +1,5 @@
+fn main() {
+    // protoc must be installed to build with the grpc feature
+    #[cfg(feature = "grpc")]
+    tonic_build::compile_protos("proto/news.proto").expect("failed to compile proto/news.proto");
+}
@@ -0,0 +1,3 @@
+/* region of local news, for example stockholm */
+ALTER TABLE entries
+    ADD COLUMN region text;
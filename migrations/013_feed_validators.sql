@@ -0,0 +1,8 @@
+CREATE TABLE IF NOT EXISTS feed_validators (
+    id integer PRIMARY KEY AUTOINCREMENT,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    /* --- */
+    href text NOT NULL UNIQUE,
+    etag text,
+    last_modified text
+);
@@ -0,0 +1,7 @@
+CREATE TABLE IF NOT EXISTS feed_fetches (
+    id integer PRIMARY KEY AUTOINCREMENT,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    /* --- */
+    feed_id integer NOT NULL,
+    error text
+);
@@ -0,0 +1,5 @@
+ALTER TABLE feed_fetches
+    ADD COLUMN duration_ms INTEGER NOT NULL DEFAULT 0;
+
+ALTER TABLE feed_fetches
+    ADD COLUMN entries_count INTEGER NOT NULL DEFAULT 0;
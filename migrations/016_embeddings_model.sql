@@ -0,0 +1,25 @@
+CREATE TABLE embeddings_with_model (
+    id integer PRIMARY KEY AUTOINCREMENT,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    /* --- */
+    md5_hash BINARY NOT NULL,
+    value text NOT NULL,
+    size integer NOT NULL,
+    model text NOT NULL,
+    UNIQUE (md5_hash, model)
+);
+
+INSERT INTO embeddings_with_model (id, created_at, md5_hash, value, size, model)
+SELECT
+    id,
+    created_at,
+    md5_hash,
+    value,
+    size,
+    'text-embedding-3-large'
+FROM
+    embeddings;
+
+DROP TABLE embeddings;
+
+ALTER TABLE embeddings_with_model RENAME TO embeddings;
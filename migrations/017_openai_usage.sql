@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS openai_usage (
+    id integer PRIMARY KEY AUTOINCREMENT,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    /* --- */
+    model text NOT NULL,
+    prompt_tokens integer NOT NULL,
+    completion_tokens integer NOT NULL,
+    cost_usd real NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS openai_usage_created_at ON openai_usage (created_at);
@@ -0,0 +1,2 @@
+ALTER TABLE reports
+    ADD COLUMN algorithm TEXT NOT NULL DEFAULT 'dbscan';
@@ -0,0 +1,4 @@
+ALTER TABLE reports
+    ADD COLUMN tolerance_method TEXT;
+
+UPDATE reports SET tolerance_method = 'grid_search' WHERE algorithm = 'dbscan';
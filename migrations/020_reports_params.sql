@@ -0,0 +1,8 @@
+ALTER TABLE reports
+    ADD COLUMN tolerance_range_start REAL NOT NULL DEFAULT 0.9;
+
+ALTER TABLE reports
+    ADD COLUMN tolerance_range_end REAL NOT NULL DEFAULT 1.1;
+
+ALTER TABLE reports
+    ADD COLUMN samples INTEGER NOT NULL DEFAULT 50;
@@ -0,0 +1,8 @@
+ALTER TABLE reports
+    ADD COLUMN score_metric TEXT NOT NULL DEFAULT 'silhouette';
+
+ALTER TABLE reports
+    ADD COLUMN davies_bouldin REAL;
+
+ALTER TABLE reports
+    ADD COLUMN calinski_harabasz REAL;
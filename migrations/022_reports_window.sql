@@ -0,0 +1,5 @@
+ALTER TABLE reports
+    ADD COLUMN window_start TIMESTAMP;
+
+ALTER TABLE reports
+    ADD COLUMN window_end TIMESTAMP;
@@ -0,0 +1,7 @@
+CREATE TABLE IF NOT EXISTS report_group_lineage (
+    report_group_id integer NOT NULL,
+    parent_report_group_id integer NOT NULL,
+    /* number of embeddings both groups have */
+    overlap integer NOT NULL,
+    PRIMARY KEY (report_group_id, parent_report_group_id)
+);
@@ -0,0 +1,7 @@
+CREATE TABLE IF NOT EXISTS group_titles (
+    report_group_id integer NOT NULL,
+    lang_code text NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    value text NOT NULL,
+    PRIMARY KEY (report_group_id, lang_code)
+);
@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS group_summaries (
+    /* md5 hash of the descriptions the summary was written from */
+    md5_hash blob NOT NULL,
+    lang_code text NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    value text NOT NULL,
+    PRIMARY KEY (md5_hash, lang_code)
+);
+
+ALTER TABLE report_groups
+    ADD COLUMN summary_md5_hash BLOB;
@@ -0,0 +1,2 @@
+ALTER TABLE report_groups
+    ADD COLUMN category TEXT;
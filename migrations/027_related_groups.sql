@@ -0,0 +1,7 @@
+CREATE TABLE IF NOT EXISTS related_groups (
+    report_group_id integer NOT NULL,
+    related_report_group_id integer NOT NULL,
+    /* distance between centroids of the groups */
+    distance real NOT NULL,
+    PRIMARY KEY (report_group_id, related_report_group_id)
+);
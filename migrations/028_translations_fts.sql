@@ -0,0 +1,22 @@
+CREATE VIRTUAL TABLE IF NOT EXISTS translations_fts USING fts5(
+    value,
+    content = 'translations',
+    content_rowid = 'id',
+    /* swedish letters are not the same as their base letters */
+    tokenize = 'unicode61 remove_diacritics 0'
+);
+
+INSERT INTO translations_fts (translations_fts) VALUES ('rebuild');
+
+CREATE TRIGGER IF NOT EXISTS translations_fts_insert AFTER INSERT ON translations BEGIN
+    INSERT INTO translations_fts (rowid, value) VALUES (new.id, new.value);
+END;
+
+CREATE TRIGGER IF NOT EXISTS translations_fts_delete AFTER DELETE ON translations BEGIN
+    INSERT INTO translations_fts (translations_fts, rowid, value) VALUES ('delete', old.id, old.value);
+END;
+
+CREATE TRIGGER IF NOT EXISTS translations_fts_update AFTER UPDATE ON translations BEGIN
+    INSERT INTO translations_fts (translations_fts, rowid, value) VALUES ('delete', old.id, old.value);
+    INSERT INTO translations_fts (rowid, value) VALUES (new.id, new.value);
+END;
@@ -0,0 +1,26 @@
+/* values are converted from json text to little-endian f32 blobs when the database is opened */
+CREATE TABLE embeddings_blob (
+    id integer PRIMARY KEY AUTOINCREMENT,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    /* --- */
+    md5_hash BINARY NOT NULL,
+    value blob NOT NULL,
+    size integer NOT NULL,
+    model text NOT NULL,
+    UNIQUE (md5_hash, model)
+);
+
+INSERT INTO embeddings_blob (id, created_at, md5_hash, value, size, model)
+SELECT
+    id,
+    created_at,
+    md5_hash,
+    value,
+    size,
+    model
+FROM
+    embeddings;
+
+DROP TABLE embeddings;
+
+ALTER TABLE embeddings_blob RENAME TO embeddings;
@@ -0,0 +1,6 @@
+ALTER TABLE embeddings
+    ADD COLUMN precision TEXT NOT NULL DEFAULT 'f32';
+
+/* int8 values are multiplied by it */
+ALTER TABLE embeddings
+    ADD COLUMN scale REAL;
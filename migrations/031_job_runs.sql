@@ -0,0 +1,19 @@
+CREATE TABLE IF NOT EXISTS job_runs (
+    id integer PRIMARY KEY AUTOINCREMENT,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    /* --- */
+    name text NOT NULL,
+    started_at DATETIME NOT NULL,
+    finished_at DATETIME NOT NULL,
+    /* durations of the stages, null when a stage did not run */
+    crawl_ms integer,
+    embed_ms integer,
+    report_ms integer,
+    /* new entries, embeddings, and groups of the report */
+    entries_count integer,
+    embeddings_count integer,
+    groups_count integer,
+    error text
+);
+
+CREATE INDEX IF NOT EXISTS job_runs_name_id ON job_runs (name, id);
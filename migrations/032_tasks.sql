@@ -0,0 +1,17 @@
+CREATE TABLE IF NOT EXISTS tasks (
+    id integer PRIMARY KEY AUTOINCREMENT,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    /* --- */
+    /* embed, translate or summarize */
+    kind text NOT NULL,
+    /* field to embed or translate, or report group to summarize */
+    target_id integer NOT NULL,
+    /* pending, done or failed */
+    status text NOT NULL DEFAULT 'pending',
+    attempts integer NOT NULL DEFAULT 0,
+    error text,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (kind, target_id)
+);
+
+CREATE INDEX IF NOT EXISTS tasks_kind_status ON tasks (kind, status);
@@ -0,0 +1,6 @@
+/* locks held by one of the instances sharing the database, until released or expired */
+CREATE TABLE IF NOT EXISTS leases (
+    name text PRIMARY KEY,
+    holder text NOT NULL,
+    expires_at DATETIME NOT NULL
+);
@@ -0,0 +1,3 @@
+/* when the group was found to be growing rapidly, null if it was not */
+ALTER TABLE report_groups
+    ADD COLUMN breaking_at DATETIME;
@@ -0,0 +1,12 @@
+/* values fields of known entries changed to after they were first crawled */
+CREATE TABLE IF NOT EXISTS field_revisions (
+    id integer PRIMARY KEY AUTOINCREMENT,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    /* --- */
+    entry_id integer NOT NULL,
+    name text NOT NULL,
+    lang_code text NOT NULL,
+    md5_hash BINARY NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS field_revisions_entry_id_name_lang_code ON field_revisions (entry_id, name, lang_code);
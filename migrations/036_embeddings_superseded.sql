@@ -0,0 +1,4 @@
+/* when no field had the embedded text anymore, because a newer revision of the description
+   replaced it. field revisions keep the first value of the revised fields from now on */
+ALTER TABLE embeddings
+    ADD COLUMN superseded_at DATETIME;
@@ -0,0 +1,11 @@
+/* hrefs of entries are canonicalized when the database is opened, entries that turn out to be
+   the same are merged into the earliest of them */
+CREATE TABLE IF NOT EXISTS entries_to_canonicalize (
+    entry_id integer PRIMARY KEY
+);
+
+INSERT INTO entries_to_canonicalize (entry_id)
+SELECT
+    id
+FROM
+    entries;
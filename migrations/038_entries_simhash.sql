@@ -0,0 +1,7 @@
+/* fingerprint of the normalized description, and the earlier entry of another feed the entry is
+   a near-duplicate of, if any */
+ALTER TABLE entries
+    ADD COLUMN simhash integer;
+
+ALTER TABLE entries
+    ADD COLUMN duplicate_of integer;
@@ -0,0 +1,8 @@
+CREATE TABLE IF NOT EXISTS robots_txts (
+    id integer PRIMARY KEY AUTOINCREMENT,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    /* --- */
+    origin text NOT NULL UNIQUE,
+    body text NOT NULL,
+    fetched_at DATETIME NOT NULL
+);
@@ -0,0 +1,3 @@
+/* picture of the article, from the feed or the article page */
+ALTER TABLE entries
+    ADD COLUMN image_href text;
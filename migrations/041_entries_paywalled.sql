@@ -0,0 +1,6 @@
+/* article of the entry is behind a paywall, according to its feed or its page */
+ALTER TABLE entries
+    ADD COLUMN paywalled integer NOT NULL DEFAULT 0;
+
+/* entries of feeds that are paywalled: svd, ntm papers, hbl and bonnier papers */
+UPDATE entries SET paywalled = 1 WHERE feed_id IN (3, 15, 16, 17, 18, 19, 20, 23, 24, 25);
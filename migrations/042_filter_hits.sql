@@ -0,0 +1,11 @@
+/* crawled entries that were skipped because they mention a blocked keyword */
+CREATE TABLE IF NOT EXISTS filter_hits (
+    id integer PRIMARY KEY AUTOINCREMENT,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    /* --- */
+    feed_id integer NOT NULL,
+    href text NOT NULL,
+    keyword text NOT NULL,
+    title text NOT NULL,
+    UNIQUE (href, keyword)
+);
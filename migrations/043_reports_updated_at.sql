@@ -0,0 +1,4 @@
+/* when groups of the report last changed, reports are updated after they are created with
+   headlines and summaries, and with new entries by incremental clustering */
+ALTER TABLE reports
+    ADD COLUMN updated_at DATETIME;
@@ -0,0 +1,7 @@
+/* social networks groups were announced on, groups are announced once per network */
+CREATE TABLE IF NOT EXISTS report_group_posts (
+    report_group_id integer NOT NULL,
+    network text NOT NULL,
+    posted_at DATETIME NOT NULL,
+    PRIMARY KEY (report_group_id, network)
+);
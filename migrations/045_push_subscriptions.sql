@@ -0,0 +1,7 @@
+/* browsers that subscribed to notifications of breaking news */
+CREATE TABLE IF NOT EXISTS push_subscriptions (
+    endpoint text NOT NULL PRIMARY KEY,
+    p256dh text NOT NULL,
+    auth text NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,6 @@
+/* spoken summaries of the top groups of a day, as mp3 */
+CREATE TABLE IF NOT EXISTS briefings (
+    date DATE NOT NULL PRIMARY KEY,
+    audio BLOB NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
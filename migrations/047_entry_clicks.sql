@@ -0,0 +1,7 @@
+/* clicks on links to articles, counted per day without anything about who clicked */
+CREATE TABLE IF NOT EXISTS entry_clicks (
+    entry_id integer NOT NULL,
+    date DATE NOT NULL,
+    count integer NOT NULL DEFAULT 0,
+    PRIMARY KEY (entry_id, date)
+);
@@ -0,0 +1,2 @@
+/* what the task was done for, done tasks are queued again when it changes */
+ALTER TABLE tasks ADD COLUMN input text;
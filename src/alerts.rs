@@ -0,0 +1,49 @@
+use crate::{clustering, id::Id};
+
+#[derive(Clone)]
+pub struct Client {
+    http_client: reqwest::Client,
+    webhook_url: url::Url,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct Alert {
+    /// human readable summary, `text` is understood by most chat webhooks
+    pub text: String,
+    pub feed: String,
+    pub consecutive_failures: u32,
+    pub error: String,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct Breaking {
+    pub text: String,
+    pub group_id: Id<clustering::ReportGroup>,
+    pub title: String,
+    pub url: url::Url,
+    pub sources: u32,
+}
+
+impl Client {
+    pub fn new(webhook_url: &url::Url) -> Self {
+        Self {
+            http_client: reqwest::Client::new(),
+            webhook_url: webhook_url.clone(),
+        }
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn send(
+        &self,
+        alert: &(impl serde::Serialize + std::fmt::Debug + Sync),
+    ) -> Result<(), Box<dyn std::error::Error + 'static + Send + Sync>> {
+        self.http_client
+            .post(self.webhook_url.clone())
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(serde_json::to_string(alert)?)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
@@ -1,11 +1,128 @@
-use crate::{clustering, db, feeds, id::Id, md5_hash, normalizer::normalize_sv, openai};
+use crate::{
+    alerts, clustering, db, feeds, filters, id::Id, md5_hash, normalizer::normalize_sv, openai,
+    persisted::Persisted, providers, push, quantization, shutdown, simhash, social,
+};
+
+pub mod tasks;
+
+#[derive(Clone)]
+pub struct Config {
+    pub svt_regions: Vec<String>,
+    pub crawler: feeds::http::Config,
+    pub article_images: bool,
+    pub paywall_heuristics: bool,
+    pub keywords: filters::Keywords,
+    pub alerts_client: Option<alerts::Client>,
+    pub alert_after_failures: u32,
+    pub openai_concurrency: usize,
+    pub translation_batch_size: usize,
+    pub translation_languages: Vec<feeds::LanguageCode>,
+    pub clustering: clustering::Params,
+    pub incremental_clustering: bool,
+    pub clustering_window: Option<chrono::Duration>,
+    pub group_enrichments: Vec<Enrichment>,
+    pub breaking_sources: usize,
+    pub breaking_window: chrono::Duration,
+    pub breaking_client: Option<alerts::Client>,
+    pub push_client: Option<push::Client>,
+    pub posters: Vec<social::Poster>,
+    pub announce_sources: usize,
+    pub content_warnings: Vec<clustering::Category>,
+    pub base_url: url::Url,
+    pub embedding_precision: quantization::Precision,
+    pub instance_id: String,
+    pub briefing_client: Option<openai::Client>,
+    pub report_updates: Option<std::sync::Arc<ReportUpdates>>,
+}
+
+pub type ReportUpdates = tokio::sync::watch::Sender<Option<chrono::DateTime<chrono::Utc>>>;
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct JobRun {
+    pub name: String,
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    pub finished_at: chrono::DateTime<chrono::Utc>,
+    pub crawl_ms: Option<u32>,
+    pub embed_ms: Option<u32>,
+    pub report_ms: Option<u32>,
+    pub entries_count: Option<u32>,
+    pub embeddings_count: Option<u32>,
+    pub groups_count: Option<u32>,
+    pub error: Option<String>,
+}
+
+impl JobRun {
+    fn new(name: &str) -> Self {
+        let now = chrono::Utc::now();
+        Self {
+            name: name.to_string(),
+            started_at: now,
+            finished_at: now,
+            crawl_ms: None,
+            embed_ms: None,
+            report_ms: None,
+            entries_count: None,
+            embeddings_count: None,
+            groups_count: None,
+            error: None,
+        }
+    }
+
+    /// records the end of the run, errors of the database are logged and ignored so that they do
+    /// not hide the result of the run.
+    async fn finish<T>(mut self, db: &db::Client, result: &Result<T, Error>) {
+        self.finished_at = chrono::Utc::now();
+        self.error = result.as_ref().err().map(ToString::to_string);
+        if let Err(error) = db.insert_job_run(&self).await {
+            tracing::error!(%error, name = self.name, "failed to record job run");
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Enrichment {
+    /// neutral headline of every new group
+    Headline,
+    /// short summary of every group whose entries changed
+    Summary,
+    /// category of every new group
+    Category,
+    /// headline and summary in easy swedish of every group whose entries changed, see
+    /// `/lattlast`
+    Lattlast,
+}
 
 pub async fn run(
     db: db::Client,
-    openai_client: openai::Client,
+    embedder: std::sync::Arc<dyn providers::Embedder>,
+    translator: std::sync::Arc<dyn providers::Translator>,
+    summarizer: Option<std::sync::Arc<dyn providers::Summarizer>>,
+    config: Config,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let executor = lightspeed_scheduler::JobExecutor::new_with_utc_tz();
 
+    let maintenance_db = db.clone();
+    executor
+        .add_job_with_scheduler(
+            lightspeed_scheduler::scheduler::Scheduler::Interval {
+                interval_duration: std::time::Duration::from_secs(24 * 60 * 60),
+                execute_at_startup: false,
+            },
+            lightspeed_scheduler::job::Job::new("background", "maintain", None, move || {
+                let db = maintenance_db.clone();
+                Box::pin(async move {
+                    let run = JobRun::new("maintain");
+                    let result = maintain(&db).await;
+                    run.finish(&db, &result).await;
+                    result.map_err(|error| {
+                        tracing::error!("background maintenance failed: {}", error);
+                        error
+                    })
+                })
+            }),
+        )
+        .await;
+
     executor
         .add_job_with_scheduler(
             lightspeed_scheduler::scheduler::Scheduler::Interval {
@@ -14,9 +131,20 @@ pub async fn run(
             },
             lightspeed_scheduler::job::Job::new("background", "fetch", None, move || {
                 let db = db.clone();
-                let openai_client = openai_client.clone();
+                let embedder = embedder.clone();
+                let translator = translator.clone();
+                let summarizer = summarizer.clone();
+                let config = config.clone();
                 Box::pin(async move {
-                    fetch(&db, &openai_client).await.map_err(|error| {
+                    fetch(
+                        &db,
+                        embedder.as_ref(),
+                        translator.as_ref(),
+                        summarizer.as_deref(),
+                        &config,
+                    )
+                    .await
+                    .map_err(|error| {
                         tracing::error!("background fetch failed: {}", error);
                         error
                     })
@@ -27,189 +155,1453 @@ pub async fn run(
 
     executor.run().await?;
 
+    shutdown::signal().await;
+    // waits for the running job, so that reports are not left half-written
+    executor.stop(true).await?;
+
     Ok(())
 }
 
 type Error = Box<dyn std::error::Error + Send + Sync + 'static>;
 
+type CrawlResult = Result<Vec<feeds::CrawledEntry>, Error>;
+
+const FETCH_LEASE: &str = "fetch";
+
+const FETCH_LEASE_TTL: chrono::Duration = chrono::Duration::hours(1);
+
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn fetch(
+    db: &db::Client,
+    embedder: &dyn providers::Embedder,
+    translator: &dyn providers::Translator,
+    summarizer: Option<&dyn providers::Summarizer>,
+    config: &Config,
+) -> Result<(), Error> {
+    // only one of the instances sharing the database crawls and writes reports at a time
+    let expires_at = chrono::Utc::now() + FETCH_LEASE_TTL;
+    if !db
+        .acquire_lease(FETCH_LEASE, &config.instance_id, expires_at)
+        .await?
+    {
+        tracing::info!("another instance is fetching, skipping");
+        return Ok(());
+    }
+
+    let mut run = JobRun::new("fetch");
+    let result = fetch_stages(db, embedder, translator, summarizer, config, &mut run).await;
+    run.finish(db, &result).await;
+    db.release_lease(FETCH_LEASE, &config.instance_id).await?;
+
+    match result {
+        Err(error) if error.is::<openai::BudgetExceeded>() => {
+            tracing::error!(%error, "skipping embeddings and translations until tomorrow");
+            Ok(())
+        }
+        result => result,
+    }
+}
+
+#[tracing::instrument(level = "debug", skip_all)]
+async fn maintain(db: &db::Client) -> Result<(), Error> {
+    let embeddings = db.delete_orphaned_embeddings().await?;
+    let translations = db.delete_orphaned_translations().await?;
+    let group_summaries = db.delete_orphaned_group_summaries().await?;
+    tracing::info!(
+        embeddings,
+        translations,
+        group_summaries,
+        "deleted orphaned rows"
+    );
+
+    db.optimize().await?;
+
+    Ok(())
+}
+
+/// groups entries published between `from` and `to`, inclusive, anew. reports are dated at the
+/// end of their days, so that they replace the latest reports of the dates.
+#[tracing::instrument(level = "debug", skip(db, embedder, translator, summarizer, config))]
+pub async fn recluster(
+    db: &db::Client,
+    embedder: &dyn providers::Embedder,
+    translator: &dyn providers::Translator,
+    summarizer: Option<&dyn providers::Summarizer>,
+    config: &Config,
+    from: chrono::NaiveDate,
+    to: chrono::NaiveDate,
+) -> Result<(), Error> {
+    let config = Config {
+        incremental_clustering: false,
+        ..config.clone()
+    };
+    for date in from.iter_days().take_while(|date| *date <= to) {
+        tracing::info!(%date, "reclustering");
+        let end_of_day = date
+            .and_hms_opt(23, 59, 59)
+            .expect("failed to create end of day")
+            .and_utc();
+        let now = end_of_day.min(chrono::Utc::now());
+
+        generate_embeddings(db, embedder, &config, &date).await?;
+        generate_report(db, embedder, translator, summarizer, &config, now).await?;
+    }
+    Ok(())
+}
+
+async fn fetch_stages(
+    db: &db::Client,
+    embedder: &dyn providers::Embedder,
+    translator: &dyn providers::Translator,
+    summarizer: Option<&dyn providers::Summarizer>,
+    config: &Config,
+    run: &mut JobRun,
+) -> Result<(), Error> {
+    let start = std::time::Instant::now();
+    run.entries_count = Some(to_u32(crawl(db, config).await?));
+    run.crawl_ms = Some(elapsed_ms(start));
+
+    let now = chrono::Utc::now();
+    let start = std::time::Instant::now();
+    run.embeddings_count = Some(to_u32(
+        generate_embeddings(db, embedder, config, &now.date_naive()).await?,
+    ));
+    run.embed_ms = Some(elapsed_ms(start));
+
+    let start = std::time::Instant::now();
+    run.groups_count = Some(to_u32(
+        generate_report(db, embedder, translator, summarizer, config, now).await?,
+    ));
+    run.report_ms = Some(elapsed_ms(start));
+
+    if let Some(briefing_client) = &config.briefing_client {
+        if let Err(error) = generate_briefing(db, config, briefing_client, now).await {
+            // the previous briefing is kept
+            tracing::error!(%error, "failed to generate briefing");
+        }
+    }
+
+    Ok(())
+}
+
+const BRIEFING_INTERVAL: chrono::Duration = chrono::Duration::hours(3);
+
+const BRIEFING_GROUPS: usize = 5;
+
 #[tracing::instrument(level = "debug", skip_all)]
-async fn fetch(db: &db::Client, openai_client: &openai::Client) -> Result<(), Error> {
-    crawl(db).await?;
-    generate_embeddings(db, openai_client).await?;
-    generate_report(db, openai_client).await?;
+async fn generate_briefing(
+    db: &db::Client,
+    config: &Config,
+    briefing_client: &openai::Client,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Result<(), Error> {
+    let date = now.date_naive();
+    let created_at = db.find_briefing_created_at_by_date(date).await?;
+    if created_at.is_some_and(|created_at| now - created_at < BRIEFING_INTERVAL) {
+        return Ok(());
+    }
+    let Some(report) = db.find_latest_report().await? else {
+        return Ok(());
+    };
+
+    let entries = db
+        .list_report_group_entries_by_date_lang_code(date, &feeds::LanguageCode::EN)
+        .await?;
+    let groups = entries.iter().fold(
+        std::collections::BTreeMap::<Id<clustering::ReportGroup>, (usize, Option<String>)>::new(),
+        |mut groups, entry| {
+            let (size, title) = groups.entry(entry.group_id).or_default();
+            *size += 1;
+            if entry.is_center {
+                *title = Some(entry.group_title.as_ref().unwrap_or(&entry.title).clone());
+            }
+            groups
+        },
+    );
+    let mut groups = groups
+        .into_values()
+        .filter_map(|(size, title)| title.map(|title| (size, title)))
+        .collect::<Vec<_>>();
+    if groups.is_empty() {
+        return Ok(());
+    }
+    groups.sort_by_key(|(size, _)| std::cmp::Reverse(*size));
+
+    let script = std::iter::once(format!("Today in Sweden, {}.", date.format("%A, %B %-d")))
+        .chain(
+            groups
+                .iter()
+                .take(BRIEFING_GROUPS)
+                .map(|(_, title)| format!("{}.", title.trim().trim_end_matches('.'))),
+        )
+        .chain(std::iter::once("That's all for now.".to_string()))
+        .collect::<Vec<_>>()
+        .join("\n\n");
 
+    let audio = briefing_client.speech(&script).await?;
+    db.upsert_briefing(date, &audio).await?;
+    mark_report_updated(db, config, report.id).await?;
+    tracing::info!(%date, bytes = audio.len(), "generated briefing");
+    Ok(())
+}
+
+fn to_u32(value: usize) -> u32 {
+    value.try_into().expect("usize -> u32 failed")
+}
+
+fn elapsed_ms(start: std::time::Instant) -> u32 {
+    start
+        .elapsed()
+        .as_millis()
+        .try_into()
+        .expect("u128 -> u32 failed")
+}
+
+#[tracing::instrument(level = "debug", skip(db, embedder, config))]
+pub async fn reembed(
+    db: &db::Client,
+    embedder: &dyn providers::Embedder,
+    config: &Config,
+    from: chrono::NaiveDate,
+    to: chrono::NaiveDate,
+) -> Result<(), Error> {
+    for date in from.iter_days().take_while(|date| *date <= to) {
+        tracing::info!(%date, model = embedder.model(), "reembedding");
+        generate_embeddings(db, embedder, config, &date).await?;
+    }
     Ok(())
 }
 
 #[tracing::instrument(level = "debug", skip_all)]
-async fn crawl(db: &db::Client) -> Result<(), Error> {
-    let http_client = reqwest::ClientBuilder::new()
-        .user_agent("svergie news crawler")
-        .build()?;
-
-    let (
-        abc_entries,
-        dagen_entries,
-        aftonbladet_entries,
-        dn_entries,
-        expressen_entries,
-        nkpg_entries,
-        scaraborgs_entries,
-        svd_entries,
-        svt_entries,
-        tv4_entries,
-    ) = futures::try_join!(
-        feeds::abc::crawl(&http_client),
-        feeds::aftonbladet::crawl(&http_client),
-        feeds::dagen::crawl(&http_client),
-        feeds::dn::crawl(&http_client),
-        feeds::expressen::crawl(&http_client),
-        feeds::nkpg::crawl(&http_client),
-        feeds::scaraborgs::crawl(&http_client),
-        feeds::svd::crawl(&http_client),
-        feeds::svt::crawl(&http_client),
-        feeds::tv4::crawl(&http_client),
-    )?;
+async fn crawl(db: &db::Client, config: &Config) -> Result<usize, Error> {
+    let http_client = feeds::http::Client::new(
+        reqwest::ClientBuilder::new()
+            .user_agent(feeds::http::USER_AGENT)
+            .build()?,
+        db.clone(),
+        config.crawler.clone(),
+    );
 
-    let entries = []
-        .into_iter()
-        .chain(abc_entries.into_iter())
-        .chain(aftonbladet_entries.into_iter())
-        .chain(dagen_entries.into_iter())
-        .chain(dn_entries.into_iter())
-        .chain(expressen_entries.into_iter())
-        .chain(nkpg_entries.into_iter())
-        .chain(scaraborgs_entries.into_iter())
-        .chain(svd_entries.into_iter())
-        .chain(svt_entries.into_iter())
-        .chain(tv4_entries.into_iter());
-
-    for (entry, fields) in entries {
-        if let Some(entry) = db.insert_entry(&entry).await? {
-            let fields = fields.into_iter().map(|(name, lang_code, value)| {
-                let md5_hash = md5_hash::compute(&value);
-                (
-                    feeds::Field {
-                        entry_id: entry.id,
-                        name,
-                        lang_code,
-                        md5_hash,
-                    },
-                    feeds::Translation {
-                        value: value.to_string(),
-                        md5_hash,
-                    },
-                )
-            });
-
-            for (sv_field, sv_translation) in fields {
-                futures::try_join!(
-                    db.insert_field(sv_field),
-                    db.insert_translation(sv_translation)
-                )?;
+    let mut crawls: Vec<(Id<feeds::Feed>, futures::future::BoxFuture<'_, CrawlResult>)> = vec![
+        (
+            feeds::abc::FEED.id,
+            Box::pin(feeds::abc::crawl(&http_client)),
+        ),
+        (
+            feeds::aftonbladet::FEED.id,
+            Box::pin(feeds::aftonbladet::crawl(&http_client)),
+        ),
+        (
+            feeds::dagen::FEED.id,
+            Box::pin(feeds::dagen::crawl(&http_client)),
+        ),
+        (feeds::dn::FEED.id, Box::pin(feeds::dn::crawl(&http_client))),
+        (
+            feeds::expressen::FEED.id,
+            Box::pin(feeds::expressen::crawl(&http_client)),
+        ),
+        (
+            feeds::hbl::FEED.id,
+            Box::pin(feeds::hbl::crawl(&http_client)),
+        ),
+        (
+            feeds::nkpg::FEED.id,
+            Box::pin(feeds::nkpg::crawl(&http_client)),
+        ),
+        (
+            feeds::nyheteridag::FEED.id,
+            Box::pin(feeds::nyheteridag::crawl(&http_client)),
+        ),
+        (
+            feeds::scaraborgs::FEED.id,
+            Box::pin(feeds::scaraborgs::crawl(&http_client)),
+        ),
+        (
+            feeds::svd::FEED.id,
+            Box::pin(feeds::svd::crawl(&http_client)),
+        ),
+        (
+            feeds::svt::FEED.id,
+            Box::pin(feeds::svt::crawl(&http_client)),
+        ),
+        (
+            feeds::thelocal::FEED.id,
+            Box::pin(feeds::thelocal::crawl(&http_client)),
+        ),
+        (feeds::tt::FEED.id, Box::pin(feeds::tt::crawl(&http_client))),
+        (
+            feeds::tv4::FEED.id,
+            Box::pin(feeds::tv4::crawl(&http_client)),
+        ),
+    ];
+    for feed in feeds::bonnier::FEEDS.iter() {
+        crawls.push((
+            feed.id,
+            Box::pin(feeds::bonnier::crawl(&http_client, feed.id)),
+        ));
+    }
+    for feed in feeds::ntm::FEEDS.iter() {
+        crawls.push((feed.id, Box::pin(feeds::ntm::crawl(&http_client, feed.id))));
+    }
+    for region in &config.svt_regions {
+        crawls.push((
+            feeds::svt_lokalt::FEED.id,
+            Box::pin(feeds::svt_lokalt::crawl(&http_client, region)),
+        ));
+    }
+
+    // a single broken feed should not prevent others from being crawled
+    let results =
+        futures::future::join_all(crawls.into_iter().map(|(feed_id, crawl)| async move {
+            let start = std::time::Instant::now();
+            let result = crawl.await;
+            (feed_id, start.elapsed(), result)
+        }))
+        .await;
+
+    let mut entries = vec![];
+    for (feed_id, duration, result) in results {
+        let duration_ms = duration.as_millis().try_into().expect("u128 -> u32 failed");
+        match result {
+            Ok(feed_entries) => {
+                db.insert_feed_fetch(&feeds::Fetch {
+                    feed_id,
+                    duration_ms,
+                    entries_count: feed_entries.len().try_into().expect("usize -> u32 failed"),
+                    error: None,
+                })
+                .await?;
+                entries.extend(feed_entries);
+            }
+            Err(error) => {
+                tracing::error!(%feed_id, %error, "failed to crawl feed");
+                db.insert_feed_fetch(&feeds::Fetch {
+                    feed_id,
+                    duration_ms,
+                    entries_count: 0,
+                    error: Some(error.to_string()),
+                })
+                .await?;
+                alert_on_failures(db, config, feed_id, &error.to_string()).await?;
+            }
+        }
+    }
+
+    let entries = filter_entries(db, &config.keywords, entries).await?;
+    let inserted = db.insert_entries_batch(&entries).await?;
+    let revised = db.insert_field_revisions(&entries).await?;
+    mark_duplicates(db, &entries, &inserted).await?;
+    flag_paywalled(db, &inserted).await?;
+    inspect_article_pages(db, &http_client, config, &inserted).await?;
+    tracing::debug!(
+        crawled = entries.len(),
+        inserted = inserted.len(),
+        revised,
+        "inserted entries"
+    );
+
+    http_client.save_validators().await?;
+
+    Ok(inserted.len())
+}
+
+/// returns entries that do not mention blocked keywords, and records the skipped ones so that
+/// they can be reviewed. skipped entries are not stored, so they are crawled anew once the
+/// keywords change.
+#[tracing::instrument(level = "debug", skip_all, fields(entries = entries.len()))]
+async fn filter_entries(
+    db: &db::Client,
+    keywords: &filters::Keywords,
+    entries: Vec<feeds::CrawledEntry>,
+) -> Result<Vec<feeds::CrawledEntry>, Error> {
+    let mut hits = vec![];
+    let mut kept = vec![];
+    for entry in entries {
+        match keywords.blocked_keyword(&entry) {
+            Some(keyword) => {
+                let (value, fields) = &entry;
+                let title = fields
+                    .iter()
+                    .find(|(name, _, _)| *name == feeds::FieldName::Title)
+                    .map(|(_, _, title)| title.clone())
+                    .unwrap_or_default();
+                hits.push(filters::Hit {
+                    feed_id: value.feed_id,
+                    href: value.href.clone(),
+                    keyword: keyword.to_string(),
+                    title,
+                });
             }
+            None => kept.push(entry),
+        }
+    }
+    if !hits.is_empty() {
+        tracing::debug!(skipped = hits.len(), "skipped entries by keywords");
+        db.insert_filter_hits(&hits).await?;
+    }
+    Ok(kept)
+}
+
+#[tracing::instrument(level = "debug", skip_all, fields(inserted = inserted.len()))]
+async fn flag_paywalled(
+    db: &db::Client,
+    inserted: &[Persisted<feeds::Entry>],
+) -> Result<(), Error> {
+    for entry in inserted {
+        let paywalled = feeds::LIST
+            .iter()
+            .any(|feed| feed.id == entry.value.feed_id && feed.value.paywalled);
+        if paywalled {
+            db.update_entry_paywalled(entry.id, true).await?;
+        }
+    }
+    Ok(())
+}
+
+#[tracing::instrument(level = "debug", skip_all, fields(inserted = inserted.len()))]
+async fn inspect_article_pages(
+    db: &db::Client,
+    http_client: &feeds::http::Client,
+    config: &Config,
+    inserted: &[Persisted<feeds::Entry>],
+) -> Result<(), Error> {
+    let needs_image =
+        |entry: &Persisted<feeds::Entry>| config.article_images && entry.value.image_href.is_none();
+    // requests to the same host wait for each other in the client
+    let results = futures::future::join_all(
+        inserted
+            .iter()
+            .filter(|entry| needs_image(entry) || config.paywall_heuristics)
+            .map(|entry| async move {
+                let href = url::Url::from(entry.value.href.clone());
+                let body = http_client.get(entry.value.feed_id, href.as_str()).await;
+                (entry, href, body)
+            }),
+    )
+    .await;
+
+    let (mut images, mut paywalls) = (0, 0);
+    for (entry, href, body) in results {
+        let body = match body {
+            Ok(body) => String::from_utf8_lossy(&body).into_owned(),
+            Err(error) => {
+                tracing::debug!(%error, %href, "failed to fetch article page");
+                continue;
+            }
+        };
+        if needs_image(entry) {
+            if let Some(image_href) = feeds::og_image_href(&body, &href) {
+                db.update_entry_image_href(entry.id, &image_href).await?;
+                images += 1;
+            }
+        }
+        if config.paywall_heuristics {
+            if let Some(paywalled) = feeds::is_paywalled(&body) {
+                db.update_entry_paywalled(entry.id, paywalled).await?;
+                paywalls += 1;
+            }
+        }
+    }
+    tracing::debug!(images, paywalls, "inspected article pages");
+
+    Ok(())
+}
+
+const DUPLICATE_WINDOW: chrono::Duration = chrono::Duration::days(2);
+
+#[tracing::instrument(level = "debug", skip_all, fields(inserted = inserted.len()))]
+async fn mark_duplicates(
+    db: &db::Client,
+    entries: &[feeds::CrawledEntry],
+    inserted: &[Persisted<feeds::Entry>],
+) -> Result<(), Error> {
+    let mut inserted = inserted
+        .iter()
+        .filter_map(|entry| {
+            let href = entry.value.href.to_string();
+            let description = entries
+                .iter()
+                .find(|(crawled, _)| crawled.href.to_string() == href)?
+                .1
+                .iter()
+                .find(|(name, _, _)| *name == feeds::FieldName::Description)
+                .map(|(_, _, value)| value)?;
+            simhash::compute(&normalize_sv(description)).map(|simhash| (entry, simhash))
+        })
+        .collect::<Vec<_>>();
+    let Some(earliest) = inserted
+        .iter()
+        .map(|(entry, _)| entry.value.published_at)
+        .min()
+    else {
+        return Ok(());
+    };
+    inserted.sort_by_key(|(entry, _)| entry.value.published_at);
+
+    let mut originals = db
+        .list_original_entry_simhashes_by_published_at(
+            &(earliest - DUPLICATE_WINDOW..chrono::Utc::now()),
+        )
+        .await?;
+    let mut duplicates = 0;
+    for (entry, simhash) in inserted {
+        let duplicate_of = originals
+            .iter()
+            .find(|(id, feed_id, original)| {
+                *id != entry.id
+                    && *feed_id != entry.value.feed_id
+                    && simhash::distance(simhash, *original) <= simhash::MAX_DISTANCE
+            })
+            .map(|(id, _, _)| *id);
+        db.update_entry_simhash(entry.id, simhash, duplicate_of)
+            .await?;
+        match duplicate_of {
+            Some(_) => duplicates += 1,
+            None => originals.push((entry.id, entry.value.feed_id, simhash)),
         }
     }
+    tracing::debug!(duplicates, "marked duplicates");
+
+    Ok(())
+}
+
+#[tracing::instrument(level = "debug", skip(db, config))]
+async fn alert_on_failures(
+    db: &db::Client,
+    config: &Config,
+    feed_id: Id<feeds::Feed>,
+    error: &str,
+) -> Result<(), Error> {
+    let Some(alerts_client) = &config.alerts_client else {
+        return Ok(());
+    };
+
+    let consecutive_failures = db.count_consecutive_feed_failures(&feed_id).await?;
+    if consecutive_failures != config.alert_after_failures {
+        return Ok(());
+    }
+
+    let feed = feeds::LIST
+        .iter()
+        .find(|feed| feed.id == feed_id)
+        .expect("feed must exist");
+    let alert = alerts::Alert {
+        text: format!(
+            "{} failed {} times in a row: {}",
+            feed.value.title, consecutive_failures, error
+        ),
+        feed: feed.value.title.clone(),
+        consecutive_failures,
+        error: error.to_string(),
+    };
+    if let Err(error) = alerts_client.send(&alert).await {
+        tracing::error!(%error, "failed to send alert");
+    }
 
     Ok(())
 }
 
 #[tracing::instrument(level = "debug", skip_all)]
-async fn generate_embeddings(db: &db::Client, openai_client: &openai::Client) -> Result<(), Error> {
-    let translations_without_embeddings = db
-        .list_translations_without_embeddings_by_lang_code_field_name_date(
-            feeds::LanguageCode::SV,
-            feeds::FieldName::Description,
-            &chrono::Utc::now().date_naive(),
+async fn generate_embeddings(
+    db: &db::Client,
+    embedder: &dyn providers::Embedder,
+    config: &Config,
+    date: &chrono::NaiveDate,
+) -> Result<usize, Error> {
+    for lang_code in [feeds::LanguageCode::SV, feeds::LanguageCode::EN] {
+        let fields_without_embeddings = db
+            .list_fields_without_embeddings_by_lang_code_field_name_date_model(
+                lang_code,
+                feeds::FieldName::Description,
+                date,
+                embedder.model(),
+            )
+            .await?;
+        // fields are embedded again when their values or the model change
+        db.insert_tasks(
+            tasks::Kind::Embed,
+            &fields_without_embeddings
+                .iter()
+                .map(|field| {
+                    let input = format!("{}:{:?}", embedder.model(), field.value.md5_hash);
+                    (field.id.into(), Some(input))
+                })
+                .collect::<Vec<_>>(),
         )
         .await?;
+    }
+    tasks::run_embed(db, embedder, config).await
+}
 
-    for translation in translations_without_embeddings {
-        let text = normalize_sv(&translation.value.value);
-        let embedding = openai_client.embeddings(&text).await?;
+#[tracing::instrument(level = "debug", skip_all)]
+async fn generate_embedding(
+    db: &db::Client,
+    embedder: &dyn providers::Embedder,
+    precision: quantization::Precision,
+    lang_code: &feeds::LanguageCode,
+    translation: Persisted<feeds::Translation>,
+) -> Result<(), Error> {
+    let text = match lang_code {
+        feeds::LanguageCode::SV => normalize_sv(&translation.value.value),
+        _ => translation.value.value.clone(),
+    };
+    let embedding = embedder.embed(&text).await?;
 
-        db.insert_embeddig(&clustering::Embedding {
+    db.insert_embeddig(
+        &clustering::Embedding {
             md5_hash: translation.value.md5_hash,
             size: embedding
                 .len()
                 .try_into()
                 .expect("failed to convert usize into u32"),
             value: embedding,
-        })
-        .await?;
-    }
+            model: embedder.model().to_string(),
+        },
+        precision,
+    )
+    .await?;
+
     Ok(())
 }
 
 #[tracing::instrument(level = "debug", skip_all)]
-async fn generate_report(db: &db::Client, openai_client: &openai::Client) -> Result<(), Error> {
-    let today_title_embeddings = db
-        .list_embeddings_by_field_name_lang_code_date(
+async fn generate_report(
+    db: &db::Client,
+    embedder: &dyn providers::Embedder,
+    translator: &dyn providers::Translator,
+    summarizer: Option<&dyn providers::Summarizer>,
+    config: &Config,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Result<usize, Error> {
+    let window = if let Some(duration) = config.clustering_window {
+        now - duration..now
+    } else {
+        let start_of_day = now
+            .date_naive()
+            .and_hms_opt(0, 0, 0)
+            .expect("failed to create start of day")
+            .and_utc();
+        start_of_day..start_of_day + chrono::Duration::days(1)
+    };
+    let (sv_embeddings, en_embeddings) = futures::try_join!(
+        db.list_embeddings_by_field_name_lang_code_published_at_model(
             feeds::FieldName::Description,
             feeds::LanguageCode::SV,
-            chrono::Utc::now().date_naive(),
-        )
-        .await?;
+            &window,
+            embedder.model(),
+        ),
+        db.list_embeddings_by_field_name_lang_code_published_at_model(
+            feeds::FieldName::Description,
+            feeds::LanguageCode::EN,
+            &window,
+            embedder.model(),
+        ),
+    )?;
+    // entries from english sources are clustered together with swedish ones
+    let today_title_embeddings = sv_embeddings
+        .into_iter()
+        .chain(en_embeddings)
+        .collect::<Vec<_>>();
 
     if today_title_embeddings.is_empty() {
-        return Ok(());
+        return Ok(0);
+    }
+
+    if config.incremental_clustering {
+        if let Some(report) = db.find_latest_report_by_date(now.date_naive()).await? {
+            return update_report(
+                db,
+                translator,
+                summarizer,
+                config,
+                &report,
+                today_title_embeddings,
+                now,
+            )
+            .await;
+        }
     }
 
-    let (groups, (min_points, tolerance), score) =
-        clustering::group_embeddings(&today_title_embeddings).await;
+    let (groups, (min_points, tolerance, tolerance_method), scores) =
+        clustering::group_embeddings(&today_title_embeddings, &config.clustering).await;
+
+    let groups = futures::future::try_join_all(
+        groups
+            .into_iter()
+            .map(|(embedding_ids, center)| prefer_wire_service_center(db, embedding_ids, center)),
+    )
+    .await?;
 
     // ensure that all translations are available
-    let translator = openai::Translator::new(openai_client);
-    futures::future::try_join_all(groups.iter().flat_map(|(group, _)| group).map(|id| {
-        translate(
-            db,
-            &translator,
-            id,
-            &feeds::FieldName::Title,
-            &feeds::LanguageCode::EN,
-        )
-    }))
+    translate_titles(
+        db,
+        translator,
+        config,
+        groups
+            .iter()
+            .flat_map(|(group, _)| group.iter().copied())
+            .collect(),
+    )
     .await?;
 
+    let previous_report = db.find_latest_report_before(now).await?;
+
     let report = db
-        .insert_report(&clustering::Report {
-            score,
-            tolerance,
-            min_points: min_points.try_into().expect("usize -> u32 failed"),
-            rows: today_title_embeddings
-                .len()
-                .try_into()
-                .expect("usize -> u32 failed"),
-            dimentions: today_title_embeddings[0].value.size,
+        .insert_report(
+            &clustering::Report {
+                algorithm: config.clustering.algorithm,
+                tolerance_method,
+                score_metric: config.clustering.score_metric,
+                score: scores.silhouette,
+                davies_bouldin: scores.davies_bouldin,
+                calinski_harabasz: scores.calinski_harabasz,
+                tolerance,
+                tolerance_range_start: *config.clustering.tolerance_range.start(),
+                tolerance_range_end: *config.clustering.tolerance_range.end(),
+                samples: config
+                    .clustering
+                    .samples
+                    .try_into()
+                    .expect("usize -> u32 failed"),
+                min_points: min_points.try_into().expect("usize -> u32 failed"),
+                rows: today_title_embeddings
+                    .len()
+                    .try_into()
+                    .expect("usize -> u32 failed"),
+                dimentions: today_title_embeddings[0].value.size,
+                window_start: Some(window.start),
+                window_end: Some(window.end),
+                updated_at: None,
+            },
+            now,
+        )
+        .await?;
+
+    let groups =
+        futures::future::try_join_all(groups.into_iter().map(|(embedding_ids, center)| {
+            db.insert_report_group(clustering::ReportGroup {
+                report_id: report.id,
+                center_embedding_id: embedding_ids[center],
+                embedding_ids,
+            })
+        }))
+        .await?;
+
+    if let Some(summarizer) = summarizer {
+        enrich_groups(db, summarizer, config, &groups, vec![]).await?;
+    }
+
+    link_related_groups(db, &groups, &today_title_embeddings).await?;
+
+    if let Some(previous_report) = previous_report {
+        let previous_groups = db
+            .list_report_groups_by_report_id(previous_report.id)
+            .await?;
+        link_report_groups(db, &previous_groups, &groups).await?;
+    }
+
+    detect_breaking_groups(db, config, &groups, now).await?;
+    if !config.posters.is_empty() {
+        announce_major_groups(db, config, &groups, now).await?;
+    }
+    mark_report_updated(db, config, report.id).await?;
+
+    Ok(groups.len())
+}
+
+async fn link_report_groups(
+    db: &db::Client,
+    previous_groups: &[Persisted<clustering::ReportGroup>],
+    groups: &[Persisted<clustering::ReportGroup>],
+) -> Result<(), Error> {
+    for group in groups {
+        let parent = previous_groups
+            .iter()
+            .map(|previous_group| {
+                let overlap = group
+                    .value
+                    .embedding_ids
+                    .iter()
+                    .filter(|id| previous_group.value.embedding_ids.contains(id))
+                    .count();
+                (previous_group, overlap)
+            })
+            .filter(|(_, overlap)| *overlap > 0)
+            .max_by_key(|(_, overlap)| *overlap);
+        if let Some((parent, overlap)) = parent {
+            db.insert_report_group_lineage(
+                group.id,
+                parent.id,
+                overlap.try_into().expect("usize -> u32 failed"),
+            )
+            .await?;
+        }
+    }
+    Ok(())
+}
+
+/// adds embeddings that are not grouped yet to the existing groups of the report, and groups the
+/// rest into new groups of the same report, so that ids of existing groups do not change.
+async fn update_report(
+    db: &db::Client,
+    translator: &dyn providers::Translator,
+    summarizer: Option<&dyn providers::Summarizer>,
+    config: &Config,
+    report: &Persisted<clustering::Report>,
+    embeddings: Vec<Persisted<clustering::Embedding>>,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Result<usize, Error> {
+    let groups = db.list_report_groups_by_report_id(report.id).await?;
+
+    let grouped_ids = groups
+        .iter()
+        .flat_map(|group| group.value.embedding_ids.iter().copied())
+        .collect::<std::collections::BTreeSet<_>>();
+    let (grouped, ungrouped): (Vec<_>, Vec<_>) = embeddings
+        .iter()
+        .partition(|embedding| grouped_ids.contains(&embedding.id));
+    if ungrouped.is_empty() {
+        return Ok(groups.len());
+    }
+    let ungrouped = ungrouped.into_iter().cloned().collect::<Vec<_>>();
+
+    let group_embeddings = groups
+        .iter()
+        .map(|group| {
+            grouped
+                .iter()
+                .filter(|embedding| group.value.embedding_ids.contains(&embedding.id))
+                .map(|embedding| (*embedding).clone())
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    let assignments =
+        clustering::assign_to_groups(&group_embeddings, &ungrouped, report.value.tolerance);
+
+    let mut additions = vec![vec![]; groups.len()];
+    let mut unassigned = vec![];
+    for (embedding, assignment) in ungrouped.into_iter().zip(assignments) {
+        match assignment {
+            Some(i) => additions[i].push(embedding.id),
+            None => unassigned.push(embedding),
+        }
+    }
+
+    let new_groups =
+        if unassigned.len() < config.clustering.min_points {
+            vec![]
+        } else {
+            let (new_groups, _, _) =
+                clustering::group_embeddings(&unassigned, &config.clustering).await;
+            futures::future::try_join_all(new_groups.into_iter().map(|(embedding_ids, center)| {
+                prefer_wire_service_center(db, embedding_ids, center)
+            }))
+            .await?
+        };
+
+    tracing::info!(
+        report_id = %report.id,
+        added = additions.iter().map(Vec::len).sum::<usize>(),
+        new_groups = new_groups.len(),
+        "updating report"
+    );
+
+    translate_titles(
+        db,
+        translator,
+        config,
+        additions
+            .iter()
+            .flatten()
+            .chain(new_groups.iter().flat_map(|(group, _)| group.iter()))
+            .copied()
+            .collect(),
+    )
+    .await?;
+
+    let mut changed_group_ids = vec![];
+    for (group, embedding_ids) in groups.iter().zip(additions) {
+        if !embedding_ids.is_empty() {
+            db.insert_report_group_embeddings(group.id, &embedding_ids)
+                .await?;
+            changed_group_ids.push(group.id);
+        }
+    }
+
+    let new_groups =
+        futures::future::try_join_all(new_groups.into_iter().map(|(embedding_ids, center)| {
+            db.insert_report_group(clustering::ReportGroup {
+                report_id: report.id,
+                center_embedding_id: embedding_ids[center],
+                embedding_ids,
+            })
+        }))
+        .await?;
+
+    if let Some(summarizer) = summarizer {
+        enrich_groups(db, summarizer, config, &new_groups, changed_group_ids).await?;
+    }
+
+    // relations of existing groups change as well
+    let groups = db.list_report_groups_by_report_id(report.id).await?;
+    link_related_groups(db, &groups, &embeddings).await?;
+
+    detect_breaking_groups(db, config, &groups, now).await?;
+    if !config.posters.is_empty() {
+        announce_major_groups(db, config, &groups, now).await?;
+    }
+    mark_report_updated(db, config, report.id).await?;
+
+    Ok(groups.len())
+}
+
+async fn mark_report_updated(
+    db: &db::Client,
+    config: &Config,
+    report_id: Id<clustering::Report>,
+) -> Result<(), Error> {
+    db.update_report_updated_at(report_id).await?;
+    if let Some(report_updates) = &config.report_updates {
+        report_updates.send_replace(Some(chrono::Utc::now()));
+    }
+    Ok(())
+}
+
+/// marks groups covered by at least `breaking_sources` feeds within the last `breaking_window` as
+/// breaking. groups stay breaking for `breaking_window` across reports, and are posted to the
+/// webhook only the first time.
+#[tracing::instrument(level = "debug", skip_all)]
+async fn detect_breaking_groups(
+    db: &db::Client,
+    config: &Config,
+    groups: &[Persisted<clustering::ReportGroup>],
+    now: chrono::DateTime<chrono::Utc>,
+) -> Result<(), Error> {
+    let window = now - config.breaking_window..now;
+    for group in groups {
+        let breaking_at = db.find_report_group_breaking_at_by_id(group.id).await?;
+        if let Some(breaking_at) = breaking_at.filter(|breaking_at| *breaking_at >= window.start) {
+            // already breaking in this report, or recently in the one it developed from
+            db.update_report_group_breaking_at(group.id, breaking_at)
+                .await?;
+            continue;
+        }
+
+        let entries = futures::future::try_join_all(
+            group
+                .value
+                .embedding_ids
+                .iter()
+                .map(|embedding_id| db.list_entries_by_embedding_id(embedding_id)),
+        )
+        .await?;
+        let sources = entries
+            .iter()
+            .flatten()
+            .filter(|entry| window.contains(&entry.value.published_at))
+            .map(|entry| u32::from(entry.value.feed_id))
+            .collect::<std::collections::BTreeSet<_>>()
+            .len();
+        if sources < config.breaking_sources {
+            continue;
+        }
+
+        tracing::info!(group_id = %group.id, sources, "breaking news");
+        db.update_report_group_breaking_at(group.id, now).await?;
+        if let (None, Some(breaking_client)) = (breaking_at, &config.breaking_client) {
+            notify_breaking_group(db, config, breaking_client, group.id, to_u32(sources)).await?;
+        }
+        if let (None, Some(push_client)) = (breaking_at, &config.push_client) {
+            push_breaking_group(db, config, push_client, group.id, to_u32(sources)).await?;
+        }
+    }
+    Ok(())
+}
+
+async fn notify_breaking_group(
+    db: &db::Client,
+    config: &Config,
+    breaking_client: &alerts::Client,
+    group_id: Id<clustering::ReportGroup>,
+    sources: u32,
+) -> Result<(), Error> {
+    let entries = db
+        .list_report_group_entries_by_id_lang_code(group_id, &feeds::LanguageCode::EN)
+        .await?;
+    let Some(center) = entries
+        .iter()
+        .find(|entry| entry.is_center)
+        .or(entries.first())
+    else {
+        return Ok(());
+    };
+    let title = center.group_title.as_ref().unwrap_or(&center.title).clone();
+    let breaking = alerts::Breaking {
+        text: format!("Breaking: {title} ({sources} sources)"),
+        group_id,
+        title,
+        url: config
+            .base_url
+            .join(&format!("groups/{group_id}"))
+            .expect("valid url"),
+        sources,
+    };
+    if let Err(error) = breaking_client.send(&breaking).await {
+        tracing::error!(%error, "failed to send breaking news");
+    }
+    Ok(())
+}
+
+const PUSH_CONCURRENCY: usize = 16;
+
+async fn push_breaking_group(
+    db: &db::Client,
+    config: &Config,
+    push_client: &push::Client,
+    group_id: Id<clustering::ReportGroup>,
+    sources: u32,
+) -> Result<(), Error> {
+    use futures::StreamExt;
+
+    let entries = db
+        .list_report_group_entries_by_id_lang_code(group_id, &feeds::LanguageCode::EN)
+        .await?;
+    let Some(center) = entries
+        .iter()
+        .find(|entry| entry.is_center)
+        .or(entries.first())
+    else {
+        return Ok(());
+    };
+    let notification = push::Notification {
+        title: center.group_title.as_ref().unwrap_or(&center.title).clone(),
+        body: format!("Breaking news, {sources} sources"),
+        url: config
+            .base_url
+            .join(&format!("groups/{group_id}"))
+            .expect("valid url"),
+    };
+
+    let subscriptions = db.list_push_subscriptions().await?;
+    let notification = &notification;
+    let results = futures::stream::iter(subscriptions)
+        .map(|subscription| async move {
+            let result = push_client.send(&subscription, notification).await;
+            (subscription, result)
         })
+        .buffer_unordered(PUSH_CONCURRENCY)
+        .collect::<Vec<_>>()
+        .await;
+    for (subscription, result) in results {
+        match result {
+            Ok(()) => {}
+            Err(push::Error::Gone) => db.delete_push_subscription(&subscription.endpoint).await?,
+            Err(error) => {
+                tracing::error!(%error, endpoint = subscription.endpoint, "failed to push breaking news");
+            }
+        }
+    }
+    Ok(())
+}
+
+#[tracing::instrument(level = "debug", skip_all)]
+async fn announce_major_groups(
+    db: &db::Client,
+    config: &Config,
+    groups: &[Persisted<clustering::ReportGroup>],
+    now: chrono::DateTime<chrono::Utc>,
+) -> Result<(), Error> {
+    for group in groups {
+        if group.value.embedding_ids.len() < config.announce_sources {
+            // every source has at least one embedding in the group
+            continue;
+        }
+        let mut posters = Vec::new();
+        for poster in &config.posters {
+            let posted_at = db
+                .find_report_group_posted_at_by_id_network(group.id, poster.network())
+                .await?;
+            if posted_at.is_none() {
+                posters.push(poster);
+            }
+        }
+        if posters.is_empty() {
+            continue;
+        }
+
+        let Some(announcement) = major_group_announcement(db, config, group.id).await? else {
+            continue;
+        };
+        for poster in posters {
+            let network = poster.network();
+            tracing::info!(group_id = %group.id, ?network, sources = announcement.sources, "announcing");
+            if let Err(error) = poster.post(&announcement).await {
+                // announced on the next run
+                tracing::error!(%error, ?network, "failed to announce");
+                continue;
+            }
+            db.insert_report_group_post(group.id, network, now).await?;
+        }
+    }
+    Ok(())
+}
+
+async fn major_group_announcement(
+    db: &db::Client,
+    config: &Config,
+    group_id: Id<clustering::ReportGroup>,
+) -> Result<Option<social::Announcement>, Error> {
+    let entries = db
+        .list_report_group_entries_by_id_lang_code(group_id, &feeds::LanguageCode::EN)
         .await?;
+    let sources = entries
+        .iter()
+        .map(|entry| u32::from(entry.feed_id))
+        .collect::<std::collections::BTreeSet<_>>()
+        .len();
+    if sources < config.announce_sources {
+        return Ok(None);
+    }
+    let Some(center) = entries
+        .iter()
+        .find(|entry| entry.is_center)
+        .or(entries.first())
+    else {
+        return Ok(None);
+    };
+    Ok(Some(social::Announcement {
+        group_id,
+        title: center.group_title.as_ref().unwrap_or(&center.title).clone(),
+        url: config
+            .base_url
+            .join(&format!("groups/{group_id}"))
+            .expect("valid url"),
+        sources,
+        content_warning: center
+            .category
+            .filter(|category| config.content_warnings.contains(category))
+            .map(|category| category.as_str().to_string()),
+    }))
+}
+
+const MAX_RELATED_GROUPS: usize = 3;
 
-    futures::future::try_join_all(groups.into_iter().map(|(embedding_ids, center)| {
-        db.insert_report_group(clustering::ReportGroup {
-            report_id: report.id,
-            center_embedding_id: embedding_ids[center],
-            embedding_ids,
+async fn link_related_groups(
+    db: &db::Client,
+    groups: &[Persisted<clustering::ReportGroup>],
+    embeddings: &[Persisted<clustering::Embedding>],
+) -> Result<(), Error> {
+    let group_embeddings = groups
+        .iter()
+        .map(|group| {
+            embeddings
+                .iter()
+                .filter(|embedding| group.value.embedding_ids.contains(&embedding.id))
+                .collect::<Vec<_>>()
         })
+        .collect::<Vec<_>>();
+    let related = clustering::related_groups(&group_embeddings, MAX_RELATED_GROUPS);
+
+    futures::future::try_join_all(groups.iter().zip(related).map(|(group, related)| {
+        let related = related
+            .into_iter()
+            .map(|(i, distance)| (groups[i].id, distance))
+            .collect::<Vec<_>>();
+        async move { db.replace_related_groups(group.id, &related).await }
     }))
     .await?;
 
     Ok(())
 }
 
+async fn enrich_groups(
+    db: &db::Client,
+    summarizer: &dyn providers::Summarizer,
+    config: &Config,
+    new_groups: &[Persisted<clustering::ReportGroup>],
+    changed_group_ids: Vec<Id<clustering::ReportGroup>>,
+) -> Result<(), Error> {
+    if config.group_enrichments.contains(&Enrichment::Headline) {
+        generate_group_titles(db, summarizer, config, new_groups).await?;
+    }
+    if config.group_enrichments.contains(&Enrichment::Category) {
+        generate_group_categories(db, summarizer, config, new_groups).await?;
+    }
+    let group_ids = changed_group_ids
+        .into_iter()
+        .chain(new_groups.iter().map(|group| group.id))
+        .collect::<Vec<_>>();
+    if config.group_enrichments.contains(&Enrichment::Summary) {
+        generate_group_summaries(db, summarizer, config, group_ids.clone()).await?;
+    }
+    if config.group_enrichments.contains(&Enrichment::Lattlast) {
+        generate_group_lattlasts(db, summarizer, config, group_ids).await?;
+    }
+    Ok(())
+}
+
+async fn generate_group_titles(
+    db: &db::Client,
+    summarizer: &dyn providers::Summarizer,
+    config: &Config,
+    groups: &[Persisted<clustering::ReportGroup>],
+) -> Result<(), Error> {
+    use futures::{StreamExt, TryStreamExt};
+
+    futures::stream::iter(groups.iter().map(|group| group.id).collect::<Vec<_>>())
+        .map(|group_id| generate_group_title(db, summarizer, group_id))
+        .buffer_unordered(config.openai_concurrency)
+        .try_collect::<()>()
+        .await
+}
+
+async fn generate_group_title(
+    db: &db::Client,
+    summarizer: &dyn providers::Summarizer,
+    group_id: Id<clustering::ReportGroup>,
+) -> Result<(), Error> {
+    let titles = list_group_titles(db, group_id, &feeds::LanguageCode::EN).await?;
+    if titles.is_empty() {
+        return Ok(());
+    }
+    if let Some(headline) = summarizer.headline(&titles).await? {
+        db.insert_group_title(group_id, &feeds::LanguageCode::EN, &headline)
+            .await?;
+    }
+    Ok(())
+}
+
+async fn list_group_titles(
+    db: &db::Client,
+    group_id: Id<clustering::ReportGroup>,
+    lang_code: &feeds::LanguageCode,
+) -> Result<Vec<String>, Error> {
+    let entries = db
+        .list_report_group_entries_by_id_lang_code(group_id, lang_code)
+        .await?;
+    let mut titles = entries
+        .into_iter()
+        .map(|entry| entry.title)
+        .collect::<Vec<_>>();
+    titles.sort();
+    titles.dedup();
+    Ok(titles)
+}
+
+async fn generate_group_categories(
+    db: &db::Client,
+    summarizer: &dyn providers::Summarizer,
+    config: &Config,
+    groups: &[Persisted<clustering::ReportGroup>],
+) -> Result<(), Error> {
+    use futures::{StreamExt, TryStreamExt};
+
+    futures::stream::iter(groups.iter().map(|group| group.id).collect::<Vec<_>>())
+        .map(|group_id| generate_group_category(db, summarizer, group_id))
+        .buffer_unordered(config.openai_concurrency)
+        .try_collect::<()>()
+        .await
+}
+
+async fn generate_group_category(
+    db: &db::Client,
+    summarizer: &dyn providers::Summarizer,
+    group_id: Id<clustering::ReportGroup>,
+) -> Result<(), Error> {
+    let titles = list_group_titles(db, group_id, &feeds::LanguageCode::EN).await?;
+    if titles.is_empty() {
+        return Ok(());
+    }
+    if let Some(category) = summarizer.category(&titles).await? {
+        db.update_report_group_category(group_id, category).await?;
+    }
+    Ok(())
+}
+
+/// writes an english summary for each group from the descriptions of its entries. summaries are
+/// cached by the descriptions, so that groups of the same entries share them between reports.
+async fn generate_group_summaries(
+    db: &db::Client,
+    summarizer: &dyn providers::Summarizer,
+    config: &Config,
+    group_ids: Vec<Id<clustering::ReportGroup>>,
+) -> Result<(), Error> {
+    db.insert_tasks(
+        tasks::Kind::Summarize,
+        &group_ids
+            .into_iter()
+            .map(|id| (u32::from(id), None))
+            .collect::<Vec<_>>(),
+    )
+    .await?;
+    tasks::run_summarize(db, summarizer, config).await
+}
+
+const MAX_SUMMARY_DESCRIPTIONS: usize = 20;
+
+async fn list_group_descriptions(
+    db: &db::Client,
+    group_id: Id<clustering::ReportGroup>,
+) -> Result<(Vec<String>, md5_hash::Md5Hash), Error> {
+    let mut descriptions = db.list_descriptions_by_report_group_id(group_id).await?;
+    descriptions.sort();
+    descriptions.dedup();
+    descriptions.truncate(MAX_SUMMARY_DESCRIPTIONS);
+    let md5_hash = md5_hash::compute(descriptions.join("\n"));
+    Ok((descriptions, md5_hash))
+}
+
+async fn generate_group_summary(
+    db: &db::Client,
+    summarizer: &dyn providers::Summarizer,
+    group_id: Id<clustering::ReportGroup>,
+) -> Result<(), Error> {
+    let (descriptions, md5_hash) = list_group_descriptions(db, group_id).await?;
+    if descriptions.is_empty() {
+        return Ok(());
+    }
+
+    if db
+        .find_group_summary_by_md5_hash_lang_code(&md5_hash, &feeds::LanguageCode::EN)
+        .await?
+        .is_none()
+    {
+        let Some(summary) = summarizer.summary(&descriptions).await? else {
+            return Ok(());
+        };
+        db.insert_group_summary(&md5_hash, &feeds::LanguageCode::EN, &summary)
+            .await?;
+    }
+    db.update_report_group_summary_md5_hash(group_id, &md5_hash)
+        .await?;
+    Ok(())
+}
+
+async fn generate_group_lattlasts(
+    db: &db::Client,
+    summarizer: &dyn providers::Summarizer,
+    config: &Config,
+    group_ids: Vec<Id<clustering::ReportGroup>>,
+) -> Result<(), Error> {
+    use futures::{StreamExt, TryStreamExt};
+
+    futures::stream::iter(group_ids)
+        .map(|group_id| generate_group_lattlast(db, summarizer, group_id))
+        .buffer_unordered(config.openai_concurrency)
+        .try_collect::<()>()
+        .await
+}
+
+async fn generate_group_lattlast(
+    db: &db::Client,
+    summarizer: &dyn providers::Summarizer,
+    group_id: Id<clustering::ReportGroup>,
+) -> Result<(), Error> {
+    let (titles, (descriptions, md5_hash)) = futures::try_join!(
+        list_group_titles(db, group_id, &feeds::LanguageCode::SV),
+        list_group_descriptions(db, group_id),
+    )?;
+    if titles.is_empty() || descriptions.is_empty() {
+        return Ok(());
+    }
+    let Some(lattlast) = summarizer.lattlast(&titles, &descriptions).await? else {
+        return Ok(());
+    };
+    futures::try_join!(
+        db.insert_group_title(group_id, &feeds::LanguageCode::Lattlast, &lattlast.headline),
+        db.insert_group_summary(&md5_hash, &feeds::LanguageCode::Lattlast, &lattlast.summary),
+        db.update_report_group_summary_md5_hash(group_id, &md5_hash),
+    )?;
+    Ok(())
+}
+
+async fn translate_titles(
+    db: &db::Client,
+    translator: &dyn providers::Translator,
+    config: &Config,
+    embedding_ids: Vec<Id<clustering::Embedding>>,
+) -> Result<(), Error> {
+    use futures::{StreamExt, TryStreamExt};
+
+    let pairs = config
+        .translation_languages
+        .iter()
+        .flat_map(|lang_code| embedding_ids.iter().map(move |id| (*id, lang_code.clone())))
+        .collect::<Vec<_>>();
+    let untranslated_fields = futures::stream::iter(pairs)
+        .map(|(id, lang_code)| async move {
+            list_untranslated_fields(db, id, &feeds::FieldName::Title, &lang_code).await
+        })
+        .buffer_unordered(config.openai_concurrency)
+        .try_concat()
+        .await?;
+    // a task translates a field into all languages it is missing in, so it is done again when
+    // languages are added
+    let languages = config
+        .translation_languages
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(",");
+    let targets = untranslated_fields
+        .iter()
+        .map(|(field, _)| {
+            let input = format!("{:?}:{languages}", field.value.md5_hash);
+            (field.id.into(), Some(input))
+        })
+        .collect::<std::collections::BTreeMap<u32, _>>();
+    db.insert_tasks(
+        tasks::Kind::Translate,
+        &targets.into_iter().collect::<Vec<_>>(),
+    )
+    .await?;
+    tasks::run_translate(db, translator, config).await
+}
+
+/// if a wire service published one of the grouped entries, it is most likely the original
+/// that other outlets republished, so use it as the center of the group.
 #[tracing::instrument(level = "debug", skip_all)]
-async fn translate(
+async fn prefer_wire_service_center(
+    db: &db::Client,
+    embedding_ids: Vec<Id<clustering::Embedding>>,
+    center: usize,
+) -> Result<(Vec<Id<clustering::Embedding>>, usize), Error> {
+    let entries = futures::future::try_join_all(
+        embedding_ids
+            .iter()
+            .map(|embedding_id| db.list_entries_by_embedding_id(embedding_id)),
+    )
+    .await?;
+
+    let wire_service_center = entries.iter().position(|entries| {
+        entries.iter().any(|entry| {
+            feeds::LIST
+                .iter()
+                .find(|feed| feed.id == entry.value.feed_id)
+                .is_some_and(|feed| feed.value.wire_service)
+        })
+    });
+
+    Ok((embedding_ids, wire_service_center.unwrap_or(center)))
+}
+
+#[tracing::instrument(level = "debug", skip_all)]
+async fn list_untranslated_fields(
     db: &db::Client,
-    translator: &openai::Translator<'_>,
-    embedding_id: &Id<clustering::Embedding>,
+    embedding_id: Id<clustering::Embedding>,
     field_name: &feeds::FieldName,
     lang_code: &feeds::LanguageCode,
-) -> Result<(), Error> {
-    let embedding = db.find_embedding_by_id(embedding_id).await?;
+) -> Result<Vec<(Persisted<feeds::Field>, String)>, Error> {
+    let embedding = db.find_embedding_by_id(&embedding_id).await?;
     let fields = db
         .list_fields_by_md5_hash(&embedding.value.md5_hash)
-        .await?;
+        .await?
+        .into_iter()
+        // entries that are already in the target language do not need a translation
+        .filter(|field| field.value.lang_code != *lang_code)
+        .collect::<Vec<_>>();
     if fields.is_empty() {
-        return Ok(());
+        return Ok(vec![]);
     }
 
     let fields = futures::future::try_join_all(fields.iter().map(|field| {
@@ -242,8 +1634,28 @@ async fn translate(
     )
     .await?;
 
-    for (field, original) in to_translate.into_iter().zip(originals) {
-        let translation = translator.translate_sv_to_en(&original.value.value).await?;
+    Ok(to_translate
+        .into_iter()
+        .zip(originals)
+        .map(|(field, original)| (field, original.value.value))
+        .collect())
+}
+
+#[tracing::instrument(level = "debug", skip_all, fields(batch_size = fields.len(), %from, %lang_code))]
+async fn translate(
+    db: &db::Client,
+    translator: &dyn providers::Translator,
+    fields: Vec<(Persisted<feeds::Field>, String)>,
+    from: &feeds::LanguageCode,
+    lang_code: &feeds::LanguageCode,
+) -> Result<(), Error> {
+    let values = fields
+        .iter()
+        .map(|(_, value)| value.clone())
+        .collect::<Vec<_>>();
+    let translations = translator.translate_batch(from, lang_code, &values).await?;
+
+    for ((field, _), translation) in fields.into_iter().zip(translations) {
         let md5_hash = md5_hash::compute(&translation);
         futures::future::try_join(
             db.insert_translation(feeds::Translation {
@@ -252,7 +1664,7 @@ async fn translate(
             }),
             db.insert_field(feeds::Field {
                 md5_hash,
-                lang_code: feeds::LanguageCode::EN,
+                lang_code: lang_code.clone(),
                 ..field.value.clone()
             }),
         )
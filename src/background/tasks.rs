@@ -0,0 +1,264 @@
+//! pipeline work is queued in the database before it is done, so that work that was interrupted
+//! by a crash or a failed request is picked up by the next run instead of leaving gaps.
+//! running a task twice is harmless.
+
+use super::{generate_embedding, generate_group_summary, translate, Config, Error};
+use crate::{clustering, db, feeds, id::Id, openai, persisted::Persisted, providers};
+
+const MAX_ATTEMPTS: u32 = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[sqlx(rename_all = "lowercase")]
+pub enum Kind {
+    Embed,
+    Translate,
+    Summarize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[sqlx(rename_all = "lowercase")]
+pub enum Status {
+    Pending,
+    Done,
+    Failed,
+}
+
+impl std::fmt::Display for Kind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Embed => write!(f, "embed"),
+            Self::Translate => write!(f, "translate"),
+            Self::Summarize => write!(f, "summarize"),
+        }
+    }
+}
+
+impl std::fmt::Display for Status {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Pending => write!(f, "pending"),
+            Self::Done => write!(f, "done"),
+            Self::Failed => write!(f, "failed"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct Task {
+    pub kind: Kind,
+    pub target_id: u32,
+    pub status: Status,
+    pub attempts: u32,
+    pub error: Option<String>,
+}
+
+async fn finish(db: &db::Client, task: &Task, result: Result<(), Error>) -> Result<bool, Error> {
+    match result {
+        Ok(()) => {
+            db.complete_task(task.kind, task.target_id).await?;
+            Ok(true)
+        }
+        // the task is left pending until the budget allows it
+        Err(error) if error.is::<openai::BudgetExceeded>() => Err(error),
+        Err(error) => {
+            tracing::error!(kind = %task.kind, target_id = task.target_id, %error, "task failed");
+            db.fail_task(task.kind, task.target_id, &error.to_string(), MAX_ATTEMPTS)
+                .await?;
+            Ok(false)
+        }
+    }
+}
+
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn run_embed(
+    db: &db::Client,
+    embedder: &dyn providers::Embedder,
+    config: &Config,
+) -> Result<usize, Error> {
+    use futures::{StreamExt, TryStreamExt};
+
+    let tasks = db.list_pending_tasks(Kind::Embed).await?;
+    futures::stream::iter(tasks)
+        .map(|task| async move {
+            let result = embed(db, embedder, config, Id::from(task.target_id)).await;
+            finish(db, &task, result).await
+        })
+        .buffer_unordered(config.openai_concurrency)
+        .try_fold(
+            0,
+            |count, done| async move { Ok(count + usize::from(done)) },
+        )
+        .await
+}
+
+async fn embed(
+    db: &db::Client,
+    embedder: &dyn providers::Embedder,
+    config: &Config,
+    field_id: Id<feeds::Field>,
+) -> Result<(), Error> {
+    let field = db.find_field_by_id(field_id).await?;
+    // descriptions of different entries can be equal, they share the embedding
+    let exists = db
+        .list_embeddings_by_md5_hash(&field.value.md5_hash)
+        .await?
+        .iter()
+        .any(|embedding| embedding.value.model == embedder.model());
+    if exists {
+        return Ok(());
+    }
+    let translation = db
+        .find_translation_by_md5_hash(&field.value.md5_hash)
+        .await?;
+    generate_embedding(
+        db,
+        embedder,
+        config.embedding_precision,
+        &field.value.lang_code,
+        translation,
+    )
+    .await
+}
+
+/// translates all pending fields into the languages they are missing in, in batches of fields
+/// of the same language. tasks are done once all their translations are.
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn run_translate(
+    db: &db::Client,
+    translator: &dyn providers::Translator,
+    config: &Config,
+) -> Result<(), Error> {
+    use futures::StreamExt;
+
+    let tasks = db.list_pending_tasks(Kind::Translate).await?;
+    let mut pending = vec![];
+    let mut fields_by_languages = std::collections::BTreeMap::<_, Vec<_>>::new();
+    for task in tasks {
+        let field = db.find_field_by_id(Id::from(task.target_id)).await?;
+        let mut missing = vec![];
+        for lang_code in &config.translation_languages {
+            if *lang_code == field.value.lang_code {
+                continue;
+            }
+            let translated = db
+                .find_field_by_entry_id_name_lang_code(
+                    &field.value.entry_id,
+                    &field.value.name,
+                    lang_code,
+                )
+                .await?
+                .is_some();
+            if !translated {
+                missing.push(lang_code.clone());
+            }
+        }
+        if missing.is_empty() {
+            db.complete_task(task.kind, task.target_id).await?;
+            continue;
+        }
+        let original = db
+            .find_translation_by_md5_hash(&field.value.md5_hash)
+            .await?;
+        for lang_code in missing {
+            // languages are keyed by their codes, as they are not ordered
+            fields_by_languages
+                .entry((field.value.lang_code.to_string(), lang_code.to_string()))
+                .or_default()
+                .push((
+                    task.clone(),
+                    field.clone(),
+                    original.value.value.clone(),
+                    lang_code,
+                ));
+        }
+        pending.push(task);
+    }
+
+    let mut batches = vec![];
+    for fields in fields_by_languages.into_values() {
+        batches.extend(
+            fields
+                .chunks(config.translation_batch_size)
+                .map(<[_]>::to_vec),
+        );
+    }
+    let failures = futures::stream::iter(batches)
+        .map(|batch| translate_batch(db, translator, batch))
+        .buffer_unordered(config.openai_concurrency)
+        .filter_map(|result| futures::future::ready(result.err()))
+        .collect::<Vec<_>>()
+        .await;
+
+    // a field is in a batch for each of its languages, it fails once if any of them does. fields
+    // that were not translated because of the budget are left pending.
+    let mut skipped = std::collections::BTreeSet::new();
+    let mut errors = std::collections::BTreeMap::new();
+    let mut budget_exceeded = None;
+    for (target_ids, error) in failures {
+        if error.is::<openai::BudgetExceeded>() {
+            skipped.extend(target_ids);
+            budget_exceeded = Some(error);
+            continue;
+        }
+        tracing::error!(kind = %Kind::Translate, count = target_ids.len(), %error, "tasks failed");
+        for target_id in target_ids {
+            errors.entry(target_id).or_insert_with(|| error.to_string());
+        }
+    }
+    for task in pending {
+        if skipped.contains(&task.target_id) {
+            continue;
+        }
+        match errors.get(&task.target_id) {
+            Some(error) => {
+                db.fail_task(task.kind, task.target_id, error, MAX_ATTEMPTS)
+                    .await?;
+            }
+            None => db.complete_task(task.kind, task.target_id).await?,
+        }
+    }
+    budget_exceeded.map_or(Ok(()), Err)
+}
+
+async fn translate_batch(
+    db: &db::Client,
+    translator: &dyn providers::Translator,
+    batch: Vec<(Task, Persisted<feeds::Field>, String, feeds::LanguageCode)>,
+) -> Result<(), (Vec<u32>, Error)> {
+    let Some((_, first, _, lang_code)) = batch.first() else {
+        return Ok(());
+    };
+    let from = first.value.lang_code.clone();
+    let lang_code = lang_code.clone();
+    let (target_ids, fields): (Vec<_>, Vec<_>) = batch
+        .into_iter()
+        .map(|(task, field, original, _)| (task.target_id, (field, original)))
+        .unzip();
+    translate(db, translator, fields, &from, &lang_code)
+        .await
+        .map_err(|error| (target_ids, error))
+}
+
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn run_summarize(
+    db: &db::Client,
+    summarizer: &dyn providers::Summarizer,
+    config: &Config,
+) -> Result<(), Error> {
+    use futures::{StreamExt, TryStreamExt};
+
+    let tasks = db.list_pending_tasks(Kind::Summarize).await?;
+    futures::stream::iter(tasks)
+        .map(|task| async move {
+            let result = generate_group_summary(
+                db,
+                summarizer,
+                Id::<clustering::ReportGroup>::from(task.target_id),
+            )
+            .await;
+            finish(db, &task, result).await
+        })
+        .buffer_unordered(config.openai_concurrency)
+        .try_for_each(|_| futures::future::ready(Ok(())))
+        .await
+}
@@ -0,0 +1,89 @@
+use crate::db;
+
+const PREFIX: &str = "sverige-news-";
+const EXTENSION: &str = ".sqlite3";
+const GZIP_EXTENSION: &str = ".sqlite3.gz";
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("database: {0}")]
+    Db(#[from] sqlx::Error),
+    #[error("io: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("join: {0}")]
+    Join(#[from] tokio::task::JoinError),
+}
+
+#[tracing::instrument(level = "debug", skip(db))]
+pub async fn run(
+    db: &db::Client,
+    dir: &std::path::Path,
+    gzip: bool,
+    keep: Option<usize>,
+) -> Result<std::path::PathBuf, Error> {
+    std::fs::create_dir_all(dir)?;
+
+    let path = dir.join(format!(
+        "{PREFIX}{}{EXTENSION}",
+        chrono::Utc::now().format("%Y%m%dT%H%M%SZ")
+    ));
+    db.vacuum_into(&path).await?;
+
+    let dir = dir.to_path_buf();
+    let path = tokio::task::spawn_blocking(move || -> Result<_, Error> {
+        let path = if gzip { compress(&path)? } else { path };
+        if let Some(keep) = keep {
+            remove_old(&dir, keep)?;
+        }
+        Ok(path)
+    })
+    .await??;
+
+    tracing::info!(path = %path.display(), "backup created");
+
+    Ok(path)
+}
+
+fn compress(path: &std::path::Path) -> Result<std::path::PathBuf, Error> {
+    let mut gz_path = path.as_os_str().to_owned();
+    gz_path.push(".gz");
+    let gz_path = std::path::PathBuf::from(gz_path);
+
+    let mut input = std::fs::File::open(path)?;
+    let mut encoder = flate2::write::GzEncoder::new(
+        std::fs::File::create(&gz_path)?,
+        flate2::Compression::default(),
+    );
+    std::io::copy(&mut input, &mut encoder)?;
+    encoder.finish()?;
+    std::fs::remove_file(path)?;
+
+    Ok(gz_path)
+}
+
+/// removes all but `keep` latest backups. names contain the time they were taken at, so sorting
+/// them by name sorts them by time.
+fn remove_old(dir: &std::path::Path, keep: usize) -> Result<(), Error> {
+    let mut backups = std::fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| {
+                    name.starts_with(PREFIX)
+                        && (name.ends_with(EXTENSION) || name.ends_with(GZIP_EXTENSION))
+                })
+        })
+        .collect::<Vec<_>>();
+    backups.sort();
+
+    let remove = backups.len().saturating_sub(keep);
+    for path in &backups[..remove] {
+        tracing::info!(path = %path.display(), "removing old backup");
+        std::fs::remove_file(path)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,169 @@
+/// posts to a bluesky account over xrpc, see <https://docs.bsky.app/docs/advanced-guides/posts>
+#[derive(Clone)]
+pub struct Client {
+    inner: reqwest::Client,
+    service_url: url::Url,
+    identifier: String,
+    app_password: String,
+    session: std::sync::Arc<tokio::sync::Mutex<Option<Session>>>,
+}
+
+type Error = Box<dyn std::error::Error + 'static + Send + Sync>;
+
+pub const MAX_LENGTH: usize = 300;
+
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Session {
+    access_jwt: String,
+    refresh_jwt: String,
+    did: String,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct XrpcError {
+    error: String,
+}
+
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Post<'a> {
+    #[serde(rename = "$type")]
+    kind: &'static str,
+    text: &'a str,
+    created_at: String,
+    langs: [&'static str; 1],
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    facets: Vec<serde_json::Value>,
+}
+
+impl Client {
+    pub fn new(service_url: &url::Url, identifier: &str, app_password: &str) -> Self {
+        Self {
+            inner: reqwest::Client::new(),
+            service_url: service_url.clone(),
+            identifier: identifier.to_string(),
+            app_password: app_password.to_string(),
+            session: std::sync::Arc::new(tokio::sync::Mutex::new(None)),
+        }
+    }
+
+    fn xrpc_url(&self, method: &str) -> url::Url {
+        self.service_url
+            .join(&format!("xrpc/{method}"))
+            .expect("valid url")
+    }
+
+    async fn create_session(&self) -> Result<Session, Error> {
+        let body = serde_json::json!({
+            "identifier": self.identifier,
+            "password": self.app_password,
+        });
+        let response = self
+            .inner
+            .post(self.xrpc_url("com.atproto.server.createSession"))
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body.to_string())
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(serde_json::from_str(&response.text().await?)?)
+    }
+
+    async fn refresh_session(&self, session: &Session) -> Result<Session, Error> {
+        let response = self
+            .inner
+            .post(self.xrpc_url("com.atproto.server.refreshSession"))
+            .bearer_auth(&session.refresh_jwt)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(serde_json::from_str(&response.text().await?)?)
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn post(&self, text: &str, link: &url::Url) -> Result<(), Error> {
+        let mut session = self.session.lock().await;
+        let current = match session.take() {
+            Some(current) => current,
+            None => self.create_session().await?,
+        };
+
+        let current = match self.create_post(&current, text, link).await? {
+            None => current,
+            Some(error) if error.error == "ExpiredToken" => {
+                // refresh tokens expire as well, a new session is created then
+                let refreshed = match self.refresh_session(&current).await {
+                    Ok(refreshed) => refreshed,
+                    Err(error) => {
+                        tracing::debug!(%error, "failed to refresh session");
+                        self.create_session().await?
+                    }
+                };
+                if let Some(error) = self.create_post(&refreshed, text, link).await? {
+                    return Err(error.error.into());
+                }
+                refreshed
+            }
+            Some(error) => {
+                *session = Some(current);
+                return Err(error.error.into());
+            }
+        };
+        *session = Some(current);
+        Ok(())
+    }
+
+    async fn create_post(
+        &self,
+        session: &Session,
+        text: &str,
+        link: &url::Url,
+    ) -> Result<Option<XrpcError>, Error> {
+        let facets = text
+            .find(link.as_str())
+            .map(|start| {
+                // facets are indexed by bytes of the utf-8 text
+                serde_json::json!({
+                    "index": {
+                        "byteStart": start,
+                        "byteEnd": start + link.as_str().len(),
+                    },
+                    "features": [{
+                        "$type": "app.bsky.richtext.facet#link",
+                        "uri": link.as_str(),
+                    }],
+                })
+            })
+            .into_iter()
+            .collect();
+        let body = serde_json::json!({
+            "repo": session.did,
+            "collection": "app.bsky.feed.post",
+            "record": Post {
+                kind: "app.bsky.feed.post",
+                text,
+                created_at: chrono::Utc::now().to_rfc3339(),
+                langs: ["en"],
+                facets,
+            },
+        });
+        let response = self
+            .inner
+            .post(self.xrpc_url("com.atproto.repo.createRecord"))
+            .bearer_auth(&session.access_jwt)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body.to_string())
+            .send()
+            .await?;
+        let status = response.status();
+        if status.is_success() {
+            return Ok(None);
+        }
+        let text = response.text().await?;
+        match serde_json::from_str::<XrpcError>(&text) {
+            Ok(error) if status.is_client_error() => Ok(Some(error)),
+            _ => Err(format!("{status}: {text}").into()),
+        }
+    }
+}
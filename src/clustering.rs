@@ -1,3 +1,5 @@
+mod hdbscan;
+
 use linfa::{metrics::SilhouetteScore, traits::Transformer, DatasetBase};
 use linfa_clustering::Dbscan;
 use linfa_nn::{
@@ -13,15 +15,111 @@ pub struct Embedding {
     pub md5_hash: Md5Hash,
     pub value: Vec<f32>,
     pub size: u32,
+    pub model: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum, sqlx::Type)]
+#[sqlx(rename_all = "lowercase")]
+pub enum Algorithm {
+    /// density based clustering with a single tolerance
+    Dbscan,
+    /// hierarchical dbscan, finds clusters of varying density
+    Hdbscan,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[sqlx(rename_all = "snake_case")]
+pub enum ToleranceMethod {
+    /// knee of the sorted distances to the k-th nearest neighbour
+    KDistance,
+    /// best scoring tolerance of a fixed range
+    GridSearch,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum, sqlx::Type)]
+#[sqlx(rename_all = "snake_case")]
+pub enum ScoreMetric {
+    Silhouette,
+    DaviesBouldin,
+    CalinskiHarabasz,
+}
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    serde::Serialize,
+    serde::Deserialize,
+    sqlx::Type,
+    clap::ValueEnum,
+)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(rename_all = "lowercase")]
+pub enum Category {
+    Politics,
+    Crime,
+    Economy,
+    Sports,
+    Culture,
+    Weather,
+    Other,
+}
+
+impl Category {
+    pub const ALL: [Category; 7] = [
+        Category::Politics,
+        Category::Crime,
+        Category::Economy,
+        Category::Sports,
+        Category::Culture,
+        Category::Weather,
+        Category::Other,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Category::Politics => "politics",
+            Category::Crime => "crime",
+            Category::Economy => "economy",
+            Category::Sports => "sports",
+            Category::Culture => "culture",
+            Category::Weather => "weather",
+            Category::Other => "other",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Scores {
+    /// from -1 to 1, higher is better
+    pub silhouette: f32,
+    /// from 0, lower is better. not defined for less than two clusters
+    pub davies_bouldin: Option<f64>,
+    /// from 0, higher is better. not defined for less than two clusters
+    pub calinski_harabasz: Option<f64>,
 }
 
 #[derive(Debug, Clone, sqlx::FromRow)]
 pub struct Report {
+    pub algorithm: Algorithm,
+    pub tolerance_method: Option<ToleranceMethod>,
     pub min_points: u32,
     pub tolerance: f32,
+    pub tolerance_range_start: f32,
+    pub tolerance_range_end: f32,
+    pub samples: u32,
+    pub score_metric: ScoreMetric,
     pub score: f32,
+    pub davies_bouldin: Option<f64>,
+    pub calinski_harabasz: Option<f64>,
     pub rows: u32,
     pub dimentions: u32,
+    pub window_start: Option<chrono::DateTime<chrono::Utc>>,
+    pub window_end: Option<chrono::DateTime<chrono::Utc>>,
+    #[sqlx(default)]
+    pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 #[derive(Debug, Clone)]
@@ -31,19 +129,43 @@ pub struct ReportGroup {
     pub center_embedding_id: Id<Embedding>,
 }
 
-static MIN_POINTS: usize = 3;
-static RANGE: std::ops::RangeInclusive<f32> = 0.9..=1.1;
-static SAMPLES: usize = 50;
+#[derive(Debug, Clone)]
+pub struct Params {
+    pub algorithm: Algorithm,
+    pub min_points: usize,
+    pub tolerance_range: std::ops::RangeInclusive<f32>,
+    pub samples: usize,
+    pub score_metric: ScoreMetric,
+}
+
+pub fn parse_tolerance_range(value: &str) -> Result<std::ops::RangeInclusive<f32>, String> {
+    let (start, end) = value
+        .split_once("..")
+        .ok_or_else(|| format!("expected start..end, got {value}"))?;
+    let start = start.parse::<f32>().map_err(|error| error.to_string())?;
+    let end = end
+        .trim_start_matches('=')
+        .parse::<f32>()
+        .map_err(|error| error.to_string())?;
+    if start > end {
+        return Err(format!("range start {start} is greater than end {end}"));
+    }
+    Ok(start..=end)
+}
+
+type Grouping = (
+    Vec<(Vec<Id<Embedding>>, usize)>,
+    (usize, f32, Option<ToleranceMethod>),
+    Scores,
+);
 
 /// given a set of embeddings, group them into clusters
-/// using the DBSCAN algorithm
+/// using the given algorithm
 ///
 /// returns a list of pairs of clusters and their most central point,
-/// parameters used to generate the clusters, and the silhouette score
+/// parameters used to generate the clusters, and the scores
 #[tracing::instrument(skip(embeddings))]
-pub async fn group_embeddings(
-    embeddings: &[Persisted<Embedding>],
-) -> (Vec<(Vec<Id<Embedding>>, usize)>, (usize, f32), f32) {
+pub async fn group_embeddings(embeddings: &[Persisted<Embedding>], params: &Params) -> Grouping {
     let shape = (embeddings.len(), embeddings[0].value.size as usize);
     let vectors = embeddings
         .iter()
@@ -51,26 +173,35 @@ pub async fn group_embeddings(
         .collect::<Vec<_>>();
     let vectors: Array2<f32> = Array2::from_shape_vec(shape, vectors).expect("invalid shape");
 
-    // first, run a grid search to find the best tolerance for the DBSCAN algorithm
-    let step = (RANGE.end() - RANGE.start()) / SAMPLES as f32;
-    let (mut best_clusters, mut best_tolerance, mut best_score) = (vec![], 0.0, 0.0);
-    for i in 0..SAMPLES {
-        let tolerance = RANGE.start() + step * i as f32;
-        let (clusters, score) = dbscan(&vectors, MIN_POINTS, tolerance).await;
-        tracing::info!(tolerance = tolerance, score = ?score, clusters_len = clusters.len(), "sample");
-        if clusters.len() as f32 * score > best_clusters.len() as f32 * best_score {
-            best_clusters = clusters;
-            best_tolerance = tolerance;
-            best_score = score;
-        } else if clusters.len() < best_clusters.len() {
-            // break once number of clusters starts to decrease
-            break;
+    let (best_clusters, best_tolerance, tolerance_method, best_score) = match params.algorithm {
+        Algorithm::Dbscan => {
+            if let Some((clusters, tolerance, score)) =
+                k_distance_dbscan(&vectors, params.min_points).await
+            {
+                (clusters, tolerance, Some(ToleranceMethod::KDistance), score)
+            } else {
+                let (clusters, tolerance, score) = grid_search_dbscan(&vectors, params).await;
+                (
+                    clusters,
+                    tolerance,
+                    Some(ToleranceMethod::GridSearch),
+                    score,
+                )
+            }
         }
-    }
+        Algorithm::Hdbscan => {
+            let (clusters, score) = hdbscan(&vectors, params.min_points).await;
+            // hdbscan does not need a tolerance
+            (clusters, 0.0, None, score)
+        }
+    };
+
+    let scores = Scores::new(&vectors, &best_clusters, best_score);
 
     tracing::info!(
+        tolerance_method = ?tolerance_method,
         tolerance = best_tolerance,
-        score = best_score,
+        scores = ?scores,
         clusters_len = best_clusters.len(),
         "best"
     );
@@ -110,7 +241,456 @@ pub async fn group_embeddings(
         })
         .collect::<Vec<_>>();
 
-    (clusters, (MIN_POINTS, best_tolerance), best_score)
+    (
+        clusters,
+        (params.min_points, best_tolerance, tolerance_method),
+        scores,
+    )
+}
+
+impl Scores {
+    fn new(vectors: &Array2<f32>, clusters: &[Vec<usize>], silhouette: f32) -> Self {
+        let vectors = vectors.mapv(f64::from);
+        let centroids = clusters
+            .iter()
+            .map(|cluster| {
+                vectors
+                    .select(ndarray::Axis(0), cluster)
+                    .mean_axis(ndarray::Axis(0))
+                    .expect("empty cluster")
+            })
+            .collect::<Vec<_>>();
+
+        Self {
+            silhouette,
+            davies_bouldin: davies_bouldin(&vectors, clusters, &centroids),
+            calinski_harabasz: calinski_harabasz(&vectors, clusters, &centroids),
+        }
+    }
+
+    fn value(&self, metric: ScoreMetric) -> f64 {
+        match metric {
+            ScoreMetric::Silhouette => f64::from(self.silhouette),
+            ScoreMetric::DaviesBouldin => self
+                .davies_bouldin
+                .map_or(0.0, |davies_bouldin| 1.0 / (1.0 + davies_bouldin)),
+            ScoreMetric::CalinskiHarabasz => self.calinski_harabasz.unwrap_or(0.0),
+        }
+    }
+}
+
+/// assigns embeddings to the group with the nearest member, if that member is within the
+/// tolerance. when tolerance is not known (hdbscan), the largest distance between neighbours
+/// within the group is used instead. returns a group index per embedding, or none for embeddings
+/// that do not belong to any of the groups.
+pub fn assign_to_groups(
+    groups: &[Vec<Persisted<Embedding>>],
+    embeddings: &[Persisted<Embedding>],
+    tolerance: f32,
+) -> Vec<Option<usize>> {
+    let distance = |a: &Embedding, b: &Embedding| {
+        a.value
+            .iter()
+            .zip(&b.value)
+            .map(|(a, b)| (a - b) * (a - b))
+            .sum::<f32>()
+            .sqrt()
+    };
+
+    let tolerances = groups
+        .iter()
+        .map(|group| {
+            if tolerance > 0.0 {
+                return tolerance;
+            }
+            group
+                .iter()
+                .map(|a| {
+                    group
+                        .iter()
+                        .filter(|b| b.id != a.id)
+                        .map(|b| distance(&a.value, &b.value))
+                        .fold(f32::INFINITY, f32::min)
+                })
+                .filter(|distance| distance.is_finite())
+                .fold(0.0, f32::max)
+        })
+        .collect::<Vec<_>>();
+
+    embeddings
+        .iter()
+        .map(|embedding| {
+            groups
+                .iter()
+                .enumerate()
+                .filter_map(|(i, group)| {
+                    group
+                        .iter()
+                        .map(|member| distance(&embedding.value, &member.value))
+                        .min_by(f32::total_cmp)
+                        .filter(|distance| *distance <= tolerances[i])
+                        .map(|distance| (i, distance))
+                })
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(i, _)| i)
+        })
+        .collect()
+}
+
+pub fn related_groups(groups: &[Vec<&Persisted<Embedding>>], k: usize) -> Vec<Vec<(usize, f32)>> {
+    let centroids = groups
+        .iter()
+        .map(|group| centroid(group))
+        .collect::<Vec<_>>();
+
+    let distances = centroids
+        .iter()
+        .enumerate()
+        .map(|(i, a)| {
+            centroids
+                .iter()
+                .enumerate()
+                .filter_map(|(j, b)| match (a, b) {
+                    (Some(a), Some(b)) if i != j => Some((j, centroid_distance(a, b))),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    let mut all_distances = distances
+        .iter()
+        .flatten()
+        .map(|(_, distance)| *distance)
+        .collect::<Vec<_>>();
+    all_distances.sort_by(f32::total_cmp);
+    let Some(median) = all_distances.get(all_distances.len() / 2).copied() else {
+        return vec![vec![]; groups.len()];
+    };
+
+    distances
+        .into_iter()
+        .map(|mut distances| {
+            distances.retain(|(_, distance)| *distance < median);
+            distances.sort_by(|a, b| a.1.total_cmp(&b.1));
+            distances.truncate(k);
+            distances
+        })
+        .collect()
+}
+
+const TOPIC_QUANTILE: usize = 10;
+
+/// links groups of different days into topics. every group joins the topic of the nearest group
+/// of an earlier day by distance between centroids, if that pair is among the closest tenth of
+/// all such pairs. returns indices of groups of every topic, in the order of their first groups.
+pub fn link_across_days(
+    groups: &[(chrono::NaiveDate, Vec<&Persisted<Embedding>>)],
+) -> Vec<Vec<usize>> {
+    let centroids = groups
+        .iter()
+        .map(|(_, group)| centroid(group))
+        .collect::<Vec<_>>();
+
+    let mut order = (0..groups.len()).collect::<Vec<_>>();
+    order.sort_by_key(|i| groups[*i].0);
+
+    // distances to groups of earlier days of every group
+    let distances = centroids
+        .iter()
+        .enumerate()
+        .map(|(i, a)| {
+            centroids
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| groups[*j].0 < groups[i].0)
+                .filter_map(|(j, b)| match (a, b) {
+                    (Some(a), Some(b)) => Some((j, centroid_distance(a, b))),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    let mut all_distances = distances
+        .iter()
+        .flatten()
+        .map(|(_, distance)| *distance)
+        .collect::<Vec<_>>();
+    all_distances.sort_by(f32::total_cmp);
+    let max_distance = all_distances
+        .get(all_distances.len() / TOPIC_QUANTILE)
+        .copied()
+        .unwrap_or_default();
+
+    let mut topic_of = vec![None; groups.len()];
+    let mut topics = Vec::<Vec<usize>>::new();
+    for i in order {
+        let topic = distances[i]
+            .iter()
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .filter(|(_, distance)| *distance <= max_distance)
+            .and_then(|(j, _)| topic_of[*j]);
+        let topic = topic.unwrap_or_else(|| {
+            topics.push(vec![]);
+            topics.len() - 1
+        });
+        topics[topic].push(i);
+        topic_of[i] = Some(topic);
+    }
+    topics
+}
+
+fn centroid(group: &[&Persisted<Embedding>]) -> Option<Vec<f32>> {
+    let first = group.first()?;
+    let mut centroid = vec![0.0; first.value.value.len()];
+    for embedding in group {
+        for (sum, value) in centroid.iter_mut().zip(&embedding.value.value) {
+            *sum += value;
+        }
+    }
+    let len = f32::from(u16::try_from(group.len()).expect("usize -> u16 failed"));
+    Some(
+        centroid
+            .into_iter()
+            .map(|sum| sum / len)
+            .collect::<Vec<_>>(),
+    )
+}
+
+fn centroid_distance(a: &[f32], b: &[f32]) -> f32 {
+    a.iter()
+        .zip(b)
+        .map(|(a, b)| (a - b) * (a - b))
+        .sum::<f32>()
+        .sqrt()
+}
+
+pub fn nearest(embeddings: &[Persisted<Embedding>], query: &[f32], k: usize) -> Vec<(usize, f32)> {
+    let indices = embeddings
+        .iter()
+        .enumerate()
+        .filter(|(_, embedding)| embedding.value.value.len() == query.len())
+        .map(|(i, _)| i)
+        .collect::<Vec<_>>();
+    if indices.is_empty() || k == 0 {
+        return vec![];
+    }
+
+    let vectors = indices
+        .iter()
+        .flat_map(|i| embeddings[*i].value.value.iter().copied())
+        .collect::<Vec<_>>();
+    let vectors: Array2<f32> =
+        Array2::from_shape_vec((indices.len(), query.len()), vectors).expect("invalid shape");
+    let query = ndarray::ArrayView1::from(query);
+
+    let ball_tree = BallTree::new()
+        .from_batch(&vectors, L2Dist)
+        .expect("failed to construct ball tree");
+    ball_tree
+        .k_nearest(query, k.min(indices.len()))
+        .expect("failed to get nearest")
+        .into_iter()
+        .map(|(point, i)| {
+            (
+                indices[i],
+                distance::Distance::distance(&L2Dist, query, point),
+            )
+        })
+        .collect()
+}
+
+fn l2(a: ndarray::ArrayView1<f64>, b: ndarray::ArrayView1<f64>) -> f64 {
+    (&a - &b).mapv(|value| value * value).sum().sqrt()
+}
+
+fn davies_bouldin(
+    vectors: &Array2<f64>,
+    clusters: &[Vec<usize>],
+    centroids: &[ndarray::Array1<f64>],
+) -> Option<f64> {
+    if clusters.len() < 2 {
+        return None;
+    }
+
+    let spreads = clusters
+        .iter()
+        .zip(centroids)
+        .map(|(cluster, centroid)| {
+            cluster
+                .iter()
+                .map(|i| l2(vectors.row(*i), centroid.view()))
+                .sum::<f64>()
+                / f64::from(u32::try_from(cluster.len()).expect("usize -> u32 failed"))
+        })
+        .collect::<Vec<_>>();
+
+    let sum = (0..clusters.len())
+        .map(|i| {
+            (0..clusters.len())
+                .filter(|j| *j != i)
+                .map(|j| {
+                    let distance = l2(centroids[i].view(), centroids[j].view());
+                    (spreads[i] + spreads[j]) / distance.max(f64::EPSILON)
+                })
+                .fold(0.0, f64::max)
+        })
+        .sum::<f64>();
+
+    Some(sum / f64::from(u32::try_from(clusters.len()).expect("usize -> u32 failed")))
+}
+
+fn calinski_harabasz(
+    vectors: &Array2<f64>,
+    clusters: &[Vec<usize>],
+    centroids: &[ndarray::Array1<f64>],
+) -> Option<f64> {
+    let points = clusters.iter().map(Vec::len).sum::<usize>();
+    if clusters.len() < 2 || points <= clusters.len() {
+        return None;
+    }
+
+    let indices = clusters.iter().flatten().copied().collect::<Vec<_>>();
+    let center = vectors
+        .select(ndarray::Axis(0), &indices)
+        .mean_axis(ndarray::Axis(0))
+        .expect("no points");
+
+    let as_f64 = |value: usize| f64::from(u32::try_from(value).expect("usize -> u32 failed"));
+    let between = clusters
+        .iter()
+        .zip(centroids)
+        .map(|(cluster, centroid)| {
+            as_f64(cluster.len()) * l2(centroid.view(), center.view()).powi(2)
+        })
+        .sum::<f64>();
+    let within = clusters
+        .iter()
+        .zip(centroids)
+        .flat_map(|(cluster, centroid)| {
+            cluster
+                .iter()
+                .map(move |i| l2(vectors.row(*i), centroid.view()).powi(2))
+        })
+        .sum::<f64>();
+    if within <= 0.0 {
+        return None;
+    }
+
+    Some((between / as_f64(clusters.len() - 1)) / (within / as_f64(points - clusters.len())))
+}
+
+async fn k_distance_dbscan(
+    vectors: &Array2<f32>,
+    min_points: usize,
+) -> Option<(Vec<Vec<usize>>, f32, f32)> {
+    let tolerance = k_distance_knee(vectors, min_points)?;
+    let (clusters, score) = dbscan(vectors, min_points, tolerance).await;
+    if clusters.is_empty() {
+        tracing::warn!(tolerance, "no clusters at k-distance knee");
+        return None;
+    }
+    Some((clusters, tolerance, score))
+}
+
+/// sorts distances of every point to its k-th nearest neighbour, and returns the distance where
+/// the curve bends the most: points below it are in dense areas, points above it are noise.
+///
+/// returns `None` if there is no knee, for example when there are too few points.
+fn k_distance_knee(vectors: &Array2<f32>, k: usize) -> Option<f32> {
+    if vectors.nrows() <= k + 1 {
+        return None;
+    }
+
+    let ball_tree = BallTree::new()
+        .from_batch(vectors, L2Dist)
+        .expect("failed to construct ball tree");
+    let mut k_distances = vectors
+        .rows()
+        .into_iter()
+        .map(|row| {
+            // nearest neighbour is the point itself
+            let neighbours = ball_tree
+                .k_nearest(row, k + 1)
+                .expect("failed to get nearest");
+            let (nearest, _) = neighbours.last().expect("no neighbours");
+            distance::Distance::distance(&L2Dist, row, nearest.view())
+        })
+        .collect::<Vec<_>>();
+    k_distances.sort_by(f32::total_cmp);
+
+    // the knee is the point furthest below the line between the first and the last point
+    let (first, last) = (k_distances[0], k_distances[k_distances.len() - 1]);
+    if last - first <= f32::EPSILON {
+        return None;
+    }
+    let steps = f64::from(u32::try_from(k_distances.len() - 1).expect("usize -> u32 failed"));
+    let (knee, gap) = k_distances
+        .iter()
+        .zip(0_u32..)
+        .map(|(k_distance, i)| {
+            let line = f64::from(first) + f64::from(last - first) * f64::from(i) / steps;
+            (*k_distance, line - f64::from(*k_distance))
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1))?;
+
+    (gap > 0.0).then_some(knee)
+}
+
+async fn grid_search_dbscan(vectors: &Array2<f32>, params: &Params) -> (Vec<Vec<usize>>, f32, f32) {
+    // first, run a grid search to find the best tolerance for the DBSCAN algorithm
+    let range = &params.tolerance_range;
+    let step = (range.end() - range.start()) / params.samples as f32;
+    let (mut best_clusters, mut best_tolerance, mut best_score, mut best_value) =
+        (vec![], 0.0, 0.0, 0.0);
+    for i in 0..params.samples {
+        let tolerance = range.start() + step * i as f32;
+        let (clusters, score) = dbscan(vectors, params.min_points, tolerance).await;
+        let value = Scores::new(vectors, &clusters, score).value(params.score_metric);
+        tracing::info!(tolerance = tolerance, score = ?score, value, clusters_len = clusters.len(), "sample");
+        if weighted(clusters.len(), value) > weighted(best_clusters.len(), best_value) {
+            best_clusters = clusters;
+            best_tolerance = tolerance;
+            best_score = score;
+            best_value = value;
+        } else if clusters.len() < best_clusters.len() {
+            // break once number of clusters starts to decrease
+            break;
+        }
+    }
+
+    (best_clusters, best_tolerance, best_score)
+}
+
+fn weighted(clusters_len: usize, value: f64) -> f64 {
+    f64::from(u32::try_from(clusters_len).expect("usize -> u32 failed")) * value
+}
+
+async fn hdbscan(vectors: &Array2<f32>, min_points: usize) -> (Vec<Vec<usize>>, f32) {
+    let (send, recv) = tokio::sync::oneshot::channel();
+
+    let vectors = vectors.clone();
+
+    rayon::spawn(move || {
+        let labels = hdbscan::fit(&vectors, min_points);
+
+        let clusters_len = labels.iter().flatten().max().map_or(0, |max| max + 1);
+        let mut clusters = vec![vec![]; clusters_len];
+        for (index, label) in labels.iter().enumerate() {
+            if let Some(label) = label {
+                clusters[*label].push(index);
+            }
+        }
+
+        // silhouette score is not defined for less than two clusters
+        let dataset = DatasetBase::new(vectors, ndarray::Array1::from(labels));
+        let silhouette_score = dataset.silhouette_score().unwrap_or(0.0);
+
+        let _ = send.send((clusters, silhouette_score));
+    });
+
+    recv.await.expect("panic in rayon::spawn")
 }
 
 async fn dbscan(
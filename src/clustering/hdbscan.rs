@@ -0,0 +1,320 @@
+//! hdbscan clustering, see <https://hdbscan.readthedocs.io/en/latest/how_hdbscan_works.html>
+
+use ndarray::{Array2, ArrayView1};
+use rayon::prelude::*;
+
+struct CondensedEdge {
+    parent: usize,
+    child: usize,
+    lambda: f64,
+    size: usize,
+}
+
+pub fn fit(vectors: &Array2<f32>, min_cluster_size: usize) -> Vec<Option<usize>> {
+    let points = vectors.nrows();
+    if points < min_cluster_size.max(2) {
+        return vec![None; points];
+    }
+
+    let distances = pairwise_distances(vectors);
+    let core_distances = core_distances(&distances, min_cluster_size);
+    let mut edges = minimum_spanning_tree(&distances, &core_distances);
+    edges.sort_by(|a, b| a.2.total_cmp(&b.2));
+    let dendrogram = single_linkage(points, &edges);
+    let condensed = condense(points, &dendrogram, min_cluster_size);
+    let selected = select_clusters(points, &condensed);
+    labels(points, &condensed, &selected)
+}
+
+fn distance(a: ArrayView1<f32>, b: ArrayView1<f32>) -> f64 {
+    a.iter()
+        .zip(b.iter())
+        .map(|(a, b)| f64::from(a - b).powi(2))
+        .sum::<f64>()
+        .sqrt()
+}
+
+fn pairwise_distances(vectors: &Array2<f32>) -> Vec<Vec<f64>> {
+    (0..vectors.nrows())
+        .into_par_iter()
+        .map(|i| {
+            (0..vectors.nrows())
+                .map(|j| distance(vectors.row(i), vectors.row(j)))
+                .collect()
+        })
+        .collect()
+}
+
+fn core_distances(distances: &[Vec<f64>], min_samples: usize) -> Vec<f64> {
+    distances
+        .iter()
+        .map(|row| {
+            let mut row = row.clone();
+            row.sort_by(f64::total_cmp);
+            row[(min_samples - 1).min(row.len() - 1)]
+        })
+        .collect()
+}
+
+fn minimum_spanning_tree(
+    distances: &[Vec<f64>],
+    core_distances: &[f64],
+) -> Vec<(usize, usize, f64)> {
+    let points = distances.len();
+    let mut in_tree = vec![false; points];
+    let mut best = vec![(f64::INFINITY, 0); points];
+    let mut edges = Vec::with_capacity(points - 1);
+
+    let mut current = 0;
+    in_tree[current] = true;
+    for _ in 1..points {
+        let mut next = None;
+        for other in 0..points {
+            if in_tree[other] {
+                continue;
+            }
+            let reachability = distances[current][other]
+                .max(core_distances[current])
+                .max(core_distances[other]);
+            if reachability < best[other].0 {
+                best[other] = (reachability, current);
+            }
+            if next.map_or(true, |next: usize| best[other].0 < best[next].0) {
+                next = Some(other);
+            }
+        }
+        let next = next.expect("tree is not complete");
+        in_tree[next] = true;
+        edges.push((best[next].1, next, best[next].0));
+        current = next;
+    }
+
+    edges
+}
+
+/// merges points along sorted edges. node `points + i` is created by the i-th merge and is
+/// `(left, right, distance, size)`.
+fn single_linkage(points: usize, edges: &[(usize, usize, f64)]) -> Vec<(usize, usize, f64, usize)> {
+    let mut parents = (0..2 * points).collect::<Vec<_>>();
+    let mut sizes = vec![1; 2 * points];
+
+    edges
+        .iter()
+        .enumerate()
+        .map(|(i, (a, b, distance))| {
+            let (a, b) = (find(&mut parents, *a), find(&mut parents, *b));
+            let node = points + i;
+            parents[a] = node;
+            parents[b] = node;
+            sizes[node] = sizes[a] + sizes[b];
+            (a, b, *distance, sizes[node])
+        })
+        .collect()
+}
+
+fn find(parents: &mut [usize], mut node: usize) -> usize {
+    while parents[node] != node {
+        parents[node] = parents[parents[node]];
+        node = parents[node];
+    }
+    node
+}
+
+fn leaves(points: usize, dendrogram: &[(usize, usize, f64, usize)], node: usize) -> Vec<usize> {
+    let mut stack = vec![node];
+    let mut leaves = vec![];
+    while let Some(node) = stack.pop() {
+        if node < points {
+            leaves.push(node);
+        } else {
+            let (left, right, _, _) = dendrogram[node - points];
+            stack.push(left);
+            stack.push(right);
+        }
+    }
+    leaves
+}
+
+/// walks the dendrogram from the root, and keeps only splits where both sides are large enough
+/// to be clusters. clusters are numbered from `points`, root is `points`.
+fn condense(
+    points: usize,
+    dendrogram: &[(usize, usize, f64, usize)],
+    min_cluster_size: usize,
+) -> Vec<CondensedEdge> {
+    let size = |node: usize| {
+        if node < points {
+            1
+        } else {
+            dendrogram[node - points].3
+        }
+    };
+
+    let mut condensed = vec![];
+    let mut next_cluster = points + 1;
+    let mut stack = vec![(2 * points - 2, points)];
+    while let Some((node, cluster)) = stack.pop() {
+        let (left, right, distance, _) = dendrogram[node - points];
+        let lambda = 1.0 / distance.max(1e-10);
+
+        let is_large = |node: usize| size(node) >= min_cluster_size;
+        match (is_large(left), is_large(right)) {
+            (true, true) => {
+                for child in [left, right] {
+                    condensed.push(CondensedEdge {
+                        parent: cluster,
+                        child: next_cluster,
+                        lambda,
+                        size: size(child),
+                    });
+                    stack.push((child, next_cluster));
+                    next_cluster += 1;
+                }
+            }
+            (left_is_large, right_is_large) => {
+                for (child, is_large) in [(left, left_is_large), (right, right_is_large)] {
+                    if is_large && child >= points {
+                        // cluster lost some points, but it continues
+                        stack.push((child, cluster));
+                    } else {
+                        for point in leaves(points, dendrogram, child) {
+                            condensed.push(CondensedEdge {
+                                parent: cluster,
+                                child: point,
+                                lambda,
+                                size: 1,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    condensed
+}
+
+/// excess of mass selection: a cluster is selected if it is more stable than its descendants.
+fn select_clusters(points: usize, condensed: &[CondensedEdge]) -> Vec<usize> {
+    let clusters = condensed
+        .iter()
+        .map(|edge| edge.parent.max(edge.child))
+        .max()
+        .map_or(0, |max| max + 1 - points);
+
+    let mut birth = vec![0.0; clusters];
+    let mut stability = vec![0.0; clusters];
+    let mut children = vec![vec![]; clusters];
+    for edge in condensed.iter().filter(|edge| edge.child >= points) {
+        birth[edge.child - points] = edge.lambda;
+        children[edge.parent - points].push(edge.child - points);
+    }
+    for edge in condensed {
+        let parent = edge.parent - points;
+        let size = f64::from(u32::try_from(edge.size).expect("usize -> u32 failed"));
+        stability[parent] += (edge.lambda - birth[parent]) * size;
+    }
+
+    let mut is_selected = vec![false; clusters];
+    // children always have larger numbers than their parents, root is never selected
+    for cluster in (1..clusters).rev() {
+        let children_stability = children[cluster]
+            .iter()
+            .map(|child| stability[*child])
+            .sum::<f64>();
+        if children_stability > stability[cluster] {
+            stability[cluster] = children_stability;
+        } else {
+            is_selected[cluster] = true;
+            let mut stack = children[cluster].clone();
+            while let Some(descendant) = stack.pop() {
+                is_selected[descendant] = false;
+                stack.extend(children[descendant].iter().copied());
+            }
+        }
+    }
+
+    (0..clusters)
+        .filter(|cluster| is_selected[*cluster])
+        .map(|cluster| cluster + points)
+        .collect()
+}
+
+fn labels(points: usize, condensed: &[CondensedEdge], selected: &[usize]) -> Vec<Option<usize>> {
+    let mut parents = std::collections::HashMap::new();
+    for edge in condensed {
+        parents.insert(edge.child, edge.parent);
+    }
+
+    (0..points)
+        .map(|point| {
+            let mut node = point;
+            while let Some(parent) = parents.get(&node) {
+                if let Some(label) = selected.iter().position(|cluster| cluster == parent) {
+                    return Some(label);
+                }
+                node = *parent;
+            }
+            None
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn blob(x: f32, y: f32) -> Vec<[f32; 2]> {
+        [(0.0, 0.0), (0.1, 0.0), (0.0, 0.1), (0.1, 0.1), (0.05, 0.05)]
+            .into_iter()
+            .map(|(dx, dy)| [x + dx, y + dy])
+            .collect()
+    }
+
+    fn vectors(points: &[[f32; 2]]) -> Array2<f32> {
+        Array2::from_shape_vec(
+            (points.len(), 2),
+            points.iter().flatten().copied().collect(),
+        )
+        .expect("valid shape")
+    }
+
+    #[test]
+    fn separates_clusters_and_noise() {
+        let points = [blob(0.0, 0.0), blob(10.0, 10.0), vec![[100.0, -100.0]]].concat();
+        let labels = fit(&vectors(&points), 3);
+
+        let first = labels[0].expect("first blob is a cluster");
+        let second = labels[5].expect("second blob is a cluster");
+        assert_ne!(first, second);
+        assert!(labels[..5].iter().all(|label| *label == Some(first)));
+        assert!(labels[5..10].iter().all(|label| *label == Some(second)));
+        assert_eq!(labels[10], None);
+    }
+
+    #[test]
+    fn clusters_are_at_least_min_cluster_size() {
+        let points = [blob(0.0, 0.0), blob(10.0, 10.0)].concat();
+        assert_eq!(fit(&vectors(&points), 6), vec![None; 10]);
+    }
+
+    #[test]
+    fn too_few_points_are_noise() {
+        assert_eq!(fit(&vectors(&blob(0.0, 0.0)), 6), vec![None; 5]);
+        assert_eq!(fit(&vectors(&[[0.0, 0.0]]), 1), vec![None]);
+        assert!(fit(&vectors(&[]), 2).is_empty());
+    }
+
+    #[test]
+    fn identical_points_are_a_cluster() {
+        let points = [vec![[1.0, 1.0]; 4], vec![[50.0, 50.0]; 4]].concat();
+        let labels = fit(&vectors(&points), 3);
+        assert!(labels[..4]
+            .iter()
+            .all(|label| label.is_some() && *label == labels[0]));
+        assert!(labels[4..]
+            .iter()
+            .all(|label| label.is_some() && *label == labels[4]));
+        assert_ne!(labels[0], labels[4]);
+    }
+}
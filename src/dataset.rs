@@ -0,0 +1,157 @@
+//! json lines export of entries, their fields and embeddings. reports are derived from them, and
+//! ids are not exported.
+
+use crate::{clustering, db, feeds, id::Id, md5_hash, quantization};
+use std::io::{BufRead, Write};
+
+const BATCH_SIZE: usize = 500;
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+struct Entry {
+    href: String,
+    feed_id: Id<feeds::Feed>,
+    published_at: chrono::DateTime<chrono::Utc>,
+    fields: Vec<Field>,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+struct Field {
+    name: String,
+    lang_code: String,
+    value: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    embeddings: Vec<Embedding>,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+struct Embedding {
+    model: String,
+    value: Vec<f32>,
+}
+
+type Error = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn export(db: &db::Client, out: &mut impl Write) -> Result<usize, Error> {
+    let mut count = 0;
+    let mut after = Id::from(0);
+    loop {
+        let entries = db
+            .list_entries_after_id(
+                after,
+                u32::try_from(BATCH_SIZE).expect("usize -> u32 failed"),
+            )
+            .await?;
+        let Some(last) = entries.last() else {
+            return Ok(count);
+        };
+        after = last.id;
+
+        for entry in entries {
+            let mut fields = vec![];
+            for (name, lang_code, value) in db.list_field_values_by_entry_id(entry.id).await? {
+                let embeddings = db
+                    .list_embeddings_by_md5_hash(&md5_hash::compute(&value))
+                    .await?
+                    .into_iter()
+                    .map(|embedding| Embedding {
+                        model: embedding.value.model,
+                        value: embedding.value.value,
+                    })
+                    .collect();
+                fields.push(Field {
+                    name: name.to_string(),
+                    lang_code: lang_code.to_string(),
+                    value,
+                    embeddings,
+                });
+            }
+
+            serde_json::to_writer(
+                &mut *out,
+                &Entry {
+                    href: entry.value.href.to_string(),
+                    feed_id: entry.value.feed_id,
+                    published_at: entry.value.published_at,
+                    fields,
+                },
+            )?;
+            out.write_all(b"\n")?;
+            count += 1;
+        }
+    }
+}
+
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn import(
+    db: &db::Client,
+    input: impl BufRead,
+    precision: quantization::Precision,
+) -> Result<usize, Error> {
+    let mut count = 0;
+    let mut batch = vec![];
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        batch.push(serde_json::from_str::<Entry>(&line)?);
+        if batch.len() == BATCH_SIZE {
+            count += import_batch(db, &std::mem::take(&mut batch), precision).await?;
+        }
+    }
+    count += import_batch(db, &batch, precision).await?;
+    Ok(count)
+}
+
+async fn import_batch(
+    db: &db::Client,
+    batch: &[Entry],
+    precision: quantization::Precision,
+) -> Result<usize, Error> {
+    let entries = batch
+        .iter()
+        .map(|entry| -> Result<feeds::CrawledEntry, Error> {
+            let fields = entry
+                .fields
+                .iter()
+                .map(|field| -> Result<_, Error> {
+                    Ok((
+                        field.name.parse()?,
+                        field.lang_code.parse()?,
+                        field.value.clone(),
+                    ))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok((
+                feeds::Entry {
+                    feed_id: entry.feed_id,
+                    href: entry.href.parse()?,
+                    published_at: entry.published_at,
+                    image_href: None,
+                    region: None,
+                },
+                fields,
+            ))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let inserted = db.insert_entries_batch(&entries).await?;
+
+    // embeddings are shared by equal values, so they are inserted for every entry
+    for field in batch.iter().flat_map(|entry| &entry.fields) {
+        for embedding in &field.embeddings {
+            db.insert_embeddig(
+                &clustering::Embedding {
+                    md5_hash: md5_hash::compute(&field.value),
+                    size: u32::try_from(embedding.value.len()).expect("usize -> u32 failed"),
+                    value: embedding.value.clone(),
+                    model: embedding.model.clone(),
+                },
+                precision,
+            )
+            .await?;
+        }
+    }
+
+    Ok(inserted.len())
+}
@@ -1,46 +1,455 @@
 use crate::{
+    background::{self, tasks},
     clustering::{self, Embedding, ReportGroup},
-    feeds,
+    feeds, filters,
     id::Id,
-    md5_hash::Md5Hash,
+    md5_hash::{self, Md5Hash},
+    openai,
     persisted::Persisted,
-    web,
+    push, quantization, social, web,
 };
 
+/// max rows of a single multi-row insert, keeps the number of bound variables below sqlite's limit
+const BATCH_SIZE: usize = 500;
+
 #[derive(Clone)]
 pub struct Client {
     pool: sqlx::SqlitePool,
 }
 
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub max_connections: u32,
+    pub busy_timeout: std::time::Duration,
+}
+
+pub type GroupEmbedding = (Id<ReportGroup>, Persisted<Embedding>);
+
+pub type EntrySimhash = (Id<feeds::Entry>, Id<feeds::Feed>, u64);
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            max_connections: 10,
+            busy_timeout: std::time::Duration::from_secs(5),
+        }
+    }
+}
+
 impl Client {
-    pub async fn new<P: AsRef<std::path::Path>>(filename: P) -> Result<Self, sqlx::Error> {
+    pub async fn new<P: AsRef<std::path::Path>>(
+        filename: P,
+        config: &Config,
+    ) -> Result<Self, sqlx::Error> {
         let opts = sqlx::sqlite::SqliteConnectOptions::new()
             .filename(filename)
             .create_if_missing(true);
+        Self::connect_with(opts, config).await
+    }
+
+    pub async fn connect(url: &str, config: &Config) -> Result<Self, sqlx::Error> {
+        if !url.starts_with("sqlite:") {
+            return Err(sqlx::Error::Configuration(
+                format!("unsupported database url {url}, only sqlite: urls are supported").into(),
+            ));
+        }
+        let opts = <sqlx::sqlite::SqliteConnectOptions as std::str::FromStr>::from_str(url)?
+            .create_if_missing(true);
+        Self::connect_with(opts, config).await
+    }
 
-        let pool = sqlx::SqlitePool::connect_with(opts).await?;
+    async fn connect_with(
+        opts: sqlx::sqlite::SqliteConnectOptions,
+        config: &Config,
+    ) -> Result<Self, sqlx::Error> {
+        // wal lets readers of the web server run while background jobs write, and normal sync is
+        // safe with wal
+        let opts = opts
+            .journal_mode(sqlx::sqlite::SqliteJournalMode::Wal)
+            .synchronous(sqlx::sqlite::SqliteSynchronous::Normal)
+            .busy_timeout(config.busy_timeout);
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(config.max_connections)
+            .connect_with(opts)
+            .await?;
 
         sqlx::migrate!("./migrations").run(&pool).await?;
+        convert_json_embeddings(&pool).await?;
+        canonicalize_entry_hrefs(&pool).await?;
         Ok(Self { pool })
     }
 }
 
 impl Client {
+    // entries are inserted with [`Self::insert_entries_batch`], tests insert them one by one
+    #[cfg(test)]
     #[tracing::instrument(level = "debug", skip_all, fields(href = %entry.href))]
     pub async fn insert_entry(
         &self,
         entry: &feeds::Entry,
     ) -> Result<Option<Persisted<feeds::Entry>>, sqlx::Error> {
         sqlx::query_as(
-            "INSERT OR IGNORE INTO entries (href, feed_id, published_at) VALUES ( ?, ?, ?) RETURNING *",
+            "INSERT OR IGNORE INTO entries (href, feed_id, published_at, image_href, region) VALUES ( ?, ?, ?, ?, ?) RETURNING *",
         )
         .bind(entry.href.to_string())
         .bind(entry.feed_id)
         .bind(entry.published_at)
+        .bind(&entry.image_href)
+        .bind(&entry.region)
         .fetch_optional(&self.pool)
         .await
     }
 
+    #[tracing::instrument(level = "debug", skip_all, fields(entries = entries.len()))]
+    pub async fn insert_entries_batch(
+        &self,
+        entries: &[feeds::CrawledEntry],
+    ) -> Result<Vec<Persisted<feeds::Entry>>, sqlx::Error> {
+        let mut transaction = self.pool.begin().await?;
+
+        let mut inserted: Vec<Persisted<feeds::Entry>> = vec![];
+        for chunk in entries.chunks(BATCH_SIZE) {
+            let mut query = sqlx::QueryBuilder::new(
+                "INSERT OR IGNORE INTO entries (href, feed_id, published_at, image_href, region) ",
+            );
+            query.push_values(chunk, |mut row, (entry, _)| {
+                row.push_bind(entry.href.to_string())
+                    .push_bind(entry.feed_id)
+                    .push_bind(entry.published_at)
+                    .push_bind(entry.image_href.clone())
+                    .push_bind(entry.region.clone());
+            });
+            query.push(" RETURNING *");
+            inserted.extend(
+                query
+                    .build_query_as::<Persisted<feeds::Entry>>()
+                    .fetch_all(&mut *transaction)
+                    .await?,
+            );
+        }
+
+        let ids_by_href = inserted
+            .iter()
+            .map(|entry| (entry.value.href.to_string(), entry.id))
+            .collect::<std::collections::HashMap<_, _>>();
+        let fields = entries
+            .iter()
+            .filter_map(|(entry, fields)| {
+                ids_by_href
+                    .get(&entry.href.to_string())
+                    .map(|entry_id| (*entry_id, fields))
+            })
+            .flat_map(|(entry_id, fields)| {
+                fields.iter().map(move |(name, lang_code, value)| {
+                    (entry_id, name, lang_code, md5_hash::compute(value), value)
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for chunk in fields.chunks(BATCH_SIZE) {
+            let mut query =
+                sqlx::QueryBuilder::new("INSERT OR IGNORE INTO translations (md5_hash, value) ");
+            query.push_values(chunk, |mut row, (_, _, _, md5_hash, value)| {
+                row.push_bind(*md5_hash).push_bind(value.as_str());
+            });
+            query.build().execute(&mut *transaction).await?;
+
+            let mut query = sqlx::QueryBuilder::new(
+                "INSERT OR IGNORE INTO fields (entry_id, name, lang_code, md5_hash) ",
+            );
+            query.push_values(
+                chunk,
+                |mut row, (entry_id, name, lang_code, md5_hash, _)| {
+                    row.push_bind(*entry_id)
+                        .push_bind(name.to_string())
+                        .push_bind(lang_code.to_string())
+                        .push_bind(*md5_hash);
+                },
+            );
+            query.build().execute(&mut *transaction).await?;
+        }
+
+        transaction.commit().await?;
+
+        Ok(inserted)
+    }
+
+    /// descriptions are replaced with their latest values so that they are embedded anew, titles
+    /// keep their first values, which translations are of.
+    #[tracing::instrument(level = "debug", skip_all, fields(entries = entries.len()))]
+    pub async fn insert_field_revisions(
+        &self,
+        entries: &[feeds::CrawledEntry],
+    ) -> Result<usize, sqlx::Error> {
+        use sqlx::Row;
+
+        let mut seen = std::collections::HashSet::new();
+        let entries = entries
+            .iter()
+            .filter(|(entry, _)| seen.insert(entry.href.to_string()))
+            .collect::<Vec<_>>();
+
+        let mut transaction = self.pool.begin().await?;
+
+        let mut latest = std::collections::HashMap::new();
+        for chunk in entries.chunks(BATCH_SIZE) {
+            let mut query = sqlx::QueryBuilder::new(
+                "
+                SELECT
+                    entries.href AS href,
+                    fields.entry_id AS entry_id,
+                    fields.name AS name,
+                    fields.lang_code AS lang_code,
+                    COALESCE(
+                        (
+                            SELECT field_revisions.md5_hash
+                            FROM field_revisions
+                            WHERE
+                                field_revisions.entry_id = fields.entry_id
+                                AND field_revisions.name = fields.name
+                                AND field_revisions.lang_code = fields.lang_code
+                            ORDER BY field_revisions.id DESC
+                            LIMIT 1
+                        ),
+                        fields.md5_hash
+                    ) AS md5_hash
+                FROM
+                    entries
+                        JOIN fields ON fields.entry_id = entries.id
+                WHERE
+                    entries.href IN (",
+            );
+            let mut hrefs = query.separated(", ");
+            for (entry, _) in chunk {
+                hrefs.push_bind(entry.href.to_string());
+            }
+            hrefs.push_unseparated(")");
+            for row in query.build().fetch_all(&mut *transaction).await? {
+                let href: String = row.try_get("href")?;
+                let name: String = row.try_get("name")?;
+                let lang_code: String = row.try_get("lang_code")?;
+                let entry_id: Id<feeds::Entry> = row.try_get("entry_id")?;
+                let md5_hash: md5_hash::Md5Hash = row.try_get("md5_hash")?;
+                latest.insert((href, name, lang_code), (entry_id, md5_hash));
+            }
+        }
+
+        let revisions = entries
+            .iter()
+            .flat_map(|(entry, fields)| {
+                fields.iter().map(move |(name, lang_code, value)| {
+                    (entry, name, lang_code, md5_hash::compute(value), value)
+                })
+            })
+            .filter_map(|(entry, name, lang_code, md5_hash, value)| {
+                let key = (
+                    entry.href.to_string(),
+                    name.to_string(),
+                    lang_code.to_string(),
+                );
+                let (entry_id, latest_md5_hash) = latest.get(&key)?;
+                (*latest_md5_hash != md5_hash)
+                    .then_some((*entry_id, name, lang_code, md5_hash, value))
+            })
+            .collect::<Vec<_>>();
+
+        for chunk in revisions.chunks(BATCH_SIZE) {
+            let mut query =
+                sqlx::QueryBuilder::new("INSERT OR IGNORE INTO translations (md5_hash, value) ");
+            query.push_values(chunk, |mut row, (_, _, _, md5_hash, value)| {
+                row.push_bind(*md5_hash).push_bind(value.as_str());
+            });
+            query.build().execute(&mut *transaction).await?;
+
+            for (entry_id, name, lang_code, _, _) in chunk {
+                sqlx::query(
+                    "
+                    INSERT INTO field_revisions (entry_id, name, lang_code, md5_hash, created_at)
+                    SELECT entry_id, name, lang_code, md5_hash, created_at
+                    FROM fields
+                    WHERE
+                        entry_id = $1
+                        AND name = $2
+                        AND lang_code = $3
+                        AND NOT EXISTS (
+                            SELECT 1
+                            FROM field_revisions
+                            WHERE entry_id = $1 AND name = $2 AND lang_code = $3
+                        )
+                    ",
+                )
+                .bind(entry_id)
+                .bind(name.to_string())
+                .bind(lang_code.to_string())
+                .execute(&mut *transaction)
+                .await?;
+            }
+
+            let mut query = sqlx::QueryBuilder::new(
+                "INSERT INTO field_revisions (entry_id, name, lang_code, md5_hash) ",
+            );
+            query.push_values(
+                chunk,
+                |mut row, (entry_id, name, lang_code, md5_hash, _)| {
+                    row.push_bind(*entry_id)
+                        .push_bind(name.to_string())
+                        .push_bind(lang_code.to_string())
+                        .push_bind(*md5_hash);
+                },
+            );
+            query.build().execute(&mut *transaction).await?;
+        }
+
+        for (entry_id, _, lang_code, md5_hash, _) in revisions
+            .iter()
+            .filter(|(_, name, _, _, _)| **name == feeds::FieldName::Description)
+        {
+            let previous_md5_hash: Md5Hash = sqlx::query_scalar(
+                "SELECT md5_hash FROM fields WHERE entry_id = ? AND name = ? AND lang_code = ?",
+            )
+            .bind(entry_id)
+            .bind(feeds::FieldName::Description.to_string())
+            .bind(lang_code.to_string())
+            .fetch_one(&mut *transaction)
+            .await?;
+            sqlx::query(
+                "UPDATE fields SET md5_hash = ? WHERE entry_id = ? AND name = ? AND lang_code = ?",
+            )
+            .bind(md5_hash)
+            .bind(entry_id)
+            .bind(feeds::FieldName::Description.to_string())
+            .bind(lang_code.to_string())
+            .execute(&mut *transaction)
+            .await?;
+            sqlx::query(
+                "UPDATE embeddings SET superseded_at = CURRENT_TIMESTAMP WHERE md5_hash = ? AND NOT EXISTS (SELECT 1 FROM fields WHERE fields.md5_hash = embeddings.md5_hash)",
+            )
+            .bind(previous_md5_hash)
+            .execute(&mut *transaction)
+            .await?;
+            // descriptions can change back
+            sqlx::query("UPDATE embeddings SET superseded_at = NULL WHERE md5_hash = ?")
+                .bind(md5_hash)
+                .execute(&mut *transaction)
+                .await?;
+        }
+
+        transaction.commit().await?;
+
+        Ok(revisions.len())
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn list_field_revisions_by_entry_id(
+        &self,
+        entry_id: Id<feeds::Entry>,
+    ) -> Result<Vec<web::FieldRevisionView>, sqlx::Error> {
+        sqlx::query_as(
+            "
+            SELECT
+                field_revisions.name AS name,
+                field_revisions.lang_code AS lang_code,
+                translations.value AS value,
+                field_revisions.created_at AS created_at
+            FROM
+                field_revisions
+                    JOIN translations ON translations.md5_hash = field_revisions.md5_hash
+            WHERE
+                field_revisions.entry_id = ?
+            ORDER BY
+                field_revisions.id
+            ",
+        )
+        .bind(entry_id)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn update_entry_image_href(
+        &self,
+        id: Id<feeds::Entry>,
+        image_href: &crate::url::Url,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE entries SET image_href = ? WHERE id = ?")
+            .bind(image_href)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn update_entry_paywalled(
+        &self,
+        id: Id<feeds::Entry>,
+        paywalled: bool,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE entries SET paywalled = ? WHERE id = ?")
+            .bind(paywalled)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn update_entry_simhash(
+        &self,
+        id: Id<feeds::Entry>,
+        simhash: u64,
+        duplicate_of: Option<Id<feeds::Entry>>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE entries SET simhash = ?, duplicate_of = ? WHERE id = ?")
+            // sqlite integers are signed
+            .bind(i64::from_ne_bytes(simhash.to_ne_bytes()))
+            .bind(duplicate_of)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn list_original_entry_simhashes_by_published_at(
+        &self,
+        published_at: &std::ops::Range<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<Vec<EntrySimhash>, sqlx::Error> {
+        let rows: Vec<(Id<feeds::Entry>, Id<feeds::Feed>, i64)> = sqlx::query_as(
+            "
+            SELECT id, feed_id, simhash
+            FROM entries
+            WHERE
+                DATETIME(published_at) >= DATETIME(?)
+                AND DATETIME(published_at) < DATETIME(?)
+                AND simhash IS NOT NULL
+                AND duplicate_of IS NULL
+            ORDER BY published_at, id
+            ",
+        )
+        .bind(published_at.start)
+        .bind(published_at.end)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(|(id, feed_id, simhash)| (id, feed_id, u64::from_ne_bytes(simhash.to_ne_bytes())))
+            .collect())
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn list_entries_after_id(
+        &self,
+        after: Id<feeds::Entry>,
+        limit: u32,
+    ) -> Result<Vec<Persisted<feeds::Entry>>, sqlx::Error> {
+        sqlx::query_as("SELECT * FROM entries WHERE id > ? ORDER BY id LIMIT ?")
+            .bind(after)
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await
+    }
+
     #[tracing::instrument(level = "debug", skip(self))]
     pub async fn find_entry_by_id(
         &self,
@@ -51,6 +460,24 @@ impl Client {
             .fetch_one(&self.pool)
             .await
     }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn list_entries_by_embedding_id(
+        &self,
+        embedding_id: &Id<clustering::Embedding>,
+    ) -> Result<Vec<Persisted<feeds::Entry>>, sqlx::Error> {
+        sqlx::query_as(
+            "SELECT entries.*
+            FROM entries
+            JOIN fields ON fields.entry_id = entries.id
+            JOIN embeddings ON embeddings.md5_hash = fields.md5_hash
+            WHERE embeddings.id = ?
+            GROUP BY entries.id",
+        )
+        .bind(embedding_id)
+        .fetch_all(&self.pool)
+        .await
+    }
 }
 
 impl Client {
@@ -83,316 +510,3891 @@ impl Client {
     }
 
     #[tracing::instrument(level = "debug", skip(self))]
-    pub async fn list_fields_by_md5_hash(
-        &self,
-        md5_hash: &Md5Hash,
-    ) -> Result<Vec<Persisted<feeds::Field>>, sqlx::Error> {
-        sqlx::query_as("SELECT * FROM fields WHERE md5_hash = ?")
-            .bind(md5_hash)
-            .fetch_all(&self.pool)
-            .await
-    }
-}
-
-impl Client {
-    #[tracing::instrument(level = "debug", skip_all, fields(md5_hash = ?embedding.md5_hash, size = %embedding.size))]
-    pub async fn insert_embeddig(
+    pub async fn list_field_values_by_entry_id(
         &self,
-        embedding: &clustering::Embedding,
-    ) -> Result<Option<Persisted<clustering::Embedding>>, sqlx::Error> {
+        entry_id: Id<feeds::Entry>,
+    ) -> Result<Vec<(feeds::FieldName, feeds::LanguageCode, String)>, sqlx::Error> {
         sqlx::query_as(
-            "INSERT OR IGNORE INTO embeddings (md5_hash, value, size) VALUES ( ?, ?, ? ) RETURNING *",
+            "SELECT fields.name, fields.lang_code, translations.value FROM fields JOIN translations ON translations.md5_hash = fields.md5_hash WHERE fields.entry_id = ? ORDER BY fields.id",
         )
-        .bind(embedding.md5_hash)
-        .bind(serde_json::to_string(&embedding.value).expect("failed to serialize embedding"))
-        .bind(embedding.size)
-        .fetch_optional(&self.pool)
+        .bind(entry_id)
+        .fetch_all(&self.pool)
         .await
     }
 
     #[tracing::instrument(level = "debug", skip(self))]
-    pub async fn list_embeddings_by_field_name_lang_code_date(
+    pub async fn find_latest_report_group_id_by_entry_id(
         &self,
-        field_name: feeds::FieldName,
-        lang_code: feeds::LanguageCode,
-        date: chrono::NaiveDate,
-    ) -> Result<Vec<Persisted<clustering::Embedding>>, sqlx::Error> {
-        let date = date
-            .and_hms_opt(0, 0, 0)
-            .expect("failed to create start of day");
-
-        sqlx::query_as(
-            "SELECT embeddings.*
-            FROM embeddings
-            JOIN fields ON
-                fields.md5_hash = embeddings.md5_hash
-                AND fields.lang_code = $1
-                AND fields.name = $2
-            JOIN entries ON
-                entries.id = fields.entry_id
+        entry_id: Id<feeds::Entry>,
+    ) -> Result<Option<Id<clustering::ReportGroup>>, sqlx::Error> {
+        sqlx::query_scalar(
+            "
+            SELECT
+                MAX(report_group_embeddings.report_group_id)
+            FROM
+                fields
+                    JOIN embeddings ON embeddings.md5_hash = fields.md5_hash
+                    JOIN report_group_embeddings ON report_group_embeddings.embedding_id = embeddings.id
             WHERE
-                entries.published_at >= DATETIME($3, 'start of day')
-                AND entries.published_at < DATETIME($3, 'start of day', '+1 day')
-            GROUP BY embeddings.md5_hash
+                fields.entry_id = ?
             ",
         )
-        .bind(lang_code.to_string())
-        .bind(field_name.to_string())
-        .bind(date)
-        .fetch_all(&self.pool)
+        .bind(entry_id)
+        .fetch_one(&self.pool)
         .await
     }
 
     #[tracing::instrument(level = "debug", skip(self))]
-    pub async fn find_embedding_by_id(
+    pub async fn find_field_by_id(
         &self,
-        id: &Id<clustering::Embedding>,
-    ) -> Result<Persisted<clustering::Embedding>, sqlx::Error> {
-        sqlx::query_as("SELECT * FROM embeddings WHERE id = ?")
+        id: Id<feeds::Field>,
+    ) -> Result<Persisted<feeds::Field>, sqlx::Error> {
+        sqlx::query_as("SELECT * FROM fields WHERE id = ?")
             .bind(id)
             .fetch_one(&self.pool)
             .await
     }
-}
-
-impl Client {
-    #[tracing::instrument(level = "debug", skip_all, fields(md5_hash = ?transaslation.md5_hash))]
-    pub async fn insert_translation(
-        &self,
-        transaslation: feeds::Translation,
-    ) -> Result<Option<Persisted<feeds::Translation>>, sqlx::Error> {
-        sqlx::query_as(
-            "INSERT OR IGNORE INTO translations (md5_hash, value) VALUES (?, ?) RETURNING *",
-        )
-        .bind(transaslation.md5_hash)
-        .bind(transaslation.value.to_string())
-        .fetch_optional(&self.pool)
-        .await
-    }
-
-    #[tracing::instrument(level = "debug", skip(self), fields(md5_hash = ?md5_hash))]
-    pub async fn find_translation_by_md5_hash(
-        &self,
-        md5_hash: &Md5Hash,
-    ) -> Result<Persisted<feeds::Translation>, sqlx::Error> {
-        sqlx::query_as("SELECT * FROM translations WHERE md5_hash = ?")
-            .bind(md5_hash)
-            .fetch_one(&self.pool)
-            .await
-    }
 
     #[tracing::instrument(level = "debug", skip(self))]
-    pub async fn list_translations_without_embeddings_by_lang_code_field_name_date(
+    pub async fn list_fields_without_embeddings_by_lang_code_field_name_date_model(
         &self,
         language_code: feeds::LanguageCode,
         field_name: feeds::FieldName,
         date: &chrono::NaiveDate,
-    ) -> Result<Vec<Persisted<feeds::Translation>>, sqlx::Error> {
+        model: &str,
+    ) -> Result<Vec<Persisted<feeds::Field>>, sqlx::Error> {
         let date = date
             .and_hms_opt(0, 0, 0)
             .expect("failed to create start of day");
-        sqlx::query_as("SELECT translations.*
-                        FROM translations
-                        JOIN fields
-                            ON fields.md5_hash = translations.md5_hash
-                            AND fields.lang_code = $2
-                            AND fields.name = $3
+        sqlx::query_as("SELECT fields.*
+                        FROM fields
                         JOIN entries
                             ON entries.id = fields.entry_id
                         WHERE
-                            entries.published_at >= DATETIME($1, 'start of day')
+                            fields.lang_code = $2
+                                AND fields.name = $3
+                                AND entries.published_at >= DATETIME($1, 'start of day')
                                 AND entries.published_at < DATETIME($1, 'start of day', '+1 day')
-                                AND NOT EXISTS (SELECT 1 FROM embeddings WHERE embeddings.md5_hash = translations.md5_hash)
-                        GROUP BY translations.md5_hash")
+                                AND EXISTS (SELECT 1 FROM translations WHERE translations.md5_hash = fields.md5_hash)
+                                AND NOT EXISTS (SELECT 1 FROM embeddings WHERE embeddings.md5_hash = fields.md5_hash AND embeddings.model = $4)
+                        GROUP BY fields.md5_hash")
             .bind(date)
             .bind(language_code)
             .bind(field_name)
+            .bind(model)
+            .fetch_all(&self.pool)
+            .await
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn list_fields_by_md5_hash(
+        &self,
+        md5_hash: &Md5Hash,
+    ) -> Result<Vec<Persisted<feeds::Field>>, sqlx::Error> {
+        sqlx::query_as("SELECT * FROM fields WHERE md5_hash = ?")
+            .bind(md5_hash)
             .fetch_all(&self.pool)
             .await
     }
 }
 
 impl Client {
-    #[tracing::instrument(level = "debug", skip_all)]
-    pub async fn insert_report_group(
+    #[tracing::instrument(level = "debug", skip_all, fields(feed_id = %fetch.feed_id))]
+    pub async fn insert_feed_fetch(
         &self,
-        group: clustering::ReportGroup,
-    ) -> Result<Persisted<clustering::ReportGroup>, sqlx::Error> {
-        use sqlx::{Executor, Row};
-
-        let mut transaction = self.pool.begin().await?;
-
-        let group_insert_result = transaction
-            .fetch_one(
-                sqlx::query(
-                    "INSERT INTO report_groups (report_id, center_embedding_id) VALUES (?, ?) RETURNING id",
-                )
-                .bind(group.report_id)
-                .bind(group.center_embedding_id),
-            )
-            .await?;
-        let group_id = group_insert_result.try_get("id")?;
-
-        for embedding_id in &group.embedding_ids {
-            transaction.execute(
-                sqlx::query("INSERT INTO report_group_embeddings (report_group_id, embedding_id) VALUES (?, ?)")
-                    .bind(group_id)
-                    .bind(embedding_id),
-            ).await?;
-        }
-
-        transaction.commit().await?;
-
-        Ok(Persisted {
-            id: group_id,
-            created_at: chrono::Utc::now(),
-            value: group.clone(),
-        })
+        fetch: &feeds::Fetch,
+    ) -> Result<Persisted<feeds::Fetch>, sqlx::Error> {
+        sqlx::query_as(
+            "INSERT INTO feed_fetches (feed_id, duration_ms, entries_count, error) VALUES (?, ?, ?, ?) RETURNING *",
+        )
+        .bind(fetch.feed_id)
+        .bind(fetch.duration_ms)
+        .bind(fetch.entries_count)
+        .bind(&fetch.error)
+        .fetch_one(&self.pool)
+        .await
     }
 
     #[tracing::instrument(level = "debug", skip(self))]
-    pub async fn insert_report(
+    pub async fn insert_openai_usage(
         &self,
-        report: &clustering::Report,
-    ) -> Result<Persisted<clustering::Report>, sqlx::Error> {
+        usage: &openai::Usage,
+    ) -> Result<Persisted<openai::Usage>, sqlx::Error> {
         sqlx::query_as(
-            "INSERT INTO reports (score, min_points, tolerance, rows, dimentions) VALUES (?, ?, ?, ?, ?) RETURNING *",
+            "INSERT INTO openai_usage (model, prompt_tokens, completion_tokens, cost_usd) VALUES (?, ?, ?, ?) RETURNING *",
         )
-        .bind(report.score)
-        .bind(report.min_points)
-        .bind(report.tolerance)
-        .bind(report.rows)
-        .bind(report.dimentions)
+        .bind(&usage.model)
+        .bind(usage.prompt_tokens)
+        .bind(usage.completion_tokens)
+        .bind(usage.cost_usd)
         .fetch_one(&self.pool)
         .await
     }
 
     #[tracing::instrument(level = "debug", skip(self))]
-    pub async fn list_report_group_entries_by_date_lang_code(
+    pub async fn sum_openai_cost_by_date(
         &self,
-        date: chrono::NaiveDate,
-        lang_code: &feeds::LanguageCode,
-    ) -> Result<Vec<web::GroupEntryView>, sqlx::Error> {
+        date: &chrono::NaiveDate,
+    ) -> Result<f64, sqlx::Error> {
         let date = date
             .and_hms_opt(0, 0, 0)
             .expect("failed to create start of day");
-        sqlx::query_as(
-            "
-            SELECT
-                entries.group_id AS group_id,
-                entries.is_center AS is_center,
-                entries.href AS href,
-                entries.published_at AS published_at,
-                entries.feed_id AS feed_id,
-                translations.value AS title
+
+        sqlx::query_scalar(
+            "SELECT
+                COALESCE(SUM(cost_usd), 0.0)
             FROM
-                fields
-                    JOIN translations ON translations.md5_hash = fields.md5_hash
-                    JOIN (
+                openai_usage
+            WHERE
+                created_at >= DATETIME($1, 'start of day')
+                AND created_at < DATETIME($1, 'start of day', '+1 day')",
+        )
+        .bind(date)
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn count_consecutive_feed_failures(
+        &self,
+        feed_id: &Id<feeds::Feed>,
+    ) -> Result<u32, sqlx::Error> {
+        sqlx::query_scalar(
+            "SELECT
+                COUNT(*)
+            FROM
+                feed_fetches
+            WHERE
+                feed_id = $1
+                AND error IS NOT NULL
+                AND id > COALESCE((SELECT MAX(id) FROM feed_fetches WHERE feed_id = $1 AND error IS NULL), 0)",
+        )
+        .bind(feed_id)
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn list_feed_statuses(&self) -> Result<Vec<web::FeedStatusView>, sqlx::Error> {
+        sqlx::query_as(
+            "
+            SELECT
+                feed_fetches.feed_id AS feed_id,
+                MAX(CASE WHEN feed_fetches.error IS NULL THEN feed_fetches.created_at END) AS last_success_at,
+                (
+                    SELECT
+                        COUNT(*)
+                    FROM
+                        feed_fetches AS failures
+                    WHERE
+                        failures.feed_id = feed_fetches.feed_id
+                        AND failures.error IS NOT NULL
+                        AND failures.id > COALESCE((
                             SELECT
-                                entries.id AS id,
-                                (report_groups.center_embedding_id = embeddings.id) AS is_center,
-                                report_group_embeddings.report_group_id AS group_id,
-                                entries.href AS href,
-                                entries.published_at AS published_at,
-                                entries.feed_id AS feed_id
+                                MAX(successes.id)
                             FROM
-                                report_group_embeddings
-                                    JOIN report_groups ON report_group_embeddings.report_group_id = report_groups.id
-                                    JOIN embeddings ON embeddings.id = report_group_embeddings.embedding_id
-                                    JOIN fields ON fields.md5_hash = embeddings.md5_hash
-                                    JOIN entries ON entries.id = fields.entry_id
+                                feed_fetches AS successes
                             WHERE
-                                report_groups.report_id = (
-                                    SELECT
-                                        id
-                                    FROM
-                                        reports
-                                    WHERE
-                                        created_at >= DATETIME($1, 'start of day')
-                                            AND created_at < DATETIME($1, 'start of day', '+1 day')
-                                    ORDER BY
-                                        created_at DESC
-                                    LIMIT 1
-                                )
-                        ) AS entries ON entries.id = fields.entry_id
-            WHERE
-                fields.lang_code = $2
-                AND fields.name = 'title'
-            ORDER BY
-                entries.published_at DESC
+                                successes.feed_id = feed_fetches.feed_id
+                                AND successes.error IS NULL
+                        ), 0)
+                ) AS consecutive_failures,
+                (
+                    SELECT
+                        latest.error
+                    FROM
+                        feed_fetches AS latest
+                    WHERE
+                        latest.feed_id = feed_fetches.feed_id
+                    ORDER BY
+                        latest.id DESC
+                    LIMIT 1
+                ) AS last_error,
+                AVG(feed_fetches.duration_ms) AS average_duration_ms,
+                (
+                    SELECT
+                        COUNT(*)
+                    FROM
+                        entries
+                    WHERE
+                        entries.feed_id = feed_fetches.feed_id
+                        AND entries.created_at >= DATETIME('now', '-7 days')
+                ) / 7.0 AS entries_per_day
+            FROM
+                feed_fetches
+            GROUP BY
+                feed_fetches.feed_id
             ",
         )
-        .bind(date)
-        .bind(lang_code)
         .fetch_all(&self.pool)
         .await
     }
+}
+
+impl Client {
+    /// takes the lease unless another holder has it and it is not expired yet, returns true when
+    /// the lease is taken. holder can take the same lease again to extend it.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn acquire_lease(
+        &self,
+        name: &str,
+        holder: &str,
+        expires_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            "INSERT INTO leases (name, holder, expires_at) VALUES (?1, ?2, ?3)
+             ON CONFLICT (name) DO UPDATE SET holder = excluded.holder, expires_at = excluded.expires_at
+             WHERE leases.holder = excluded.holder OR leases.expires_at <= ?4",
+        )
+        .bind(name)
+        .bind(holder)
+        .bind(timestamp(expires_at))
+        .bind(timestamp(chrono::Utc::now()))
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn release_lease(&self, name: &str, holder: &str) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM leases WHERE name = ? AND holder = ?")
+            .bind(name)
+            .bind(holder)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
+
+impl Client {
+    #[tracing::instrument(level = "debug", skip_all, fields(name = %run.name))]
+    pub async fn insert_job_run(
+        &self,
+        run: &background::JobRun,
+    ) -> Result<Persisted<background::JobRun>, sqlx::Error> {
+        sqlx::query_as(
+            "INSERT INTO job_runs (name, started_at, finished_at, crawl_ms, embed_ms, report_ms, entries_count, embeddings_count, groups_count, error) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?) RETURNING *",
+        )
+        .bind(&run.name)
+        .bind(run.started_at)
+        .bind(run.finished_at)
+        .bind(run.crawl_ms)
+        .bind(run.embed_ms)
+        .bind(run.report_ms)
+        .bind(run.entries_count)
+        .bind(run.embeddings_count)
+        .bind(run.groups_count)
+        .bind(&run.error)
+        .fetch_one(&self.pool)
+        .await
+    }
 
     #[tracing::instrument(level = "debug", skip(self))]
-    pub async fn list_report_group_entries_by_id_lang_code(
+    pub async fn list_job_runs(
         &self,
-        id: Id<ReportGroup>,
+        limit: u32,
+    ) -> Result<Vec<Persisted<background::JobRun>>, sqlx::Error> {
+        sqlx::query_as("SELECT * FROM job_runs ORDER BY id DESC LIMIT ?")
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await
+    }
+}
+
+impl Client {
+    #[tracing::instrument(level = "debug", skip_all, fields(hits = hits.len()))]
+    pub async fn insert_filter_hits(&self, hits: &[filters::Hit]) -> Result<(), sqlx::Error> {
+        for chunk in hits.chunks(BATCH_SIZE) {
+            let mut query = sqlx::QueryBuilder::new(
+                "INSERT OR IGNORE INTO filter_hits (feed_id, href, keyword, title) ",
+            );
+            query.push_values(chunk, |mut row, hit| {
+                row.push_bind(hit.feed_id)
+                    .push_bind(hit.href.to_string())
+                    .push_bind(&hit.keyword)
+                    .push_bind(&hit.title);
+            });
+            query.build().execute(&self.pool).await?;
+        }
+        Ok(())
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn list_filter_hits(
+        &self,
+        limit: u32,
+    ) -> Result<Vec<Persisted<filters::Hit>>, sqlx::Error> {
+        sqlx::query_as("SELECT * FROM filter_hits ORDER BY id DESC LIMIT ?")
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await
+    }
+}
+
+impl Client {
+    #[tracing::instrument(level = "debug", skip(self, audio), fields(bytes = audio.len()))]
+    pub async fn upsert_briefing(
+        &self,
+        date: chrono::NaiveDate,
+        audio: &[u8],
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT INTO briefings (date, audio) VALUES (?, ?)
+            ON CONFLICT (date) DO UPDATE SET audio = excluded.audio, created_at = CURRENT_TIMESTAMP",
+        )
+        .bind(date)
+        .bind(audio)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn increment_entry_clicks(
+        &self,
+        entry_id: Id<feeds::Entry>,
+        date: chrono::NaiveDate,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT INTO entry_clicks (entry_id, date, count) VALUES (?, ?, 1)
+            ON CONFLICT (entry_id, date) DO UPDATE SET count = count + 1",
+        )
+        .bind(entry_id)
+        .bind(date)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn list_popular_entries_since_date_lang_code(
+        &self,
+        since: chrono::NaiveDate,
         lang_code: &feeds::LanguageCode,
-    ) -> Result<Vec<web::GroupEntryView>, sqlx::Error> {
+        limit: u32,
+    ) -> Result<Vec<web::PopularEntryView>, sqlx::Error> {
         sqlx::query_as(
             "
             SELECT
-                entries.group_id AS group_id,
-                entries.is_center AS is_center,
-                entries.href AS href,
-                entries.published_at AS published_at,
+                entries.id AS entry_id,
                 entries.feed_id AS feed_id,
-                translations.value AS title
+                translations.value AS title,
+                SUM(entry_clicks.count) AS clicks
             FROM
-                fields
+                entry_clicks
+                    JOIN entries ON entries.id = entry_clicks.entry_id
+                    JOIN fields ON fields.entry_id = entries.id
                     JOIN translations ON translations.md5_hash = fields.md5_hash
-                    JOIN (
-                            SELECT
-                                entries.id AS id,
-                                report_group_embeddings.report_group_id AS group_id,
-                                (report_groups.center_embedding_id = embeddings.id) AS is_center,
-                                entries.href AS href,
-                                entries.published_at AS published_at,
-                                entries.feed_id AS feed_id
-                            FROM
-                                report_group_embeddings
-                                    JOIN report_groups ON report_group_embeddings.report_group_id = report_groups.id
-                                    JOIN embeddings ON embeddings.id = report_group_embeddings.embedding_id
-                                    JOIN fields ON fields.md5_hash = embeddings.md5_hash
-                                    JOIN entries ON entries.id = fields.entry_id
-                            WHERE
-                                report_group_embeddings.report_group_id = ?
-                        ) AS entries ON entries.id = fields.entry_id
             WHERE
-                fields.lang_code = ?
+                entry_clicks.date >= $1
                 AND fields.name = 'title'
+                AND fields.lang_code = $2
+            GROUP BY
+                entries.id
             ORDER BY
-                entries.published_at DESC
+                clicks DESC,
+                entries.id DESC
+            LIMIT $3
             ",
         )
-        .bind(id)
+        .bind(since)
         .bind(lang_code)
+        .bind(limit)
         .fetch_all(&self.pool)
         .await
     }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn find_briefing_created_at_by_date(
+        &self,
+        date: chrono::NaiveDate,
+    ) -> Result<Option<chrono::DateTime<chrono::Utc>>, sqlx::Error> {
+        sqlx::query_scalar("SELECT created_at FROM briefings WHERE date = ?")
+            .bind(date)
+            .fetch_optional(&self.pool)
+            .await
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn find_briefing_audio_by_date(
+        &self,
+        date: chrono::NaiveDate,
+    ) -> Result<Option<Vec<u8>>, sqlx::Error> {
+        sqlx::query_scalar("SELECT audio FROM briefings WHERE date = ?")
+            .bind(date)
+            .fetch_optional(&self.pool)
+            .await
+    }
 }
 
-impl<'a> sqlx::FromRow<'a, sqlx::sqlite::SqliteRow> for Embedding {
-    fn from_row(row: &'a sqlx::sqlite::SqliteRow) -> Result<Self, sqlx::Error> {
-        use sqlx::Row;
+impl Client {
+    /// stores the subscription, keys of a subscription that already exists are replaced. new
+    /// subscriptions are not stored once there are `max` of them, returns whether it was stored
+    #[tracing::instrument(level = "debug", skip_all, fields(endpoint = %subscription.endpoint))]
+    pub async fn upsert_push_subscription(
+        &self,
+        subscription: &push::Subscription,
+        max: u32,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            "
+            INSERT INTO push_subscriptions (endpoint, p256dh, auth)
+            SELECT $1, $2, $3
+            WHERE
+                EXISTS (SELECT 1 FROM push_subscriptions WHERE endpoint = $1)
+                OR (SELECT COUNT(*) FROM push_subscriptions) < $4
+            ON CONFLICT (endpoint) DO UPDATE SET p256dh = excluded.p256dh, auth = excluded.auth
+            ",
+        )
+        .bind(&subscription.endpoint)
+        .bind(&subscription.keys.p256dh)
+        .bind(&subscription.keys.auth)
+        .bind(max)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
 
-        let md5_hash = row.try_get("md5_hash")?;
-        let size: u32 = row.try_get("size")?;
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn delete_push_subscription(&self, endpoint: &str) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM push_subscriptions WHERE endpoint = ?")
+            .bind(endpoint)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn list_push_subscriptions(&self) -> Result<Vec<push::Subscription>, sqlx::Error> {
+        sqlx::query_as("SELECT endpoint, p256dh, auth FROM push_subscriptions")
+            .fetch_all(&self.pool)
+            .await
+    }
+}
 
-        let value: String = row.try_get("value")?;
-        let value =
-            serde_json::from_str(&value).map_err(|error| sqlx::Error::Decode(Box::new(error)))?;
+impl Client {
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn find_feed_validator_by_href(
+        &self,
+        href: &str,
+    ) -> Result<Option<Persisted<feeds::http::Validator>>, sqlx::Error> {
+        sqlx::query_as("SELECT * FROM feed_validators WHERE href = ?")
+            .bind(href)
+            .fetch_optional(&self.pool)
+            .await
+    }
 
-        Ok(Embedding {
-            md5_hash,
-            value,
-            size,
-        })
+    #[tracing::instrument(level = "debug", skip_all, fields(href = %validator.href))]
+    pub async fn upsert_feed_validator(
+        &self,
+        validator: &feeds::http::Validator,
+    ) -> Result<Persisted<feeds::http::Validator>, sqlx::Error> {
+        sqlx::query_as(
+            "INSERT INTO feed_validators (href, etag, last_modified) VALUES (?, ?, ?)
+            ON CONFLICT (href) DO UPDATE SET etag = excluded.etag, last_modified = excluded.last_modified
+            RETURNING *",
+        )
+        .bind(&validator.href)
+        .bind(&validator.etag)
+        .bind(&validator.last_modified)
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn find_robots_txt_by_origin(
+        &self,
+        origin: &str,
+    ) -> Result<Option<Persisted<feeds::http::RobotsTxt>>, sqlx::Error> {
+        sqlx::query_as("SELECT * FROM robots_txts WHERE origin = ?")
+            .bind(origin)
+            .fetch_optional(&self.pool)
+            .await
+    }
+
+    #[tracing::instrument(level = "debug", skip_all, fields(origin = %robots_txt.origin))]
+    pub async fn upsert_robots_txt(
+        &self,
+        robots_txt: &feeds::http::RobotsTxt,
+    ) -> Result<Persisted<feeds::http::RobotsTxt>, sqlx::Error> {
+        sqlx::query_as(
+            "INSERT INTO robots_txts (origin, body, fetched_at) VALUES (?, ?, ?)
+            ON CONFLICT (origin) DO UPDATE SET body = excluded.body, fetched_at = excluded.fetched_at
+            RETURNING *",
+        )
+        .bind(&robots_txt.origin)
+        .bind(&robots_txt.body)
+        .bind(robots_txt.fetched_at)
+        .fetch_one(&self.pool)
+        .await
+    }
+}
+
+impl Client {
+    #[tracing::instrument(level = "debug", skip_all, fields(md5_hash = ?embedding.md5_hash, size = %embedding.size, model = %embedding.model))]
+    pub async fn insert_embeddig(
+        &self,
+        embedding: &clustering::Embedding,
+        precision: quantization::Precision,
+    ) -> Result<Option<Persisted<clustering::Embedding>>, sqlx::Error> {
+        let (value, scale) = quantization::encode(&embedding.value, precision);
+        sqlx::query_as(
+            "INSERT OR IGNORE INTO embeddings (md5_hash, value, precision, scale, size, model) VALUES ( ?, ?, ?, ?, ?, ? ) RETURNING *",
+        )
+        .bind(embedding.md5_hash)
+        .bind(value)
+        .bind(precision)
+        .bind(scale)
+        .bind(embedding.size)
+        .bind(&embedding.model)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn list_embeddings_by_field_name_lang_code_published_at_model(
+        &self,
+        field_name: feeds::FieldName,
+        lang_code: feeds::LanguageCode,
+        published_at: &std::ops::Range<chrono::DateTime<chrono::Utc>>,
+        model: &str,
+    ) -> Result<Vec<Persisted<clustering::Embedding>>, sqlx::Error> {
+        sqlx::query_as(
+            "SELECT embeddings.*
+            FROM embeddings
+            JOIN fields ON
+                fields.md5_hash = embeddings.md5_hash
+                AND fields.lang_code = $1
+                AND fields.name = $2
+            JOIN entries ON
+                entries.id = fields.entry_id
+            WHERE
+                DATETIME(entries.published_at) >= DATETIME($3)
+                AND DATETIME(entries.published_at) < DATETIME($4)
+                AND embeddings.model = $5
+                AND embeddings.superseded_at IS NULL
+            GROUP BY embeddings.md5_hash
+            ",
+        )
+        .bind(lang_code.to_string())
+        .bind(field_name.to_string())
+        .bind(published_at.start)
+        .bind(published_at.end)
+        .bind(model)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn list_embeddings_by_md5_hash(
+        &self,
+        md5_hash: &Md5Hash,
+    ) -> Result<Vec<Persisted<clustering::Embedding>>, sqlx::Error> {
+        sqlx::query_as("SELECT * FROM embeddings WHERE md5_hash = ?")
+            .bind(md5_hash)
+            .fetch_all(&self.pool)
+            .await
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn find_embedding_by_id(
+        &self,
+        id: &Id<clustering::Embedding>,
+    ) -> Result<Persisted<clustering::Embedding>, sqlx::Error> {
+        sqlx::query_as("SELECT * FROM embeddings WHERE id = ?")
+            .bind(id)
+            .fetch_one(&self.pool)
+            .await
+    }
+}
+
+impl Client {
+    #[tracing::instrument(level = "debug", skip_all, fields(md5_hash = ?transaslation.md5_hash))]
+    pub async fn insert_translation(
+        &self,
+        transaslation: feeds::Translation,
+    ) -> Result<Option<Persisted<feeds::Translation>>, sqlx::Error> {
+        sqlx::query_as(
+            "INSERT OR IGNORE INTO translations (md5_hash, value) VALUES (?, ?) RETURNING *",
+        )
+        .bind(transaslation.md5_hash)
+        .bind(transaslation.value.to_string())
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    #[tracing::instrument(level = "debug", skip(self), fields(md5_hash = ?md5_hash))]
+    pub async fn find_translation_by_md5_hash(
+        &self,
+        md5_hash: &Md5Hash,
+    ) -> Result<Persisted<feeds::Translation>, sqlx::Error> {
+        sqlx::query_as("SELECT * FROM translations WHERE md5_hash = ?")
+            .bind(md5_hash)
+            .fetch_one(&self.pool)
+            .await
+    }
+}
+
+impl Client {
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub async fn insert_report_group(
+        &self,
+        group: clustering::ReportGroup,
+    ) -> Result<Persisted<clustering::ReportGroup>, sqlx::Error> {
+        use sqlx::{Executor, Row};
+
+        let mut transaction = self.pool.begin().await?;
+
+        let group_insert_result = transaction
+            .fetch_one(
+                sqlx::query(
+                    "INSERT INTO report_groups (report_id, center_embedding_id) VALUES (?, ?) RETURNING id",
+                )
+                .bind(group.report_id)
+                .bind(group.center_embedding_id),
+            )
+            .await?;
+        let group_id = group_insert_result.try_get("id")?;
+
+        for embedding_id in &group.embedding_ids {
+            transaction.execute(
+                sqlx::query("INSERT INTO report_group_embeddings (report_group_id, embedding_id) VALUES (?, ?)")
+                    .bind(group_id)
+                    .bind(embedding_id),
+            ).await?;
+        }
+
+        transaction.commit().await?;
+
+        Ok(Persisted {
+            id: group_id,
+            created_at: chrono::Utc::now(),
+            value: group.clone(),
+        })
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn insert_report_group_embeddings(
+        &self,
+        report_group_id: Id<ReportGroup>,
+        embedding_ids: &[Id<Embedding>],
+    ) -> Result<(), sqlx::Error> {
+        use sqlx::Executor;
+
+        let mut transaction = self.pool.begin().await?;
+
+        for embedding_id in embedding_ids {
+            transaction.execute(
+                sqlx::query("INSERT INTO report_group_embeddings (report_group_id, embedding_id) VALUES (?, ?)")
+                    .bind(report_group_id)
+                    .bind(embedding_id),
+            ).await?;
+        }
+
+        transaction.commit().await
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn list_report_groups_by_report_id(
+        &self,
+        report_id: Id<clustering::Report>,
+    ) -> Result<Vec<Persisted<ReportGroup>>, sqlx::Error> {
+        use sqlx::Row;
+
+        let groups = sqlx::query(
+            "SELECT id, created_at, center_embedding_id FROM report_groups WHERE report_id = ? ORDER BY id",
+        )
+        .bind(report_id)
+        .fetch_all(&self.pool)
+        .await?;
+        let embeddings = sqlx::query(
+            "SELECT report_group_embeddings.report_group_id AS report_group_id, report_group_embeddings.embedding_id AS embedding_id FROM report_group_embeddings JOIN report_groups ON report_groups.id = report_group_embeddings.report_group_id WHERE report_groups.report_id = ?",
+        )
+        .bind(report_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        groups
+            .iter()
+            .map(|group| {
+                let id: Id<ReportGroup> = group.try_get("id")?;
+                let embedding_ids = embeddings
+                    .iter()
+                    .filter(|row| {
+                        row.try_get("report_group_id")
+                            .is_ok_and(|group_id: Id<ReportGroup>| group_id == id)
+                    })
+                    .map(|row| row.try_get("embedding_id"))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(Persisted {
+                    id,
+                    created_at: group.try_get("created_at")?,
+                    value: ReportGroup {
+                        report_id,
+                        embedding_ids,
+                        center_embedding_id: group.try_get("center_embedding_id")?,
+                    },
+                })
+            })
+            .collect()
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn insert_group_title(
+        &self,
+        report_group_id: Id<ReportGroup>,
+        lang_code: &feeds::LanguageCode,
+        value: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT OR REPLACE INTO group_titles (report_group_id, lang_code, value) VALUES (?, ?, ?)",
+        )
+        .bind(report_group_id)
+        .bind(lang_code)
+        .bind(value)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn list_descriptions_by_report_group_id(
+        &self,
+        report_group_id: Id<ReportGroup>,
+    ) -> Result<Vec<String>, sqlx::Error> {
+        sqlx::query_scalar(
+            "
+            SELECT
+                translations.value
+            FROM
+                report_group_embeddings
+                    JOIN embeddings ON embeddings.id = report_group_embeddings.embedding_id
+                    JOIN translations ON translations.md5_hash = embeddings.md5_hash
+            WHERE
+                report_group_embeddings.report_group_id = ?
+            ",
+        )
+        .bind(report_group_id)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn find_group_summary_by_md5_hash_lang_code(
+        &self,
+        md5_hash: &Md5Hash,
+        lang_code: &feeds::LanguageCode,
+    ) -> Result<Option<String>, sqlx::Error> {
+        sqlx::query_scalar("SELECT value FROM group_summaries WHERE md5_hash = ? AND lang_code = ?")
+            .bind(md5_hash)
+            .bind(lang_code)
+            .fetch_optional(&self.pool)
+            .await
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn find_group_summary_by_report_group_id_lang_code(
+        &self,
+        report_group_id: Id<ReportGroup>,
+        lang_code: &feeds::LanguageCode,
+    ) -> Result<Option<String>, sqlx::Error> {
+        sqlx::query_scalar(
+            "
+            SELECT
+                group_summaries.value
+            FROM
+                report_groups
+                    JOIN group_summaries ON group_summaries.md5_hash = report_groups.summary_md5_hash
+            WHERE
+                report_groups.id = ?
+                AND group_summaries.lang_code = ?
+            ",
+        )
+        .bind(report_group_id)
+        .bind(lang_code)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    #[tracing::instrument(level = "debug", skip(self, value))]
+    pub async fn insert_group_summary(
+        &self,
+        md5_hash: &Md5Hash,
+        lang_code: &feeds::LanguageCode,
+        value: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT OR IGNORE INTO group_summaries (md5_hash, lang_code, value) VALUES (?, ?, ?)",
+        )
+        .bind(md5_hash)
+        .bind(lang_code)
+        .bind(value)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn update_report_group_summary_md5_hash(
+        &self,
+        report_group_id: Id<ReportGroup>,
+        md5_hash: &Md5Hash,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE report_groups SET summary_md5_hash = ? WHERE id = ?")
+            .bind(md5_hash)
+            .bind(report_group_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn update_report_group_category(
+        &self,
+        report_group_id: Id<ReportGroup>,
+        category: clustering::Category,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE report_groups SET category = ? WHERE id = ?")
+            .bind(category)
+            .bind(report_group_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn update_report_group_breaking_at(
+        &self,
+        report_group_id: Id<ReportGroup>,
+        breaking_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE report_groups SET breaking_at = ? WHERE id = ?")
+            .bind(timestamp(breaking_at))
+            .bind(report_group_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn find_report_group_breaking_at_by_id(
+        &self,
+        id: Id<ReportGroup>,
+    ) -> Result<Option<chrono::DateTime<chrono::Utc>>, sqlx::Error> {
+        sqlx::query_scalar(
+            "
+            WITH RECURSIVE ancestors(id) AS (
+                SELECT $1
+                UNION
+                SELECT
+                    report_group_lineage.parent_report_group_id
+                FROM
+                    report_group_lineage
+                        JOIN ancestors ON ancestors.id = report_group_lineage.report_group_id
+            )
+            SELECT
+                MAX(report_groups.breaking_at)
+            FROM
+                report_groups
+                    JOIN ancestors ON ancestors.id = report_groups.id
+            ",
+        )
+        .bind(id)
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn insert_report_group_post(
+        &self,
+        report_group_id: Id<ReportGroup>,
+        network: social::Network,
+        posted_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT OR IGNORE INTO report_group_posts (report_group_id, network, posted_at) VALUES (?, ?, ?)",
+        )
+        .bind(report_group_id)
+        .bind(network)
+        .bind(timestamp(posted_at))
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn find_report_group_posted_at_by_id_network(
+        &self,
+        id: Id<ReportGroup>,
+        network: social::Network,
+    ) -> Result<Option<chrono::DateTime<chrono::Utc>>, sqlx::Error> {
+        sqlx::query_scalar(
+            "
+            WITH RECURSIVE ancestors(id) AS (
+                SELECT $1
+                UNION
+                SELECT
+                    report_group_lineage.parent_report_group_id
+                FROM
+                    report_group_lineage
+                        JOIN ancestors ON ancestors.id = report_group_lineage.report_group_id
+            )
+            SELECT
+                MAX(report_group_posts.posted_at)
+            FROM
+                report_group_posts
+                    JOIN ancestors ON ancestors.id = report_group_posts.report_group_id
+            WHERE
+                report_group_posts.network = $2
+            ",
+        )
+        .bind(id)
+        .bind(network)
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn replace_related_groups(
+        &self,
+        report_group_id: Id<ReportGroup>,
+        related: &[(Id<ReportGroup>, f32)],
+    ) -> Result<(), sqlx::Error> {
+        use sqlx::Executor;
+
+        let mut transaction = self.pool.begin().await?;
+
+        transaction
+            .execute(
+                sqlx::query("DELETE FROM related_groups WHERE report_group_id = ?")
+                    .bind(report_group_id),
+            )
+            .await?;
+        for (related_report_group_id, distance) in related {
+            transaction.execute(
+                sqlx::query("INSERT INTO related_groups (report_group_id, related_report_group_id, distance) VALUES (?, ?, ?)")
+                    .bind(report_group_id)
+                    .bind(related_report_group_id)
+                    .bind(distance),
+            ).await?;
+        }
+
+        transaction.commit().await
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn list_related_groups_by_id_lang_code(
+        &self,
+        id: Id<ReportGroup>,
+        lang_code: &feeds::LanguageCode,
+    ) -> Result<Vec<web::RelatedGroupView>, sqlx::Error> {
+        sqlx::query_as(
+            "
+            SELECT
+                related_groups.related_report_group_id AS group_id,
+                COALESCE(group_titles.value, translations.value) AS title
+            FROM
+                related_groups
+                    JOIN report_groups ON report_groups.id = related_groups.related_report_group_id
+                    JOIN embeddings ON embeddings.id = report_groups.center_embedding_id
+                    JOIN fields AS descriptions ON descriptions.md5_hash = embeddings.md5_hash
+                    JOIN fields AS titles ON
+                        titles.entry_id = descriptions.entry_id
+                        AND titles.name = 'title'
+                        AND titles.lang_code = $2
+                    JOIN translations ON translations.md5_hash = titles.md5_hash
+                    LEFT JOIN group_titles ON
+                        group_titles.report_group_id = report_groups.id
+                        AND group_titles.lang_code = $2
+            WHERE
+                related_groups.report_group_id = $1
+            GROUP BY
+                related_groups.related_report_group_id
+            ORDER BY
+                related_groups.distance
+            ",
+        )
+        .bind(id)
+        .bind(lang_code)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn insert_report_group_lineage(
+        &self,
+        report_group_id: Id<ReportGroup>,
+        parent_report_group_id: Id<ReportGroup>,
+        overlap: u32,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT INTO report_group_lineage (report_group_id, parent_report_group_id, overlap) VALUES (?, ?, ?)",
+        )
+        .bind(report_group_id)
+        .bind(parent_report_group_id)
+        .bind(overlap)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn find_report_group_first_seen_by_id(
+        &self,
+        id: Id<ReportGroup>,
+    ) -> Result<Option<chrono::DateTime<chrono::Utc>>, sqlx::Error> {
+        sqlx::query_scalar(
+            "
+            WITH RECURSIVE ancestors(id) AS (
+                SELECT $1
+                UNION
+                SELECT
+                    report_group_lineage.parent_report_group_id
+                FROM
+                    report_group_lineage
+                        JOIN ancestors ON ancestors.id = report_group_lineage.report_group_id
+            )
+            SELECT
+                MIN(report_groups.created_at)
+            FROM
+                report_groups
+                    JOIN ancestors ON ancestors.id = report_groups.id
+            ",
+        )
+        .bind(id)
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn find_latest_descendant_report_group_id_by_id(
+        &self,
+        id: Id<ReportGroup>,
+    ) -> Result<Option<Id<ReportGroup>>, sqlx::Error> {
+        sqlx::query_scalar(
+            "
+            WITH RECURSIVE descendants(id) AS (
+                SELECT $1
+                UNION
+                SELECT
+                    report_group_lineage.report_group_id
+                FROM
+                    report_group_lineage
+                        JOIN descendants ON descendants.id = report_group_lineage.parent_report_group_id
+            )
+            SELECT
+                MAX(report_groups.id)
+            FROM
+                report_groups
+                    JOIN descendants ON descendants.id = report_groups.id
+            ",
+        )
+        .bind(id)
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn list_report_group_changes_by_report_id_since_report_id(
+        &self,
+        report_id: Id<clustering::Report>,
+        since_report_id: Id<clustering::Report>,
+    ) -> Result<Vec<web::GroupChangeView>, sqlx::Error> {
+        sqlx::query_as(
+            "
+            WITH RECURSIVE ancestors(report_group_id, ancestor_id) AS (
+                SELECT id, id FROM report_groups WHERE report_id = $1
+                UNION
+                SELECT
+                    ancestors.report_group_id,
+                    report_group_lineage.parent_report_group_id
+                FROM
+                    report_group_lineage
+                        JOIN ancestors ON ancestors.ancestor_id = report_group_lineage.report_group_id
+                        JOIN report_groups ON report_groups.id = report_group_lineage.parent_report_group_id
+                WHERE
+                    -- lineage older than the compared report is not needed
+                    report_groups.report_id >= $2
+            ),
+            sizes(report_group_id, size) AS (
+                SELECT
+                    report_group_id,
+                    COUNT(*)
+                FROM
+                    report_group_embeddings
+                WHERE
+                    report_group_id IN (SELECT ancestor_id FROM ancestors)
+                GROUP BY
+                    report_group_id
+            )
+            SELECT
+                ancestors.report_group_id AS group_id,
+                MAX(CASE WHEN ancestors.ancestor_id = ancestors.report_group_id THEN sizes.size END) AS size,
+                MAX(CASE WHEN report_groups.report_id = $2 THEN sizes.size END) AS previous_size
+            FROM
+                ancestors
+                    JOIN report_groups ON report_groups.id = ancestors.ancestor_id
+                    JOIN sizes ON sizes.report_group_id = ancestors.ancestor_id
+            GROUP BY
+                ancestors.report_group_id
+            ",
+        )
+        .bind(report_id)
+        .bind(since_report_id)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn find_latest_report(
+        &self,
+    ) -> Result<Option<Persisted<clustering::Report>>, sqlx::Error> {
+        sqlx::query_as("SELECT * FROM reports ORDER BY created_at DESC, id DESC LIMIT 1")
+            .fetch_optional(&self.pool)
+            .await
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn find_latest_report_before(
+        &self,
+        before: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Option<Persisted<clustering::Report>>, sqlx::Error> {
+        sqlx::query_as(
+            "SELECT * FROM reports WHERE created_at < ? ORDER BY created_at DESC, id DESC LIMIT 1",
+        )
+        .bind(timestamp(before))
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn find_report_date_before(
+        &self,
+        date: chrono::NaiveDate,
+    ) -> Result<Option<chrono::NaiveDate>, sqlx::Error> {
+        let date = date
+            .and_hms_opt(0, 0, 0)
+            .expect("failed to create start of day");
+        sqlx::query_scalar(
+            "SELECT DATE(created_at) FROM reports WHERE created_at < DATETIME($1, 'start of day') ORDER BY created_at DESC LIMIT 1",
+        )
+        .bind(date)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn find_report_date_after(
+        &self,
+        date: chrono::NaiveDate,
+    ) -> Result<Option<chrono::NaiveDate>, sqlx::Error> {
+        let date = date
+            .and_hms_opt(0, 0, 0)
+            .expect("failed to create start of day");
+        sqlx::query_scalar(
+            "SELECT DATE(created_at) FROM reports WHERE created_at >= DATETIME($1, 'start of day', '+1 day') ORDER BY created_at LIMIT 1",
+        )
+        .bind(date)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn list_group_texts_by_date_lang_code(
+        &self,
+        date: chrono::NaiveDate,
+        lang_code: &feeds::LanguageCode,
+    ) -> Result<Vec<web::GroupTextView>, sqlx::Error> {
+        let date = date
+            .and_hms_opt(0, 0, 0)
+            .expect("failed to create start of day");
+        sqlx::query_as(
+            "
+            SELECT
+                report_groups.id AS group_id,
+                group_titles.value AS title,
+                group_summaries.value AS summary
+            FROM
+                report_groups
+                    JOIN group_titles ON
+                        group_titles.report_group_id = report_groups.id
+                        AND group_titles.lang_code = $2
+                    LEFT JOIN group_summaries ON
+                        group_summaries.md5_hash = report_groups.summary_md5_hash
+                        AND group_summaries.lang_code = $2
+            WHERE
+                report_groups.report_id = (
+                    SELECT
+                        id
+                    FROM
+                        reports
+                    WHERE
+                        created_at >= DATETIME($1, 'start of day')
+                            AND created_at < DATETIME($1, 'start of day', '+1 day')
+                    ORDER BY
+                        created_at DESC
+                    LIMIT 1
+                )
+            ORDER BY
+                (
+                    SELECT
+                        COUNT(*)
+                    FROM
+                        report_group_embeddings
+                    WHERE
+                        report_group_embeddings.report_group_id = report_groups.id
+                ) DESC,
+                report_groups.id
+            ",
+        )
+        .bind(date)
+        .bind(lang_code)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn list_report_days(
+        &self,
+        from: chrono::NaiveDate,
+        to: chrono::NaiveDate,
+    ) -> Result<Vec<web::ReportDayView>, sqlx::Error> {
+        let from = from
+            .and_hms_opt(0, 0, 0)
+            .expect("failed to create start of day");
+        let to = to
+            .and_hms_opt(0, 0, 0)
+            .expect("failed to create start of day");
+        sqlx::query_as(
+            "
+            WITH latest_reports AS (
+                SELECT id, DATE(created_at) AS date, MAX(created_at)
+                FROM reports
+                WHERE created_at >= DATETIME($1, 'start of day') AND created_at < DATETIME($2, 'start of day', '+1 day')
+                GROUP BY DATE(created_at)
+            )
+            SELECT latest_reports.date AS date, COUNT(report_groups.id) AS groups_count
+            FROM latest_reports
+                LEFT JOIN report_groups ON report_groups.report_id = latest_reports.id
+            GROUP BY latest_reports.id
+            ORDER BY latest_reports.date
+            ",
+        )
+        .bind(from)
+        .bind(to)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn list_report_groups_by_dates_lang_code(
+        &self,
+        from: chrono::NaiveDate,
+        to: chrono::NaiveDate,
+        lang_code: &feeds::LanguageCode,
+    ) -> Result<Vec<web::WeekGroupView>, sqlx::Error> {
+        let from = from
+            .and_hms_opt(0, 0, 0)
+            .expect("failed to create start of day");
+        let to = to
+            .and_hms_opt(0, 0, 0)
+            .expect("failed to create start of day");
+        sqlx::query_as(
+            "
+            WITH latest_reports AS (
+                SELECT id, DATE(created_at) AS date, MAX(created_at)
+                FROM reports
+                WHERE created_at >= DATETIME($1, 'start of day') AND created_at < DATETIME($2, 'start of day', '+1 day')
+                GROUP BY DATE(created_at)
+            )
+            SELECT
+                report_groups.id AS group_id,
+                latest_reports.date AS date,
+                (
+                    SELECT COUNT(*)
+                    FROM report_group_embeddings
+                    WHERE report_group_embeddings.report_group_id = report_groups.id
+                ) AS size,
+                translations.value AS title,
+                group_titles.value AS group_title
+            FROM
+                latest_reports
+                    JOIN report_groups ON report_groups.report_id = latest_reports.id
+                    JOIN embeddings ON embeddings.id = report_groups.center_embedding_id
+                    JOIN fields AS descriptions ON descriptions.md5_hash = embeddings.md5_hash
+                    JOIN fields ON
+                        fields.entry_id = descriptions.entry_id
+                        AND fields.name = 'title'
+                        AND fields.lang_code = $3
+                    JOIN translations ON translations.md5_hash = fields.md5_hash
+                    LEFT JOIN group_titles ON
+                        group_titles.report_group_id = report_groups.id
+                        AND group_titles.lang_code = $3
+            GROUP BY
+                report_groups.id
+            ORDER BY
+                latest_reports.date, report_groups.id
+            ",
+        )
+        .bind(from)
+        .bind(to)
+        .bind(lang_code)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn list_report_group_embeddings_by_dates(
+        &self,
+        from: chrono::NaiveDate,
+        to: chrono::NaiveDate,
+    ) -> Result<Vec<GroupEmbedding>, sqlx::Error> {
+        use sqlx::{FromRow, Row};
+
+        let from = from
+            .and_hms_opt(0, 0, 0)
+            .expect("failed to create start of day");
+        let to = to
+            .and_hms_opt(0, 0, 0)
+            .expect("failed to create start of day");
+        let rows = sqlx::query(
+            "
+            WITH latest_reports AS (
+                SELECT id, DATE(created_at) AS date, MAX(created_at)
+                FROM reports
+                WHERE created_at >= DATETIME($1, 'start of day') AND created_at < DATETIME($2, 'start of day', '+1 day')
+                GROUP BY DATE(created_at)
+            )
+            SELECT
+                report_group_embeddings.report_group_id AS report_group_id,
+                embeddings.*
+            FROM
+                latest_reports
+                    JOIN report_groups ON report_groups.report_id = latest_reports.id
+                    JOIN report_group_embeddings ON report_group_embeddings.report_group_id = report_groups.id
+                    JOIN embeddings ON embeddings.id = report_group_embeddings.embedding_id
+            ",
+        )
+        .bind(from)
+        .bind(to)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| Ok((row.try_get("report_group_id")?, Persisted::from_row(row)?)))
+            .collect()
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn find_latest_report_by_date(
+        &self,
+        date: chrono::NaiveDate,
+    ) -> Result<Option<Persisted<clustering::Report>>, sqlx::Error> {
+        let date = date
+            .and_hms_opt(0, 0, 0)
+            .expect("failed to create start of day");
+        sqlx::query_as(
+            "SELECT * FROM reports WHERE created_at >= DATETIME($1, 'start of day') AND created_at < DATETIME($1, 'start of day', '+1 day') ORDER BY created_at DESC LIMIT 1",
+        )
+        .bind(date)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn insert_report(
+        &self,
+        report: &clustering::Report,
+        created_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Persisted<clustering::Report>, sqlx::Error> {
+        sqlx::query_as(
+            "INSERT INTO reports (algorithm, tolerance_method, score_metric, score, davies_bouldin, calinski_harabasz, min_points, tolerance, tolerance_range_start, tolerance_range_end, samples, rows, dimentions, window_start, window_end, created_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?) RETURNING *",
+        )
+        .bind(report.algorithm)
+        .bind(report.tolerance_method)
+        .bind(report.score_metric)
+        .bind(report.score)
+        .bind(report.davies_bouldin)
+        .bind(report.calinski_harabasz)
+        .bind(report.min_points)
+        .bind(report.tolerance)
+        .bind(report.tolerance_range_start)
+        .bind(report.tolerance_range_end)
+        .bind(report.samples)
+        .bind(report.rows)
+        .bind(report.dimentions)
+        .bind(report.window_start)
+        .bind(report.window_end)
+        .bind(timestamp(created_at))
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn update_report_updated_at(
+        &self,
+        id: Id<clustering::Report>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE reports SET updated_at = CURRENT_TIMESTAMP WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn list_report_group_entries_by_date_lang_code(
+        &self,
+        date: chrono::NaiveDate,
+        lang_code: &feeds::LanguageCode,
+    ) -> Result<Vec<web::GroupEntryView>, sqlx::Error> {
+        let date = date
+            .and_hms_opt(0, 0, 0)
+            .expect("failed to create start of day");
+        sqlx::query_as(
+            "
+            SELECT
+                entries.id AS entry_id,
+                entries.group_id AS group_id,
+                entries.is_center AS is_center,
+                entries.href AS href,
+                entries.published_at AS published_at,
+                entries.feed_id AS feed_id,
+                translations.value AS title,
+                group_titles.value AS group_title,
+                entries.category AS category,
+                entries.is_breaking AS is_breaking,
+                entries.is_duplicate AS is_duplicate,
+                entries.image_href AS image_href,
+                entries.is_paywalled AS is_paywalled,
+                COALESCE(entry_clicks.count, 0) AS clicks
+            FROM
+                fields
+                    JOIN translations ON translations.md5_hash = fields.md5_hash
+                    JOIN (
+                            SELECT
+                                entries.id AS id,
+                                (report_groups.center_embedding_id = embeddings.id) AS is_center,
+                                report_groups.category AS category,
+                                (report_groups.breaking_at IS NOT NULL) AS is_breaking,
+                                (entries.duplicate_of IS NOT NULL) AS is_duplicate,
+                                entries.image_href AS image_href,
+                                entries.paywalled AS is_paywalled,
+                                report_group_embeddings.report_group_id AS group_id,
+                                entries.href AS href,
+                                entries.published_at AS published_at,
+                                entries.feed_id AS feed_id
+                            FROM
+                                report_group_embeddings
+                                    JOIN report_groups ON report_group_embeddings.report_group_id = report_groups.id
+                                    JOIN embeddings ON embeddings.id = report_group_embeddings.embedding_id
+                                    JOIN fields ON fields.md5_hash = embeddings.md5_hash
+                                    JOIN entries ON entries.id = fields.entry_id
+                            WHERE
+                                report_groups.report_id = (
+                                    SELECT
+                                        id
+                                    FROM
+                                        reports
+                                    WHERE
+                                        created_at >= DATETIME($1, 'start of day')
+                                            AND created_at < DATETIME($1, 'start of day', '+1 day')
+                                    ORDER BY
+                                        created_at DESC
+                                    LIMIT 1
+                                )
+                        ) AS entries ON entries.id = fields.entry_id
+                    LEFT JOIN (
+                            SELECT entry_id, SUM(count) AS count FROM entry_clicks GROUP BY entry_id
+                        ) AS entry_clicks ON entry_clicks.entry_id = entries.id
+                    LEFT JOIN group_titles ON
+                        group_titles.report_group_id = entries.group_id
+                        AND group_titles.lang_code = fields.lang_code
+            WHERE
+                fields.lang_code = $2
+                AND fields.name = 'title'
+            ORDER BY
+                entries.published_at DESC
+            ",
+        )
+        .bind(date)
+        .bind(lang_code)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn list_report_group_entries_by_date_lang_code_published_at(
+        &self,
+        date: chrono::NaiveDate,
+        lang_code: &feeds::LanguageCode,
+        published_at: &std::ops::Range<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<Vec<web::GroupEntryView>, sqlx::Error> {
+        let date = date
+            .and_hms_opt(0, 0, 0)
+            .expect("failed to create start of day");
+        sqlx::query_as(
+            "
+            SELECT
+                entries.id AS entry_id,
+                entries.group_id AS group_id,
+                entries.is_center AS is_center,
+                entries.href AS href,
+                entries.published_at AS published_at,
+                entries.feed_id AS feed_id,
+                translations.value AS title,
+                group_titles.value AS group_title,
+                entries.category AS category,
+                entries.is_breaking AS is_breaking,
+                entries.is_duplicate AS is_duplicate,
+                entries.image_href AS image_href,
+                entries.is_paywalled AS is_paywalled,
+                COALESCE(entry_clicks.count, 0) AS clicks
+            FROM
+                fields
+                    JOIN translations ON translations.md5_hash = fields.md5_hash
+                    JOIN (
+                            SELECT
+                                entries.id AS id,
+                                (report_groups.center_embedding_id = embeddings.id) AS is_center,
+                                report_groups.category AS category,
+                                (report_groups.breaking_at IS NOT NULL) AS is_breaking,
+                                (entries.duplicate_of IS NOT NULL) AS is_duplicate,
+                                entries.image_href AS image_href,
+                                entries.paywalled AS is_paywalled,
+                                report_group_embeddings.report_group_id AS group_id,
+                                entries.href AS href,
+                                entries.published_at AS published_at,
+                                entries.feed_id AS feed_id
+                            FROM
+                                report_group_embeddings
+                                    JOIN report_groups ON report_group_embeddings.report_group_id = report_groups.id
+                                    JOIN embeddings ON embeddings.id = report_group_embeddings.embedding_id
+                                    JOIN fields ON fields.md5_hash = embeddings.md5_hash
+                                    JOIN entries ON entries.id = fields.entry_id
+                            WHERE
+                                report_groups.report_id = (
+                                    SELECT
+                                        id
+                                    FROM
+                                        reports
+                                    WHERE
+                                        created_at >= DATETIME($1, 'start of day')
+                                            AND created_at < DATETIME($1, 'start of day', '+1 day')
+                                    ORDER BY
+                                        created_at DESC
+                                    LIMIT 1
+                                )
+                                AND DATETIME(entries.published_at) >= DATETIME($3)
+                                AND DATETIME(entries.published_at) < DATETIME($4)
+                        ) AS entries ON entries.id = fields.entry_id
+                    LEFT JOIN (
+                            SELECT entry_id, SUM(count) AS count FROM entry_clicks GROUP BY entry_id
+                        ) AS entry_clicks ON entry_clicks.entry_id = entries.id
+                    LEFT JOIN group_titles ON
+                        group_titles.report_group_id = entries.group_id
+                        AND group_titles.lang_code = fields.lang_code
+            WHERE
+                fields.lang_code = $2
+                AND fields.name = 'title'
+            ORDER BY
+                entries.published_at DESC
+            ",
+        )
+        .bind(date)
+        .bind(lang_code)
+        .bind(published_at.start)
+        .bind(published_at.end)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn list_ungrouped_entries_by_date_lang_code(
+        &self,
+        date: chrono::NaiveDate,
+        lang_code: &feeds::LanguageCode,
+    ) -> Result<Vec<web::EntryView>, sqlx::Error> {
+        let date = date
+            .and_hms_opt(0, 0, 0)
+            .expect("failed to create start of day");
+        sqlx::query_as(
+            "
+            SELECT
+                entries.href AS href,
+                entries.published_at AS published_at,
+                entries.feed_id AS feed_id,
+                entries.title AS title
+            FROM (
+                SELECT
+                    entries.id AS id,
+                    entries.href AS href,
+                    entries.published_at AS published_at,
+                    entries.feed_id AS feed_id,
+                    (
+                        SELECT
+                            translations.value
+                        FROM
+                            fields
+                                JOIN translations ON translations.md5_hash = fields.md5_hash
+                        WHERE
+                            fields.entry_id = entries.id
+                            AND fields.name = 'title'
+                        ORDER BY
+                            fields.lang_code = $2 DESC
+                        LIMIT 1
+                    ) AS title
+                FROM
+                    entries
+                WHERE
+                    DATETIME(entries.published_at) >= DATETIME($1, 'start of day')
+                    AND DATETIME(entries.published_at) < DATETIME($1, 'start of day', '+1 day')
+            ) AS entries
+            WHERE
+                entries.title IS NOT NULL
+                AND NOT EXISTS (
+                    SELECT
+                        1
+                    FROM
+                        fields
+                            JOIN embeddings ON embeddings.md5_hash = fields.md5_hash
+                            JOIN report_group_embeddings ON report_group_embeddings.embedding_id = embeddings.id
+                            JOIN report_groups ON report_groups.id = report_group_embeddings.report_group_id
+                    WHERE
+                        fields.entry_id = entries.id
+                        AND report_groups.report_id = (
+                            SELECT
+                                id
+                            FROM
+                                reports
+                            WHERE
+                                created_at >= DATETIME($1, 'start of day')
+                                    AND created_at < DATETIME($1, 'start of day', '+1 day')
+                            ORDER BY
+                                created_at DESC
+                            LIMIT 1
+                        )
+                )
+            ORDER BY
+                entries.published_at DESC
+            ",
+        )
+        .bind(date)
+        .bind(lang_code)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// returns entries with a title or description that contains all words of the query, best
+    /// matches first, along with a snippet of the match. matching words of the snippet are
+    /// wrapped into [`web::SNIPPET_MATCH_START`] and [`web::SNIPPET_MATCH_END`].
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn list_search_results_by_keywords_lang_code(
+        &self,
+        keywords: &[&str],
+        lang_code: &feeds::LanguageCode,
+        limit: u32,
+    ) -> Result<Vec<web::SearchResultView>, sqlx::Error> {
+        // every keyword is quoted, so that it is matched as is and not as a query syntax
+        let query = keywords
+            .iter()
+            .map(|keyword| format!("\"{}\"", keyword.replace('"', "\"\"")))
+            .collect::<Vec<_>>()
+            .join(" ");
+        sqlx::query_as(
+            "
+            WITH matches AS (
+                SELECT
+                    rowid AS id,
+                    snippet(translations_fts, 0, $3, $4, '…', 16) AS snippet,
+                    rank
+                FROM
+                    translations_fts
+                WHERE
+                    translations_fts MATCH $1
+                ORDER BY
+                    rank
+                LIMIT 500
+            )
+            SELECT
+                entries.href AS href,
+                entries.published_at AS published_at,
+                entries.feed_id AS feed_id,
+                (
+                    SELECT
+                        translations.value
+                    FROM
+                        fields AS titles
+                            JOIN translations ON translations.md5_hash = titles.md5_hash
+                    WHERE
+                        titles.entry_id = entries.id
+                        AND titles.name = 'title'
+                    ORDER BY
+                        titles.lang_code = $2 DESC
+                    LIMIT 1
+                ) AS title,
+                (
+                    SELECT
+                        MAX(report_group_embeddings.report_group_id)
+                    FROM
+                        fields AS descriptions
+                            JOIN embeddings ON embeddings.md5_hash = descriptions.md5_hash
+                            JOIN report_group_embeddings ON report_group_embeddings.embedding_id = embeddings.id
+                    WHERE
+                        descriptions.entry_id = entries.id
+                ) AS group_id,
+                matches.snippet AS snippet
+            FROM
+                matches
+                    JOIN translations ON translations.id = matches.id
+                    JOIN fields ON
+                        fields.md5_hash = translations.md5_hash
+                        AND fields.name IN ('title', 'description')
+                    JOIN entries ON entries.id = fields.entry_id
+            WHERE
+                title IS NOT NULL
+            GROUP BY
+                entries.id
+            ORDER BY
+                MIN(matches.rank)
+            LIMIT $5
+            ",
+        )
+        .bind(query)
+        .bind(lang_code)
+        .bind(web::SNIPPET_MATCH_START)
+        .bind(web::SNIPPET_MATCH_END)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn list_search_results_by_embedding_id_lang_code(
+        &self,
+        embedding_id: Id<clustering::Embedding>,
+        lang_code: &feeds::LanguageCode,
+    ) -> Result<Vec<web::SearchResultView>, sqlx::Error> {
+        sqlx::query_as(
+            "
+            SELECT
+                entries.href AS href,
+                entries.published_at AS published_at,
+                entries.feed_id AS feed_id,
+                (
+                    SELECT
+                        translations.value
+                    FROM
+                        fields AS titles
+                            JOIN translations ON translations.md5_hash = titles.md5_hash
+                    WHERE
+                        titles.entry_id = entries.id
+                        AND titles.name = 'title'
+                    ORDER BY
+                        titles.lang_code = $2 DESC
+                    LIMIT 1
+                ) AS title,
+                (
+                    SELECT
+                        MAX(report_group_embeddings.report_group_id)
+                    FROM
+                        report_group_embeddings
+                    WHERE
+                        report_group_embeddings.embedding_id = embeddings.id
+                ) AS group_id
+            FROM
+                embeddings
+                    JOIN fields ON fields.md5_hash = embeddings.md5_hash
+                    JOIN entries ON entries.id = fields.entry_id
+            WHERE
+                embeddings.id = $1
+                AND title IS NOT NULL
+            GROUP BY
+                entries.id
+            ",
+        )
+        .bind(embedding_id)
+        .bind(lang_code)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn list_report_group_entries_by_id_lang_code(
+        &self,
+        id: Id<ReportGroup>,
+        lang_code: &feeds::LanguageCode,
+    ) -> Result<Vec<web::GroupEntryView>, sqlx::Error> {
+        sqlx::query_as(
+            "
+            SELECT
+                entries.id AS entry_id,
+                entries.group_id AS group_id,
+                entries.is_center AS is_center,
+                entries.href AS href,
+                entries.published_at AS published_at,
+                entries.feed_id AS feed_id,
+                translations.value AS title,
+                group_titles.value AS group_title,
+                entries.category AS category,
+                entries.is_duplicate AS is_duplicate,
+                entries.image_href AS image_href,
+                entries.is_paywalled AS is_paywalled
+            FROM
+                fields
+                    JOIN translations ON translations.md5_hash = fields.md5_hash
+                    JOIN (
+                            SELECT
+                                entries.id AS id,
+                                report_group_embeddings.report_group_id AS group_id,
+                                (report_groups.center_embedding_id = embeddings.id) AS is_center,
+                                report_groups.category AS category,
+                                (entries.duplicate_of IS NOT NULL) AS is_duplicate,
+                                entries.image_href AS image_href,
+                                entries.paywalled AS is_paywalled,
+                                entries.href AS href,
+                                entries.published_at AS published_at,
+                                entries.feed_id AS feed_id
+                            FROM
+                                report_group_embeddings
+                                    JOIN report_groups ON report_group_embeddings.report_group_id = report_groups.id
+                                    JOIN embeddings ON embeddings.id = report_group_embeddings.embedding_id
+                                    JOIN fields ON fields.md5_hash = embeddings.md5_hash
+                                    JOIN entries ON entries.id = fields.entry_id
+                            WHERE
+                                report_group_embeddings.report_group_id = ?
+                        ) AS entries ON entries.id = fields.entry_id
+                    LEFT JOIN group_titles ON
+                        group_titles.report_group_id = entries.group_id
+                        AND group_titles.lang_code = fields.lang_code
+            WHERE
+                fields.lang_code = ?
+                AND fields.name = 'title'
+            ORDER BY
+                entries.published_at DESC
+            ",
+        )
+        .bind(id)
+        .bind(lang_code)
+        .fetch_all(&self.pool)
+        .await
+    }
+}
+
+impl Client {
+    /// queues tasks of the kind with their inputs. done tasks are only queued again when their
+    /// inputs changed, failed ones are left as they are.
+    #[tracing::instrument(level = "debug", skip(self, targets), fields(count = targets.len()))]
+    pub async fn insert_tasks(
+        &self,
+        kind: tasks::Kind,
+        targets: &[(u32, Option<String>)],
+    ) -> Result<(), sqlx::Error> {
+        let mut transaction = self.pool.begin().await?;
+        for chunk in targets.chunks(BATCH_SIZE) {
+            let mut query = sqlx::QueryBuilder::new("INSERT INTO tasks (kind, target_id, input) ");
+            query.push_values(chunk, |mut row, (target_id, input)| {
+                row.push_bind(kind).push_bind(target_id).push_bind(input);
+            });
+            query.push(
+                " ON CONFLICT (kind, target_id) DO UPDATE SET status = 'pending', attempts = 0, error = NULL, input = excluded.input, updated_at = CURRENT_TIMESTAMP WHERE status = 'done' AND input IS NOT excluded.input",
+            );
+            query.build().execute(&mut *transaction).await?;
+        }
+        transaction.commit().await
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn list_pending_tasks(
+        &self,
+        kind: tasks::Kind,
+    ) -> Result<Vec<tasks::Task>, sqlx::Error> {
+        sqlx::query_as("SELECT * FROM tasks WHERE kind = ? AND status = 'pending' ORDER BY id")
+            .bind(kind)
+            .fetch_all(&self.pool)
+            .await
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn complete_task(
+        &self,
+        kind: tasks::Kind,
+        target_id: u32,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "UPDATE tasks SET status = 'done', error = NULL, updated_at = CURRENT_TIMESTAMP WHERE kind = ? AND target_id = ?",
+        )
+        .bind(kind)
+        .bind(target_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn fail_task(
+        &self,
+        kind: tasks::Kind,
+        target_id: u32,
+        error: &str,
+        max_attempts: u32,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "UPDATE tasks SET attempts = attempts + 1, error = ?, status = CASE WHEN attempts + 1 >= ? THEN 'failed' ELSE 'pending' END, updated_at = CURRENT_TIMESTAMP WHERE kind = ? AND target_id = ?",
+        )
+        .bind(error)
+        .bind(max_attempts)
+        .bind(kind)
+        .bind(target_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn retry_failed_tasks(&self) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(
+            "UPDATE tasks SET status = 'pending', attempts = 0, error = NULL, updated_at = CURRENT_TIMESTAMP WHERE status = 'failed'",
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn list_failed_tasks(&self, limit: u32) -> Result<Vec<tasks::Task>, sqlx::Error> {
+        sqlx::query_as(
+            "SELECT * FROM tasks WHERE error IS NOT NULL AND status != 'done' ORDER BY updated_at DESC, id DESC LIMIT ?",
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn count_tasks_by_kind_status(
+        &self,
+    ) -> Result<Vec<(tasks::Kind, tasks::Status, u32)>, sqlx::Error> {
+        sqlx::query_as(
+            "SELECT kind, status, COUNT(*) FROM tasks GROUP BY kind, status ORDER BY kind, status",
+        )
+        .fetch_all(&self.pool)
+        .await
+    }
+}
+
+impl Client {
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn delete_orphaned_translations(&self) -> Result<u64, sqlx::Error> {
+        sqlx::query(
+            "
+            DELETE FROM
+                translations
+            WHERE
+                NOT EXISTS (SELECT 1 FROM fields WHERE fields.md5_hash = translations.md5_hash)
+                AND NOT EXISTS (SELECT 1 FROM field_revisions WHERE field_revisions.md5_hash = translations.md5_hash)
+            ",
+        )
+        .execute(&self.pool)
+        .await
+        .map(|result| result.rows_affected())
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn delete_orphaned_embeddings(&self) -> Result<u64, sqlx::Error> {
+        sqlx::query(
+            "
+            DELETE FROM
+                embeddings
+            WHERE
+                NOT EXISTS (SELECT 1 FROM fields WHERE fields.md5_hash = embeddings.md5_hash)
+                AND NOT EXISTS (SELECT 1 FROM report_group_embeddings WHERE report_group_embeddings.embedding_id = embeddings.id)
+                AND NOT EXISTS (SELECT 1 FROM report_groups WHERE report_groups.center_embedding_id = embeddings.id)
+            ",
+        )
+        .execute(&self.pool)
+        .await
+        .map(|result| result.rows_affected())
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn delete_orphaned_group_summaries(&self) -> Result<u64, sqlx::Error> {
+        sqlx::query(
+            "DELETE FROM group_summaries WHERE NOT EXISTS (SELECT 1 FROM report_groups WHERE report_groups.summary_md5_hash = group_summaries.md5_hash)",
+        )
+        .execute(&self.pool)
+        .await
+        .map(|result| result.rows_affected())
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn vacuum_into(&self, path: &std::path::Path) -> Result<(), sqlx::Error> {
+        sqlx::query("VACUUM INTO ?")
+            .bind(path.to_string_lossy())
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn optimize(&self) -> Result<(), sqlx::Error> {
+        sqlx::query("PRAGMA optimize").execute(&self.pool).await?;
+        sqlx::query("VACUUM").execute(&self.pool).await?;
+        Ok(())
+    }
+}
+
+/// formats time the way sqlite's `CURRENT_TIMESTAMP` does, so that it is ordered correctly among
+/// default values when compared as text
+fn timestamp(time: chrono::DateTime<chrono::Utc>) -> String {
+    time.format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+impl<'a> sqlx::FromRow<'a, sqlx::sqlite::SqliteRow> for Embedding {
+    fn from_row(row: &'a sqlx::sqlite::SqliteRow) -> Result<Self, sqlx::Error> {
+        use sqlx::Row;
+
+        let md5_hash = row.try_get("md5_hash")?;
+        let size: u32 = row.try_get("size")?;
+        let model = row.try_get("model")?;
+
+        let value: Vec<u8> = row.try_get("value")?;
+        let value = quantization::decode(&value, row.try_get("precision")?, row.try_get("scale")?)
+            .map_err(|error| sqlx::Error::Decode(Box::new(error)))?;
+
+        Ok(Embedding {
+            md5_hash,
+            value,
+            size,
+            model,
+        })
+    }
+}
+
+async fn convert_json_embeddings(pool: &sqlx::SqlitePool) -> Result<(), sqlx::Error> {
+    use sqlx::Row;
+
+    loop {
+        let rows =
+            sqlx::query("SELECT id, value FROM embeddings WHERE typeof(value) = 'text' LIMIT 1000")
+                .fetch_all(pool)
+                .await?;
+        if rows.is_empty() {
+            return Ok(());
+        }
+        tracing::info!(rows = rows.len(), "converting embeddings to blobs");
+
+        let mut transaction = pool.begin().await?;
+        for row in rows {
+            let id: Id<Embedding> = row.try_get("id")?;
+            let value: String = row.try_get("value")?;
+            let value: Vec<f32> = serde_json::from_str(&value)
+                .map_err(|error| sqlx::Error::Decode(Box::new(error)))?;
+            sqlx::query("UPDATE embeddings SET value = ? WHERE id = ?")
+                .bind(quantization::encode(&value, quantization::Precision::F32).0)
+                .bind(id)
+                .execute(&mut *transaction)
+                .await?;
+        }
+        transaction.commit().await?;
+    }
+}
+
+/// hrefs of entries used to be stored as crawled, canonicalizes them in batches. entries that
+/// turn out to be the same are merged into the earliest of them.
+async fn canonicalize_entry_hrefs(pool: &sqlx::SqlitePool) -> Result<(), sqlx::Error> {
+    loop {
+        let ids: Vec<Id<feeds::Entry>> = sqlx::query_scalar(
+            "SELECT entry_id FROM entries_to_canonicalize ORDER BY entry_id LIMIT 1000",
+        )
+        .fetch_all(pool)
+        .await?;
+        if ids.is_empty() {
+            return Ok(());
+        }
+        tracing::info!(entries = ids.len(), "canonicalizing hrefs of entries");
+
+        let mut transaction = pool.begin().await?;
+        for id in ids {
+            let href: Option<String> = sqlx::query_scalar("SELECT href FROM entries WHERE id = ?")
+                .bind(id)
+                .fetch_optional(&mut *transaction)
+                .await?;
+            let canonical = href
+                .as_deref()
+                .and_then(|href| href.parse::<crate::url::Url>().ok())
+                .map(|url| url.to_string());
+            if let Some(canonical) = canonical.filter(|canonical| Some(canonical) != href.as_ref())
+            {
+                let duplicate_id: Option<Id<feeds::Entry>> =
+                    sqlx::query_scalar("SELECT id FROM entries WHERE href = ?")
+                        .bind(&canonical)
+                        .fetch_optional(&mut *transaction)
+                        .await?;
+                let (kept_id, merged_id) = match duplicate_id {
+                    Some(duplicate_id) if duplicate_id < id => (duplicate_id, Some(id)),
+                    Some(duplicate_id) => (id, Some(duplicate_id)),
+                    None => (id, None),
+                };
+                if let Some(merged_id) = merged_id {
+                    merge_entry(&mut transaction, kept_id, merged_id).await?;
+                }
+                sqlx::query("UPDATE entries SET href = ? WHERE id = ?")
+                    .bind(&canonical)
+                    .bind(kept_id)
+                    .execute(&mut *transaction)
+                    .await?;
+            }
+            sqlx::query("DELETE FROM entries_to_canonicalize WHERE entry_id = ?")
+                .bind(id)
+                .execute(&mut *transaction)
+                .await?;
+        }
+        transaction.commit().await?;
+    }
+}
+
+/// embeddings of the fields of a merged entry, paired with embeddings of the same fields of the
+/// kept entry. embeddings other entries share are left alone.
+const MERGED_EMBEDDINGS: &str = "WITH replacements AS (
+    SELECT merged_embeddings.id AS merged_id, kept_embeddings.id AS kept_id
+    FROM fields AS merged_fields
+    JOIN embeddings AS merged_embeddings ON merged_embeddings.md5_hash = merged_fields.md5_hash
+    JOIN fields AS kept_fields ON
+        kept_fields.entry_id = $1
+        AND kept_fields.name = merged_fields.name
+        AND kept_fields.lang_code = merged_fields.lang_code
+    JOIN embeddings AS kept_embeddings ON
+        kept_embeddings.md5_hash = kept_fields.md5_hash
+        AND kept_embeddings.model = merged_embeddings.model
+    WHERE
+        merged_fields.entry_id = $2
+        AND NOT EXISTS (
+            SELECT 1 FROM fields
+            WHERE fields.md5_hash = merged_fields.md5_hash AND fields.entry_id != $2
+        )
+)";
+
+async fn merge_entry(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    kept_id: Id<feeds::Entry>,
+    merged_id: Id<feeds::Entry>,
+) -> Result<(), sqlx::Error> {
+    for query in [
+        // groups that already have the kept entry keep a single copy of it
+        "UPDATE OR IGNORE report_group_embeddings SET embedding_id = (SELECT kept_id FROM replacements WHERE merged_id = report_group_embeddings.embedding_id) WHERE embedding_id IN (SELECT merged_id FROM replacements)",
+        "DELETE FROM report_group_embeddings WHERE embedding_id IN (SELECT merged_id FROM replacements)",
+        "UPDATE report_groups SET center_embedding_id = (SELECT kept_id FROM replacements WHERE merged_id = report_groups.center_embedding_id) WHERE center_embedding_id IN (SELECT merged_id FROM replacements)",
+    ] {
+        sqlx::query(&format!("{MERGED_EMBEDDINGS} {query}"))
+            .bind(kept_id)
+            .bind(merged_id)
+            .execute(&mut **transaction)
+            .await?;
+    }
+    for query in [
+        "INSERT INTO entry_clicks (entry_id, date, count) SELECT $1, date, count FROM entry_clicks WHERE entry_id = $2 ON CONFLICT (entry_id, date) DO UPDATE SET count = count + excluded.count",
+        "DELETE FROM entry_clicks WHERE entry_id = $2",
+        // the kept entry is not a duplicate of itself
+        "UPDATE entries SET duplicate_of = NULLIF($1, id) WHERE duplicate_of = $2",
+        "DELETE FROM tasks WHERE kind IN ('embed', 'translate') AND target_id IN (SELECT id FROM fields WHERE entry_id = $2)",
+        "DELETE FROM field_revisions WHERE entry_id = $2",
+        "DELETE FROM fields WHERE entry_id = $2",
+        "DELETE FROM entries WHERE id = $2",
+    ] {
+        sqlx::query(query)
+            .bind(kept_id)
+            .bind(merged_id)
+            .execute(&mut **transaction)
+            .await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn client() -> Client {
+        Client::connect("sqlite::memory:", &Config::default())
+            .await
+            .expect("failed to create in-memory db")
+    }
+
+    async fn insert_story(
+        db: &Client,
+        href: &str,
+        title: &str,
+        description: &str,
+        value: Vec<f32>,
+    ) -> (Persisted<feeds::Entry>, Persisted<Embedding>) {
+        let entry = db
+            .insert_entry(&feeds::Entry {
+                feed_id: Id::from(1),
+                href: href.parse().expect("valid url"),
+                published_at: chrono::Utc::now(),
+                image_href: None,
+                region: None,
+            })
+            .await
+            .expect("failed to insert entry")
+            .expect("entry is new");
+        for (name, value) in [
+            (feeds::FieldName::Title, title),
+            (feeds::FieldName::Description, description),
+        ] {
+            let md5_hash = crate::md5_hash::compute(value);
+            db.insert_translation(feeds::Translation {
+                md5_hash,
+                value: value.to_string(),
+            })
+            .await
+            .expect("failed to insert translation");
+            db.insert_field(feeds::Field {
+                entry_id: entry.id,
+                name,
+                lang_code: feeds::LanguageCode::SV,
+                md5_hash,
+            })
+            .await
+            .expect("failed to insert field");
+        }
+        let embedding = db
+            .insert_embeddig(
+                &Embedding {
+                    md5_hash: crate::md5_hash::compute(description),
+                    size: u32::try_from(value.len()).expect("small embedding"),
+                    value,
+                    model: "test".to_string(),
+                },
+                quantization::Precision::F32,
+            )
+            .await
+            .expect("failed to insert embedding")
+            .expect("embedding is new");
+        (entry, embedding)
+    }
+
+    async fn insert_report(
+        db: &Client,
+        created_at: chrono::DateTime<chrono::Utc>,
+    ) -> Persisted<clustering::Report> {
+        db.insert_report(
+            &clustering::Report {
+                algorithm: clustering::Algorithm::Dbscan,
+                tolerance_method: None,
+                min_points: 2,
+                tolerance: 0.5,
+                tolerance_range_start: 0.1,
+                tolerance_range_end: 1.0,
+                samples: 10,
+                score_metric: clustering::ScoreMetric::Silhouette,
+                score: 0.5,
+                davies_bouldin: None,
+                calinski_harabasz: None,
+                rows: 2,
+                dimentions: 2,
+                window_start: None,
+                window_end: None,
+                updated_at: None,
+            },
+            created_at,
+        )
+        .await
+        .expect("failed to insert report")
+    }
+
+    #[tokio::test]
+    async fn entries_are_inserted_once() {
+        let db = client().await;
+        let (entry, embedding) = insert_story(
+            &db,
+            "https://example.com/1",
+            "Rubrik",
+            "Text",
+            vec![1.0, 0.0],
+        )
+        .await;
+
+        let duplicate = db
+            .insert_entry(&entry.value)
+            .await
+            .expect("failed to insert entry");
+        assert!(duplicate.is_none());
+
+        let found = db
+            .find_entry_by_id(&entry.id)
+            .await
+            .expect("failed to find entry");
+        assert_eq!(found.value.href.to_string(), entry.value.href.to_string());
+
+        let entries = db
+            .list_entries_by_embedding_id(&embedding.id)
+            .await
+            .expect("failed to list entries");
+        assert_eq!(entries.len(), 1);
+
+        let title = db
+            .find_field_by_entry_id_name_lang_code(
+                &entry.id,
+                &feeds::FieldName::Title,
+                &feeds::LanguageCode::SV,
+            )
+            .await
+            .expect("failed to find field")
+            .expect("title exists");
+        let translation = db
+            .find_translation_by_md5_hash(&title.value.md5_hash)
+            .await
+            .expect("failed to find translation");
+        assert_eq!(translation.value.value, "Rubrik");
+        assert_eq!(
+            db.list_fields_by_md5_hash(&title.value.md5_hash)
+                .await
+                .expect("failed to list fields")
+                .len(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn entries_are_inserted_in_batches() {
+        let db = client().await;
+        let entry = |href: &str| feeds::Entry {
+            feed_id: Id::from(1),
+            href: href.parse().expect("valid url"),
+            published_at: chrono::Utc::now(),
+            image_href: None,
+            region: None,
+        };
+        let fields = |title: &str| {
+            vec![(
+                feeds::FieldName::Title,
+                feeds::LanguageCode::SV,
+                title.to_string(),
+            )]
+        };
+
+        let inserted = db
+            .insert_entries_batch(&[
+                (entry("https://example.com/1"), fields("Första")),
+                (entry("https://example.com/2"), fields("Andra")),
+            ])
+            .await
+            .expect("failed to insert entries");
+        assert_eq!(inserted.len(), 2);
+
+        let inserted = db
+            .insert_entries_batch(&[
+                (entry("https://example.com/2"), fields("Ändrad")),
+                (entry("https://example.com/3"), fields("Första")),
+            ])
+            .await
+            .expect("failed to insert entries");
+        assert_eq!(inserted.len(), 1);
+        assert_eq!(inserted[0].value.href.to_string(), "https://example.com/3");
+
+        // existing entries keep their fields, translations are shared
+        let md5_hash = crate::md5_hash::compute("Första");
+        assert_eq!(
+            db.list_fields_by_md5_hash(&md5_hash)
+                .await
+                .expect("failed to list fields")
+                .len(),
+            2
+        );
+        assert!(db
+            .list_fields_by_md5_hash(&crate::md5_hash::compute("Ändrad"))
+            .await
+            .expect("failed to list fields")
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn embeddings_round_trip() {
+        let db = client().await;
+        let (_, embedding) = insert_story(
+            &db,
+            "https://example.com/1",
+            "Rubrik",
+            "Text",
+            vec![0.5, -1.0],
+        )
+        .await;
+
+        let found = db
+            .find_embedding_by_id(&embedding.id)
+            .await
+            .expect("failed to find embedding");
+        assert_eq!(found.value.value, vec![0.5, -1.0]);
+
+        let now = chrono::Utc::now();
+        let listed = db
+            .list_embeddings_by_field_name_lang_code_published_at_model(
+                feeds::FieldName::Description,
+                feeds::LanguageCode::SV,
+                &(now - chrono::Duration::hours(1)..now + chrono::Duration::hours(1)),
+                "test",
+            )
+            .await
+            .expect("failed to list embeddings");
+        assert_eq!(listed.len(), 1);
+
+        let missing = db
+            .list_fields_without_embeddings_by_lang_code_field_name_date_model(
+                feeds::LanguageCode::SV,
+                feeds::FieldName::Title,
+                &now.date_naive(),
+                "test",
+            )
+            .await
+            .expect("failed to list fields");
+        assert_eq!(missing.len(), 1);
+        assert_eq!(
+            format!("{:?}", missing[0].value.md5_hash),
+            format!("{:?}", crate::md5_hash::compute("Rubrik"))
+        );
+    }
+
+    #[tokio::test]
+    async fn reports() {
+        let db = client().await;
+        let report = insert_report(&db, chrono::Utc::now()).await;
+        let today = chrono::Utc::now().date_naive();
+        let latest = db
+            .find_latest_report_by_date(today)
+            .await
+            .expect("failed to find report")
+            .expect("report exists");
+        assert_eq!(latest.id, report.id);
+        assert_eq!(
+            db.find_latest_report_before(chrono::Utc::now() + chrono::Duration::seconds(1))
+                .await
+                .expect("failed to find report")
+                .map(|report| report.id),
+            Some(report.id)
+        );
+        assert_eq!(latest.value.updated_at, None);
+        db.update_report_updated_at(report.id)
+            .await
+            .expect("failed to update report");
+        let latest = db
+            .find_latest_report()
+            .await
+            .expect("failed to find report")
+            .expect("report exists");
+        assert_eq!(latest.id, report.id);
+        assert!(latest.value.updated_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn report_groups() {
+        let db = client().await;
+        let (first_entry, first) = insert_story(
+            &db,
+            "https://example.com/1",
+            "Första",
+            "Ett",
+            vec![1.0, 0.0],
+        )
+        .await;
+        let (_, second) =
+            insert_story(&db, "https://example.com/2", "Andra", "Två", vec![0.0, 1.0]).await;
+        insert_story(
+            &db,
+            "https://example.com/3",
+            "Tredje",
+            "Tre",
+            vec![1.0, 1.0],
+        )
+        .await;
+
+        let report = insert_report(&db, chrono::Utc::now()).await;
+        let today = chrono::Utc::now().date_naive();
+
+        let group = db
+            .insert_report_group(ReportGroup {
+                report_id: report.id,
+                embedding_ids: vec![first.id],
+                center_embedding_id: first.id,
+            })
+            .await
+            .expect("failed to insert group");
+        db.insert_report_group_embeddings(group.id, &[second.id])
+            .await
+            .expect("failed to insert group embeddings");
+        let groups = db
+            .list_report_groups_by_report_id(report.id)
+            .await
+            .expect("failed to list groups");
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].value.embedding_ids, vec![first.id, second.id]);
+        assert_eq!(
+            db.find_latest_report_group_id_by_entry_id(first_entry.id)
+                .await
+                .expect("failed to find group"),
+            Some(group.id)
+        );
+
+        db.insert_group_title(group.id, &feeds::LanguageCode::SV, "Rubrik")
+            .await
+            .expect("failed to insert title");
+        db.update_report_group_category(group.id, clustering::Category::Sports)
+            .await
+            .expect("failed to update category");
+
+        let entries = db
+            .list_report_group_entries_by_date_lang_code(today, &feeds::LanguageCode::SV)
+            .await
+            .expect("failed to list entries");
+        assert_eq!(entries.len(), 2);
+        assert!(entries.iter().all(|entry| entry.group_id == group.id));
+        assert!(entries
+            .iter()
+            .all(|entry| entry.group_title.as_deref() == Some("Rubrik")));
+        assert!(entries
+            .iter()
+            .all(|entry| entry.category == Some(clustering::Category::Sports)));
+        assert_eq!(entries.iter().filter(|entry| entry.is_center).count(), 1);
+
+        let entries = db
+            .list_report_group_entries_by_id_lang_code(group.id, &feeds::LanguageCode::SV)
+            .await
+            .expect("failed to list entries");
+        assert_eq!(entries.len(), 2);
+
+        let ungrouped = db
+            .list_ungrouped_entries_by_date_lang_code(today, &feeds::LanguageCode::SV)
+            .await
+            .expect("failed to list ungrouped entries");
+        assert_eq!(ungrouped.len(), 1);
+        assert_eq!(ungrouped[0].title, "Tredje");
+
+        let mut descriptions = db
+            .list_descriptions_by_report_group_id(group.id)
+            .await
+            .expect("failed to list descriptions");
+        descriptions.sort();
+        assert_eq!(descriptions, vec!["Ett", "Två"]);
+    }
+
+    #[tokio::test]
+    async fn group_summaries() {
+        let db = client().await;
+        let (_, first) = insert_story(
+            &db,
+            "https://example.com/1",
+            "Första",
+            "Ett",
+            vec![1.0, 0.0],
+        )
+        .await;
+        let report = insert_report(&db, chrono::Utc::now()).await;
+        let parent = db
+            .insert_report_group(ReportGroup {
+                report_id: report.id,
+                embedding_ids: vec![first.id],
+                center_embedding_id: first.id,
+            })
+            .await
+            .expect("failed to insert group");
+
+        let md5_hash = crate::md5_hash::compute("Ett");
+        db.insert_group_summary(&md5_hash, &feeds::LanguageCode::SV, "Sammanfattning")
+            .await
+            .expect("failed to insert summary");
+        db.update_report_group_summary_md5_hash(parent.id, &md5_hash)
+            .await
+            .expect("failed to update summary hash");
+        assert_eq!(
+            db.find_group_summary_by_md5_hash_lang_code(&md5_hash, &feeds::LanguageCode::SV)
+                .await
+                .expect("failed to find summary")
+                .as_deref(),
+            Some("Sammanfattning")
+        );
+        assert_eq!(
+            db.find_group_summary_by_report_group_id_lang_code(parent.id, &feeds::LanguageCode::SV)
+                .await
+                .expect("failed to find summary")
+                .as_deref(),
+            Some("Sammanfattning")
+        );
+    }
+
+    #[tokio::test]
+    async fn group_lineage_and_related_groups() {
+        let db = client().await;
+        let (_, first) = insert_story(
+            &db,
+            "https://example.com/1",
+            "Första",
+            "Ett",
+            vec![1.0, 0.0],
+        )
+        .await;
+        let (_, second) =
+            insert_story(&db, "https://example.com/2", "Andra", "Två", vec![0.0, 1.0]).await;
+
+        let report = insert_report(&db, chrono::Utc::now()).await;
+        let parent = db
+            .insert_report_group(ReportGroup {
+                report_id: report.id,
+                embedding_ids: vec![first.id],
+                center_embedding_id: first.id,
+            })
+            .await
+            .expect("failed to insert group");
+        let child = db
+            .insert_report_group(ReportGroup {
+                report_id: report.id,
+                embedding_ids: vec![second.id],
+                center_embedding_id: second.id,
+            })
+            .await
+            .expect("failed to insert group");
+
+        db.insert_report_group_lineage(child.id, parent.id, 1)
+            .await
+            .expect("failed to insert lineage");
+        assert_eq!(
+            db.find_latest_descendant_report_group_id_by_id(parent.id)
+                .await
+                .expect("failed to find descendant"),
+            Some(child.id)
+        );
+        assert_eq!(
+            db.find_latest_descendant_report_group_id_by_id(child.id)
+                .await
+                .expect("failed to find descendant"),
+            Some(child.id)
+        );
+        let parent_created_at = sqlx::query_scalar::<_, chrono::DateTime<chrono::Utc>>(
+            "SELECT created_at FROM report_groups WHERE id = ?",
+        )
+        .bind(parent.id)
+        .fetch_one(&db.pool)
+        .await
+        .expect("failed to select parent");
+        assert_eq!(
+            db.find_report_group_first_seen_by_id(child.id)
+                .await
+                .expect("failed to find first seen"),
+            Some(parent_created_at)
+        );
+
+        assert_eq!(
+            db.find_report_group_breaking_at_by_id(child.id)
+                .await
+                .expect("failed to find breaking at"),
+            None
+        );
+        db.update_report_group_breaking_at(parent.id, parent_created_at)
+            .await
+            .expect("failed to update breaking at");
+        assert_eq!(
+            db.find_report_group_breaking_at_by_id(child.id)
+                .await
+                .expect("failed to find breaking at"),
+            Some(parent_created_at)
+        );
+
+        db.insert_report_group_post(parent.id, social::Network::Mastodon, parent_created_at)
+            .await
+            .expect("failed to insert post");
+        assert_eq!(
+            db.find_report_group_posted_at_by_id_network(child.id, social::Network::Mastodon)
+                .await
+                .expect("failed to find posted at"),
+            Some(parent_created_at)
+        );
+        assert_eq!(
+            db.find_report_group_posted_at_by_id_network(child.id, social::Network::Bluesky)
+                .await
+                .expect("failed to find posted at"),
+            None
+        );
+
+        db.replace_related_groups(child.id, &[(parent.id, 0.5)])
+            .await
+            .expect("failed to replace related groups");
+        db.replace_related_groups(child.id, &[(parent.id, 0.25)])
+            .await
+            .expect("failed to replace related groups");
+        let related = db
+            .list_related_groups_by_id_lang_code(child.id, &feeds::LanguageCode::SV)
+            .await
+            .expect("failed to list related groups");
+        assert_eq!(related.len(), 1);
+        assert_eq!(related[0].group_id, parent.id);
+        assert_eq!(related[0].title, "Första");
+    }
+
+    #[tokio::test]
+    async fn report_group_changes() {
+        let db = client().await;
+        let (_, first) = insert_story(
+            &db,
+            "https://example.com/1",
+            "Första",
+            "Ett",
+            vec![1.0, 0.0],
+        )
+        .await;
+        let (_, second) =
+            insert_story(&db, "https://example.com/2", "Andra", "Två", vec![0.9, 0.1]).await;
+        let (_, third) = insert_story(
+            &db,
+            "https://example.com/3",
+            "Tredje",
+            "Tre",
+            vec![0.0, 1.0],
+        )
+        .await;
+
+        let previous_report = insert_report(&db, chrono::Utc::now()).await;
+        let previous = db
+            .insert_report_group(ReportGroup {
+                report_id: previous_report.id,
+                embedding_ids: vec![first.id],
+                center_embedding_id: first.id,
+            })
+            .await
+            .expect("failed to insert group");
+
+        let report = insert_report(&db, chrono::Utc::now()).await;
+        let grown = db
+            .insert_report_group(ReportGroup {
+                report_id: report.id,
+                embedding_ids: vec![first.id, second.id],
+                center_embedding_id: first.id,
+            })
+            .await
+            .expect("failed to insert group");
+        let new = db
+            .insert_report_group(ReportGroup {
+                report_id: report.id,
+                embedding_ids: vec![third.id],
+                center_embedding_id: third.id,
+            })
+            .await
+            .expect("failed to insert group");
+        db.insert_report_group_lineage(grown.id, previous.id, 1)
+            .await
+            .expect("failed to insert lineage");
+
+        let mut changes = db
+            .list_report_group_changes_by_report_id_since_report_id(report.id, previous_report.id)
+            .await
+            .expect("failed to list changes");
+        changes.sort_by_key(|change| change.group_id);
+        assert_eq!(
+            changes
+                .iter()
+                .map(|change| (change.group_id, change.size, change.previous_size))
+                .collect::<Vec<_>>(),
+            vec![(grown.id, 2, Some(1)), (new.id, 1, None)]
+        );
+    }
+
+    #[tokio::test]
+    async fn report_group_entries_published_at() {
+        let db = client().await;
+        let (_, embedding) = insert_story(
+            &db,
+            "https://example.com/1",
+            "Rubrik",
+            "Text",
+            vec![1.0, 0.0],
+        )
+        .await;
+        let report = insert_report(&db, chrono::Utc::now()).await;
+        db.insert_report_group(ReportGroup {
+            report_id: report.id,
+            embedding_ids: vec![embedding.id],
+            center_embedding_id: embedding.id,
+        })
+        .await
+        .expect("failed to insert group");
+
+        let today = chrono::Utc::now().date_naive();
+        let now = chrono::Utc::now();
+        let recent = db
+            .list_report_group_entries_by_date_lang_code_published_at(
+                today,
+                &feeds::LanguageCode::SV,
+                &(now - chrono::Duration::hours(1)..now + chrono::Duration::seconds(1)),
+            )
+            .await
+            .expect("failed to list entries");
+        assert_eq!(recent.len(), 1);
+        let old = db
+            .list_report_group_entries_by_date_lang_code_published_at(
+                today,
+                &feeds::LanguageCode::SV,
+                &(now - chrono::Duration::hours(2)..now - chrono::Duration::hours(1)),
+            )
+            .await
+            .expect("failed to list entries");
+        assert!(old.is_empty());
+    }
+
+    #[tokio::test]
+    async fn field_revisions() {
+        let db = client().await;
+        let crawled = |title: &str| {
+            (
+                feeds::Entry {
+                    feed_id: Id::from(1),
+                    href: "https://example.com/1".parse().expect("valid url"),
+                    published_at: chrono::Utc::now(),
+                    image_href: None,
+                    region: None,
+                },
+                vec![
+                    (
+                        feeds::FieldName::Title,
+                        feeds::LanguageCode::SV,
+                        title.to_string(),
+                    ),
+                    (
+                        feeds::FieldName::Description,
+                        feeds::LanguageCode::SV,
+                        "Text".to_string(),
+                    ),
+                ],
+            )
+        };
+        let inserted = db
+            .insert_entries_batch(&[crawled("Rubrik")])
+            .await
+            .expect("failed to insert entries");
+        let entry_id = inserted[0].id;
+        for (title, revised) in [("Rubrik", 0), ("Ny rubrik", 1), ("Ny rubrik", 0)] {
+            assert_eq!(
+                db.insert_field_revisions(&[crawled(title)])
+                    .await
+                    .expect("failed to insert revisions"),
+                revised
+            );
+        }
+
+        db.delete_orphaned_translations()
+            .await
+            .expect("failed to delete orphans");
+        let revisions = db
+            .list_field_revisions_by_entry_id(entry_id)
+            .await
+            .expect("failed to list revisions");
+        assert_eq!(
+            revisions
+                .iter()
+                .map(|revision| revision.value.as_str())
+                .collect::<Vec<_>>(),
+            vec!["Rubrik", "Ny rubrik"]
+        );
+        assert!(revisions
+            .iter()
+            .all(|revision| revision.name == feeds::FieldName::Title));
+
+        let embedding = db
+            .insert_embeddig(
+                &Embedding {
+                    md5_hash: crate::md5_hash::compute("Text"),
+                    size: 2,
+                    value: vec![1.0, 0.0],
+                    model: "test".to_string(),
+                },
+                quantization::Precision::F32,
+            )
+            .await
+            .expect("failed to insert embedding")
+            .expect("embedding is new");
+        let mut revised = crawled("Ny rubrik");
+        revised.1[1].2 = "Ny text".to_string();
+        assert_eq!(
+            db.insert_field_revisions(&[revised])
+                .await
+                .expect("failed to insert revisions"),
+            1
+        );
+        let description = db
+            .find_field_by_entry_id_name_lang_code(
+                &entry_id,
+                &feeds::FieldName::Description,
+                &feeds::LanguageCode::SV,
+            )
+            .await
+            .expect("failed to find field")
+            .expect("field exists");
+        assert!(description.value.md5_hash == crate::md5_hash::compute("Ny text"));
+        let superseded_at = sqlx::query_scalar::<_, Option<chrono::DateTime<chrono::Utc>>>(
+            "SELECT superseded_at FROM embeddings WHERE id = ?",
+        )
+        .bind(embedding.id)
+        .fetch_one(&db.pool)
+        .await
+        .expect("failed to select embedding");
+        assert!(superseded_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn entry_hrefs_are_canonical() {
+        let db = client().await;
+        let entry = db
+            .insert_entry(&feeds::Entry {
+                feed_id: Id::from(1),
+                href: "http://example.com/a/?utm_source=rss&id=1#top"
+                    .parse()
+                    .expect("valid url"),
+                published_at: chrono::Utc::now(),
+                image_href: None,
+                region: None,
+            })
+            .await
+            .expect("failed to insert entry")
+            .expect("entry is new");
+        assert_eq!(entry.value.href.to_string(), "https://example.com/a?id=1");
+
+        // entries stored before hrefs were canonical
+        for href in [
+            "https://example.com/b?ref=rss",
+            "https://example.com/b",
+            "http://example.com/b/",
+            "https://example.com/c?ref=sport",
+        ] {
+            sqlx::query("INSERT INTO entries (href, feed_id, published_at) VALUES (?, 1, ?)")
+                .bind(href)
+                .bind(chrono::Utc::now())
+                .execute(&db.pool)
+                .await
+                .expect("failed to insert entry");
+        }
+        sqlx::query("INSERT INTO entries_to_canonicalize (entry_id) SELECT id FROM entries")
+            .execute(&db.pool)
+            .await
+            .expect("failed to queue entries");
+        canonicalize_entry_hrefs(&db.pool)
+            .await
+            .expect("failed to canonicalize hrefs");
+        let entries =
+            sqlx::query_as::<_, (u32, String)>("SELECT id, href FROM entries ORDER BY id")
+                .fetch_all(&db.pool)
+                .await
+                .expect("failed to list entries");
+        assert_eq!(
+            entries,
+            vec![
+                (
+                    u32::from(entry.id),
+                    "https://example.com/a?id=1".to_string()
+                ),
+                (u32::from(entry.id) + 1, "https://example.com/b".to_string()),
+                (
+                    u32::from(entry.id) + 4,
+                    "https://example.com/c?ref=sport".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn merged_entries_stay_in_groups() {
+        let db = client().await;
+        let (kept, kept_embedding) =
+            insert_story(&db, "https://example.com/a", "Titel", "Text", vec![1.0]).await;
+        let (merged, merged_embedding) =
+            insert_story(&db, "https://example.com/b", "Titel", "Ny text", vec![1.0]).await;
+        // entry stored before hrefs were canonical
+        sqlx::query("UPDATE entries SET href = 'http://example.com/a/' WHERE id = ?")
+            .bind(merged.id)
+            .execute(&db.pool)
+            .await
+            .expect("failed to update entry");
+        let report = insert_report(&db, chrono::Utc::now()).await;
+        let group_id: u32 = sqlx::query_scalar(
+            "INSERT INTO report_groups (report_id, center_embedding_id) VALUES (?, ?) RETURNING id",
+        )
+        .bind(report.id)
+        .bind(merged_embedding.id)
+        .fetch_one(&db.pool)
+        .await
+        .expect("failed to insert group");
+        sqlx::query(
+            "INSERT INTO report_group_embeddings (report_group_id, embedding_id) VALUES (?, ?)",
+        )
+        .bind(group_id)
+        .bind(merged_embedding.id)
+        .execute(&db.pool)
+        .await
+        .expect("failed to insert group embedding");
+        db.increment_entry_clicks(merged.id, chrono::Utc::now().date_naive())
+            .await
+            .expect("failed to increment clicks");
+        let (duplicate, _) =
+            insert_story(&db, "https://example.com/c", "Titel", "Kopia", vec![1.0]).await;
+        for id in [kept.id, duplicate.id] {
+            db.update_entry_simhash(id, 0, Some(merged.id))
+                .await
+                .expect("failed to update simhash");
+        }
+
+        sqlx::query("INSERT INTO entries_to_canonicalize (entry_id) VALUES (?)")
+            .bind(merged.id)
+            .execute(&db.pool)
+            .await
+            .expect("failed to queue entry");
+        canonicalize_entry_hrefs(&db.pool)
+            .await
+            .expect("failed to canonicalize hrefs");
+
+        let (center_embedding_id, embedding_ids): (u32, String) = sqlx::query_as(
+            "SELECT center_embedding_id, GROUP_CONCAT(embedding_id) FROM report_groups JOIN report_group_embeddings ON report_group_id = id WHERE id = ?",
+        )
+        .bind(group_id)
+        .fetch_one(&db.pool)
+        .await
+        .expect("failed to select group");
+        assert_eq!(center_embedding_id, u32::from(kept_embedding.id));
+        assert_eq!(embedding_ids, u32::from(kept_embedding.id).to_string());
+        let clicks: Vec<(u32, u32)> = sqlx::query_as("SELECT entry_id, count FROM entry_clicks")
+            .fetch_all(&db.pool)
+            .await
+            .expect("failed to select clicks");
+        assert_eq!(clicks, vec![(u32::from(kept.id), 1)]);
+        let duplicates: Vec<(u32, Option<u32>)> =
+            sqlx::query_as("SELECT id, duplicate_of FROM entries ORDER BY id")
+                .fetch_all(&db.pool)
+                .await
+                .expect("failed to select entries");
+        assert_eq!(
+            duplicates,
+            vec![
+                (u32::from(kept.id), None),
+                (u32::from(duplicate.id), Some(u32::from(kept.id))),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn robots_txts() {
+        let db = client().await;
+        let origin = "https://example.com";
+        assert!(db
+            .find_robots_txt_by_origin(origin)
+            .await
+            .expect("failed to find robots.txt")
+            .is_none());
+        for body in ["", "User-agent: *\nDisallow: /"] {
+            db.upsert_robots_txt(&feeds::http::RobotsTxt {
+                origin: origin.to_string(),
+                body: body.to_string(),
+                fetched_at: chrono::Utc::now(),
+            })
+            .await
+            .expect("failed to upsert robots.txt");
+        }
+        let robots_txt = db
+            .find_robots_txt_by_origin(origin)
+            .await
+            .expect("failed to find robots.txt")
+            .expect("robots.txt exists");
+        assert_eq!(robots_txt.value.body, "User-agent: *\nDisallow: /");
+    }
+
+    #[tokio::test]
+    async fn entry_image_hrefs() {
+        let db = client().await;
+        let entry = db
+            .insert_entry(&feeds::Entry {
+                feed_id: Id::from(1),
+                href: "https://example.com/1".parse().expect("valid url"),
+                published_at: chrono::Utc::now(),
+                image_href: Some("https://example.com/1.jpg".parse().expect("valid url")),
+                region: None,
+            })
+            .await
+            .expect("failed to insert entry")
+            .expect("entry is new");
+        assert_eq!(
+            entry.value.image_href.map(|href| href.to_string()),
+            Some("https://example.com/1.jpg".to_string())
+        );
+
+        db.update_entry_image_href(
+            entry.id,
+            &"https://example.com/og.jpg".parse().expect("valid url"),
+        )
+        .await
+        .expect("failed to update image href");
+        let image_href: Option<String> =
+            sqlx::query_scalar("SELECT image_href FROM entries WHERE id = ?")
+                .bind(entry.id)
+                .fetch_one(&db.pool)
+                .await
+                .expect("failed to find entry");
+        assert_eq!(image_href.as_deref(), Some("https://example.com/og.jpg"));
+    }
+
+    #[tokio::test]
+    async fn entry_paywalled() {
+        let db = client().await;
+        let entry = db
+            .insert_entry(&feeds::Entry {
+                feed_id: Id::from(3),
+                href: "https://example.com/1".parse().expect("valid url"),
+                published_at: chrono::Utc::now(),
+                image_href: None,
+                region: None,
+            })
+            .await
+            .expect("failed to insert entry")
+            .expect("entry is new");
+        let paywalled = || async {
+            sqlx::query_scalar::<_, bool>("SELECT paywalled FROM entries WHERE id = ?")
+                .bind(entry.id)
+                .fetch_one(&db.pool)
+                .await
+                .expect("failed to find entry")
+        };
+        assert!(!paywalled().await);
+
+        db.update_entry_paywalled(entry.id, true)
+            .await
+            .expect("failed to update paywalled");
+        assert!(paywalled().await);
+    }
+
+    #[tokio::test]
+    async fn entry_duplicates() {
+        let db = client().await;
+        let (original, _) = insert_story(
+            &db,
+            "https://example.com/1",
+            "Original",
+            "Ett",
+            vec![1.0, 0.0],
+        )
+        .await;
+        let (copy, _) =
+            insert_story(&db, "https://example.com/2", "Kopia", "Två", vec![1.0, 0.0]).await;
+        let (unfingerprinted, _) =
+            insert_story(&db, "https://example.com/3", "Annan", "Tre", vec![0.0, 1.0]).await;
+        db.update_entry_simhash(original.id, u64::MAX, None)
+            .await
+            .expect("failed to update simhash");
+        db.update_entry_simhash(copy.id, u64::MAX - 1, Some(original.id))
+            .await
+            .expect("failed to update simhash");
+
+        let now = chrono::Utc::now();
+        let originals = db
+            .list_original_entry_simhashes_by_published_at(
+                &(now - chrono::Duration::hours(1)..now + chrono::Duration::hours(1)),
+            )
+            .await
+            .expect("failed to list simhashes");
+        assert_eq!(originals, vec![(original.id, Id::from(1), u64::MAX)]);
+        assert!(!originals.iter().any(|(id, _, _)| *id == unfingerprinted.id));
+
+        let originals = db
+            .list_original_entry_simhashes_by_published_at(
+                &(now - chrono::Duration::hours(2)..now - chrono::Duration::hours(1)),
+            )
+            .await
+            .expect("failed to list simhashes");
+        assert!(originals.is_empty());
+    }
+
+    #[tokio::test]
+    async fn week_groups() {
+        let db = client().await;
+        let (_, first) = insert_story(
+            &db,
+            "https://example.com/1",
+            "Första",
+            "Ett",
+            vec![1.0, 0.0],
+        )
+        .await;
+        let (_, second) =
+            insert_story(&db, "https://example.com/2", "Andra", "Två", vec![0.0, 1.0]).await;
+        let report = insert_report(&db, chrono::Utc::now()).await;
+        let group = db
+            .insert_report_group(ReportGroup {
+                report_id: report.id,
+                embedding_ids: vec![first.id, second.id],
+                center_embedding_id: first.id,
+            })
+            .await
+            .expect("failed to insert group");
+
+        let today = chrono::Utc::now().date_naive();
+        let groups = db
+            .list_report_groups_by_dates_lang_code(
+                today - chrono::Duration::days(6),
+                today,
+                &feeds::LanguageCode::SV,
+            )
+            .await
+            .expect("failed to list groups");
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].group_id, group.id);
+        assert_eq!(groups[0].date, today);
+        assert_eq!(groups[0].size, 2);
+        assert_eq!(groups[0].title, "Första");
+
+        let embeddings = db
+            .list_report_group_embeddings_by_dates(today, today)
+            .await
+            .expect("failed to list embeddings");
+        assert_eq!(embeddings.len(), 2);
+        assert!(embeddings.iter().all(|(group_id, _)| *group_id == group.id));
+    }
+
+    #[tokio::test]
+    async fn orphans_are_deleted() {
+        let db = client().await;
+        let (_, embedding) = insert_story(
+            &db,
+            "https://example.com/1",
+            "Rubrik",
+            "Text",
+            vec![1.0, 0.0],
+        )
+        .await;
+
+        let orphan = "Föräldralös";
+        db.insert_translation(feeds::Translation {
+            md5_hash: crate::md5_hash::compute(orphan),
+            value: orphan.to_string(),
+        })
+        .await
+        .expect("failed to insert translation");
+        db.insert_embeddig(
+            &Embedding {
+                md5_hash: crate::md5_hash::compute(orphan),
+                value: vec![0.0, 1.0],
+                size: 2,
+                model: "test".to_string(),
+            },
+            quantization::Precision::F32,
+        )
+        .await
+        .expect("failed to insert embedding");
+        db.insert_group_summary(
+            &crate::md5_hash::compute(orphan),
+            &feeds::LanguageCode::SV,
+            orphan,
+        )
+        .await
+        .expect("failed to insert summary");
+
+        assert_eq!(
+            db.delete_orphaned_translations()
+                .await
+                .expect("failed to delete translations"),
+            1
+        );
+        assert_eq!(
+            db.delete_orphaned_embeddings()
+                .await
+                .expect("failed to delete embeddings"),
+            1
+        );
+        assert_eq!(
+            db.delete_orphaned_group_summaries()
+                .await
+                .expect("failed to delete summaries"),
+            1
+        );
+        db.optimize().await.expect("failed to optimize");
+
+        db.find_embedding_by_id(&embedding.id)
+            .await
+            .expect("referenced embedding is kept");
+        db.find_translation_by_md5_hash(&crate::md5_hash::compute("Rubrik"))
+            .await
+            .expect("referenced translation is kept");
+    }
+
+    #[tokio::test]
+    async fn search() {
+        let db = client().await;
+        let (_, embedding) = insert_story(
+            &db,
+            "https://example.com/1",
+            "Regeringen presenterar budgeten",
+            "Finansministern har presenterat budgeten",
+            vec![1.0, 0.0],
+        )
+        .await;
+        insert_story(
+            &db,
+            "https://example.com/2",
+            "Snöstorm i norr",
+            "Kraftigt snöfall väntas",
+            vec![0.0, 1.0],
+        )
+        .await;
+
+        let results = db
+            .list_search_results_by_keywords_lang_code(&["budgeten"], &feeds::LanguageCode::SV, 10)
+            .await
+            .expect("failed to search");
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].title, "Regeringen presenterar budgeten");
+        assert!(results[0]
+            .snippet
+            .as_deref()
+            .is_some_and(|snippet| snippet.contains(web::SNIPPET_MATCH_START)));
+
+        let results = db
+            .list_search_results_by_keywords_lang_code(
+                &["\"budgeten"],
+                &feeds::LanguageCode::SV,
+                10,
+            )
+            .await
+            .expect("quotes are escaped");
+        assert_eq!(results.len(), 1);
+
+        let results = db
+            .list_search_results_by_embedding_id_lang_code(embedding.id, &feeds::LanguageCode::SV)
+            .await
+            .expect("failed to list search results");
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].group_id, None);
+    }
+
+    #[tokio::test]
+    async fn job_runs() {
+        let db = client().await;
+        for name in ["fetch", "maintain"] {
+            db.insert_job_run(&background::JobRun {
+                name: name.to_string(),
+                started_at: chrono::Utc::now(),
+                finished_at: chrono::Utc::now(),
+                crawl_ms: Some(100),
+                embed_ms: None,
+                report_ms: None,
+                entries_count: Some(3),
+                embeddings_count: None,
+                groups_count: None,
+                error: Some("failed".to_string()),
+            })
+            .await
+            .expect("failed to insert job run");
+        }
+
+        let runs = db.list_job_runs(1).await.expect("failed to list job runs");
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].value.name, "maintain");
+        assert_eq!(runs[0].value.crawl_ms, Some(100));
+        assert_eq!(runs[0].value.embed_ms, None);
+        assert_eq!(runs[0].value.error.as_deref(), Some("failed"));
+    }
+
+    #[tokio::test]
+    async fn filter_hits() {
+        let db = client().await;
+        let keywords = filters::Keywords::new(&["horoskop".to_string()], &[]);
+        let entry: feeds::CrawledEntry = (
+            feeds::Entry {
+                feed_id: Id::from(4),
+                href: "https://example.com/1".parse().expect("valid url"),
+                published_at: chrono::Utc::now(),
+                image_href: None,
+                region: None,
+            },
+            vec![(
+                feeds::FieldName::Title,
+                feeds::LanguageCode::SV,
+                "Dagens Horoskop".to_string(),
+            )],
+        );
+        let keyword = keywords
+            .blocked_keyword(&entry)
+            .expect("keyword is blocked");
+        let hit = filters::Hit {
+            feed_id: entry.0.feed_id,
+            href: entry.0.href.clone(),
+            keyword: keyword.to_string(),
+            title: "Dagens Horoskop".to_string(),
+        };
+        // entries are skipped on every crawl
+        for _ in 0..2 {
+            db.insert_filter_hits(std::slice::from_ref(&hit))
+                .await
+                .expect("failed to insert filter hits");
+        }
+
+        let hits = db
+            .list_filter_hits(10)
+            .await
+            .expect("failed to list filter hits");
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].value.keyword, "horoskop");
+        assert_eq!(hits[0].value.href.to_string(), "https://example.com/1");
+
+        let allowing = filters::Keywords::new(&["horoskop".to_string()], &["dagens".to_string()]);
+        assert_eq!(allowing.blocked_keyword(&entry), None);
+    }
+
+    #[tokio::test]
+    async fn briefings() {
+        let db = client().await;
+        let date = chrono::NaiveDate::from_ymd_opt(2024, 3, 1).expect("valid date");
+        assert_eq!(
+            db.find_briefing_created_at_by_date(date)
+                .await
+                .expect("failed to find briefing"),
+            None
+        );
+
+        db.upsert_briefing(date, b"first")
+            .await
+            .expect("failed to insert briefing");
+        db.upsert_briefing(date, b"second")
+            .await
+            .expect("failed to update briefing");
+        assert!(db
+            .find_briefing_created_at_by_date(date)
+            .await
+            .expect("failed to find briefing")
+            .is_some());
+        assert_eq!(
+            db.find_briefing_audio_by_date(date)
+                .await
+                .expect("failed to find briefing")
+                .as_deref(),
+            Some(b"second".as_slice())
+        );
+        assert_eq!(
+            db.find_briefing_audio_by_date(date.succ_opt().expect("valid date"))
+                .await
+                .expect("failed to find briefing"),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn entry_clicks() {
+        let db = client().await;
+        let (first, _) = insert_story(
+            &db,
+            "https://example.com/1",
+            "Första",
+            "Ett",
+            vec![1.0, 0.0],
+        )
+        .await;
+        let (second, _) =
+            insert_story(&db, "https://example.com/2", "Andra", "Två", vec![0.0, 1.0]).await;
+        let yesterday = chrono::NaiveDate::from_ymd_opt(2024, 3, 1).expect("valid date");
+        let today = yesterday.succ_opt().expect("valid date");
+
+        for (entry_id, date) in [
+            (first.id, yesterday),
+            (first.id, today),
+            (first.id, today),
+            (second.id, yesterday),
+            (second.id, yesterday),
+            (second.id, yesterday),
+        ] {
+            db.increment_entry_clicks(entry_id, date)
+                .await
+                .expect("failed to increment clicks");
+        }
+
+        let popular = db
+            .list_popular_entries_since_date_lang_code(yesterday, &feeds::LanguageCode::SV, 10)
+            .await
+            .expect("failed to list popular entries");
+        assert_eq!(
+            popular
+                .iter()
+                .map(|entry| (entry.entry_id, entry.title.as_str(), entry.clicks))
+                .collect::<Vec<_>>(),
+            vec![(second.id, "Andra", 3), (first.id, "Första", 3)]
+        );
+        let popular = db
+            .list_popular_entries_since_date_lang_code(today, &feeds::LanguageCode::SV, 10)
+            .await
+            .expect("failed to list popular entries");
+        assert_eq!(
+            popular
+                .iter()
+                .map(|entry| (entry.entry_id, entry.title.as_str(), entry.clicks))
+                .collect::<Vec<_>>(),
+            vec![(first.id, "Första", 2)]
+        );
+    }
+
+    #[tokio::test]
+    async fn push_subscriptions() {
+        let db = client().await;
+        let mut subscription = push::Subscription {
+            endpoint: "https://push.example.com/1".to_string(),
+            keys: push::Keys {
+                p256dh: "key".to_string(),
+                auth: "secret".to_string(),
+            },
+        };
+        assert!(db
+            .upsert_push_subscription(&subscription, 1)
+            .await
+            .expect("failed to insert subscription"));
+        // browsers send subscriptions again with new keys
+        subscription.keys.auth = "new secret".to_string();
+        assert!(db
+            .upsert_push_subscription(&subscription, 1)
+            .await
+            .expect("failed to update subscription"));
+        let other = push::Subscription {
+            endpoint: "https://push.example.com/2".to_string(),
+            ..subscription.clone()
+        };
+        assert!(!db
+            .upsert_push_subscription(&other, 1)
+            .await
+            .expect("failed to insert subscription"));
+        assert_eq!(
+            db.list_push_subscriptions()
+                .await
+                .expect("failed to list subscriptions"),
+            vec![subscription.clone()]
+        );
+
+        db.delete_push_subscription(&subscription.endpoint)
+            .await
+            .expect("failed to delete subscription");
+        assert!(db
+            .list_push_subscriptions()
+            .await
+            .expect("failed to list subscriptions")
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn tasks() {
+        let db = client().await;
+        let input = |value: &str| Some(value.to_string());
+        db.insert_tasks(
+            tasks::Kind::Embed,
+            &[(1, input("a")), (2, input("b")), (3, input("c"))],
+        )
+        .await
+        .expect("failed to insert tasks");
+        db.insert_tasks(tasks::Kind::Translate, &[(1, None)])
+            .await
+            .expect("failed to insert tasks");
+
+        db.complete_task(tasks::Kind::Embed, 1)
+            .await
+            .expect("failed to complete task");
+        db.fail_task(tasks::Kind::Embed, 2, "timeout", 2)
+            .await
+            .expect("failed to fail task");
+        let pending = db
+            .list_pending_tasks(tasks::Kind::Embed)
+            .await
+            .expect("failed to list pending tasks");
+        assert_eq!(
+            pending
+                .iter()
+                .map(|task| task.target_id)
+                .collect::<Vec<_>>(),
+            vec![2, 3]
+        );
+        assert_eq!(pending[0].attempts, 1);
+        assert_eq!(pending[0].error.as_deref(), Some("timeout"));
+
+        db.fail_task(tasks::Kind::Embed, 2, "timeout", 2)
+            .await
+            .expect("failed to fail task");
+        let failed = db
+            .list_failed_tasks(10)
+            .await
+            .expect("failed to list failed tasks");
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].status, tasks::Status::Failed);
+        assert_eq!(failed[0].attempts, 2);
+
+        // done tasks are queued again only when their inputs change, failed ones are not
+        db.insert_tasks(
+            tasks::Kind::Embed,
+            &[(1, input("a")), (2, input("b")), (3, input("c"))],
+        )
+        .await
+        .expect("failed to insert tasks");
+        let pending = db
+            .list_pending_tasks(tasks::Kind::Embed)
+            .await
+            .expect("failed to list pending tasks");
+        assert_eq!(
+            pending
+                .iter()
+                .map(|task| task.target_id)
+                .collect::<Vec<_>>(),
+            vec![3]
+        );
+
+        db.insert_tasks(
+            tasks::Kind::Embed,
+            &[(1, input("changed")), (2, input("changed"))],
+        )
+        .await
+        .expect("failed to insert tasks");
+        let pending = db
+            .list_pending_tasks(tasks::Kind::Embed)
+            .await
+            .expect("failed to list pending tasks");
+        assert_eq!(
+            pending
+                .iter()
+                .map(|task| (task.target_id, task.attempts))
+                .collect::<Vec<_>>(),
+            vec![(1, 0), (3, 0)]
+        );
+
+        let counts = db
+            .count_tasks_by_kind_status()
+            .await
+            .expect("failed to count tasks");
+        assert_eq!(
+            counts,
+            vec![
+                (tasks::Kind::Embed, tasks::Status::Failed, 1),
+                (tasks::Kind::Embed, tasks::Status::Pending, 2),
+                (tasks::Kind::Translate, tasks::Status::Pending, 1),
+            ]
+        );
+
+        let retried = db
+            .retry_failed_tasks()
+            .await
+            .expect("failed to retry tasks");
+        assert_eq!(retried, 1);
+        let pending = db
+            .list_pending_tasks(tasks::Kind::Embed)
+            .await
+            .expect("failed to list pending tasks");
+        assert_eq!(pending.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn leases() {
+        let db = client().await;
+        let later = chrono::Utc::now() + chrono::Duration::hours(1);
+        let acquire = |holder, expires_at| {
+            let db = db.clone();
+            async move {
+                db.acquire_lease("fetch", holder, expires_at)
+                    .await
+                    .expect("failed to acquire lease")
+            }
+        };
+
+        assert!(acquire("a", later).await);
+        assert!(!acquire("b", later).await);
+        assert!(acquire("a", later).await);
+
+        db.release_lease("fetch", "b")
+            .await
+            .expect("failed to release lease");
+        assert!(!acquire("b", later).await);
+        db.release_lease("fetch", "a")
+            .await
+            .expect("failed to release lease");
+        assert!(acquire("b", later).await);
+
+        // expired leases are taken over
+        assert!(acquire("b", chrono::Utc::now() - chrono::Duration::minutes(1)).await);
+        assert!(acquire("a", later).await);
+    }
+
+    #[tokio::test]
+    async fn report_dates() {
+        let db = client().await;
+        let date = |day| chrono::NaiveDate::from_ymd_opt(2024, 3, day).expect("valid date");
+        let mut reports = Vec::new();
+        for (day, hour) in [(1, 12), (3, 12), (3, 13)] {
+            let created_at = date(day)
+                .and_hms_opt(hour, 0, 0)
+                .expect("valid time")
+                .and_utc();
+            reports.push(insert_report(&db, created_at).await);
+        }
+        let (_, embedding) =
+            insert_story(&db, "https://example.com/1", "Rubrik", "Text", vec![1.0]).await;
+        db.insert_report_group(ReportGroup {
+            report_id: reports[2].id,
+            embedding_ids: vec![embedding.id],
+            center_embedding_id: embedding.id,
+        })
+        .await
+        .expect("failed to insert group");
+
+        let before = |day| {
+            let db = db.clone();
+            async move {
+                db.find_report_date_before(date(day))
+                    .await
+                    .expect("failed to find report date")
+            }
+        };
+        assert_eq!(before(1).await, None);
+        assert_eq!(before(2).await, Some(date(1)));
+        assert_eq!(before(3).await, Some(date(1)));
+        assert_eq!(before(10).await, Some(date(3)));
+
+        let after = |day| {
+            let db = db.clone();
+            async move {
+                db.find_report_date_after(date(day))
+                    .await
+                    .expect("failed to find report date")
+            }
+        };
+        assert_eq!(after(1).await, Some(date(3)));
+        assert_eq!(after(2).await, Some(date(3)));
+        assert_eq!(after(3).await, None);
+
+        let days = db
+            .list_report_days(date(2), date(31))
+            .await
+            .expect("failed to list report days");
+        assert_eq!(
+            days.iter()
+                .map(|day| (day.date, day.groups_count))
+                .collect::<Vec<_>>(),
+            vec![(date(3), 1)]
+        );
+    }
+
+    #[tokio::test]
+    async fn group_texts() {
+        let db = client().await;
+        let (_, first) = insert_story(
+            &db,
+            "https://example.com/1",
+            "Första",
+            "Ett",
+            vec![1.0, 0.0],
+        )
+        .await;
+        let (_, second) =
+            insert_story(&db, "https://example.com/2", "Andra", "Två", vec![0.0, 1.0]).await;
+        let report = insert_report(&db, chrono::Utc::now()).await;
+        let mut groups = Vec::new();
+        for embedding_ids in [vec![first.id], vec![first.id, second.id]] {
+            groups.push(
+                db.insert_report_group(ReportGroup {
+                    report_id: report.id,
+                    center_embedding_id: embedding_ids[0],
+                    embedding_ids,
+                })
+                .await
+                .expect("failed to insert group"),
+            );
+        }
+        let md5_hash = crate::md5_hash::compute("Ett\nTvå");
+        for group in &groups {
+            db.insert_group_title(group.id, &feeds::LanguageCode::Lattlast, "Lätt rubrik")
+                .await
+                .expect("failed to insert title");
+        }
+        db.insert_group_summary(&md5_hash, &feeds::LanguageCode::Lattlast, "Lätt text")
+            .await
+            .expect("failed to insert summary");
+        db.update_report_group_summary_md5_hash(groups[1].id, &md5_hash)
+            .await
+            .expect("failed to update summary");
+
+        let today = chrono::Utc::now().date_naive();
+        let texts = db
+            .list_group_texts_by_date_lang_code(today, &feeds::LanguageCode::Lattlast)
+            .await
+            .expect("failed to list group texts");
+        assert_eq!(
+            texts
+                .iter()
+                .map(|text| (text.group_id, text.summary.as_deref()))
+                .collect::<Vec<_>>(),
+            vec![(groups[1].id, Some("Lätt text")), (groups[0].id, None)]
+        );
+        assert!(db
+            .list_group_texts_by_date_lang_code(today, &feeds::LanguageCode::EN)
+            .await
+            .expect("failed to list group texts")
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn feed_fetches() {
+        let db = client().await;
+        let feed_id = Id::from(1);
+        for error in [None, Some("timeout"), Some("timeout")] {
+            db.insert_feed_fetch(&feeds::Fetch {
+                feed_id,
+                duration_ms: 100,
+                entries_count: 1,
+                error: error.map(ToString::to_string),
+            })
+            .await
+            .expect("failed to insert fetch");
+        }
+        assert_eq!(
+            db.count_consecutive_feed_failures(&feed_id)
+                .await
+                .expect("failed to count failures"),
+            2
+        );
+        let statuses = db
+            .list_feed_statuses()
+            .await
+            .expect("failed to list statuses");
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].consecutive_failures, 2);
+        assert_eq!(statuses[0].last_error.as_deref(), Some("timeout"));
+        assert!(statuses[0].last_success_at.is_some());
+
+        let validator = feeds::http::Validator {
+            href: "https://example.com/feed".to_string(),
+            etag: Some("1".to_string()),
+            last_modified: None,
+        };
+        db.upsert_feed_validator(&validator)
+            .await
+            .expect("failed to upsert validator");
+        db.upsert_feed_validator(&feeds::http::Validator {
+            etag: Some("2".to_string()),
+            ..validator.clone()
+        })
+        .await
+        .expect("failed to upsert validator");
+        let found = db
+            .find_feed_validator_by_href(&validator.href)
+            .await
+            .expect("failed to find validator")
+            .expect("validator exists");
+        assert_eq!(found.value.etag.as_deref(), Some("2"));
+
+        for _ in 0..2 {
+            db.insert_openai_usage(&openai::Usage {
+                model: "test".to_string(),
+                prompt_tokens: 1,
+                completion_tokens: 1,
+                cost_usd: 0.25,
+            })
+            .await
+            .expect("failed to insert usage");
+        }
+        let cost = db
+            .sum_openai_cost_by_date(&chrono::Utc::now().date_naive())
+            .await
+            .expect("failed to sum cost");
+        assert!((cost - 0.5).abs() < f64::EPSILON);
     }
 }
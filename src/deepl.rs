@@ -0,0 +1,154 @@
+use reqwest_middleware::{ClientBuilder, ClientWithMiddleware};
+
+use crate::{feeds, providers, retry};
+
+static MAX_TEXTS: usize = 50;
+
+#[derive(Clone)]
+pub struct Client {
+    base_url: url::Url,
+    inner: ClientWithMiddleware,
+}
+
+#[derive(Debug, serde::Deserialize, thiserror::Error)]
+#[error("{message}")]
+pub struct ErrorResponse {
+    message: String,
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(untagged)]
+enum Response<T> {
+    Ok(T),
+    Error(ErrorResponse),
+}
+
+impl Client {
+    pub fn new(base_url: &url::Url, token: &str) -> Self {
+        let http_client = {
+            let mut headers = reqwest::header::HeaderMap::new();
+            headers.insert(
+                reqwest::header::AUTHORIZATION,
+                reqwest::header::HeaderValue::from_str(&format!("DeepL-Auth-Key {token}"))
+                    .expect("invalid authorization header value"),
+            );
+            let client = reqwest::ClientBuilder::new()
+                .default_headers(headers)
+                .build()
+                .expect("failed to build reqwest client");
+            ClientBuilder::new(client)
+                // deepl responds with 429 when requests are sent too often
+                .with(retry::Middleware::new(
+                    vec![
+                        reqwest::StatusCode::TOO_MANY_REQUESTS,
+                        reqwest::StatusCode::INTERNAL_SERVER_ERROR,
+                        reqwest::StatusCode::SERVICE_UNAVAILABLE,
+                    ],
+                    5,
+                ))
+                .build()
+        };
+        Self {
+            base_url: base_url.clone(),
+            inner: http_client,
+        }
+    }
+
+    pub async fn translate_texts(
+        &self,
+        texts: &[String],
+        source_lang: &str,
+        target_lang: &str,
+    ) -> Result<Vec<String>, Box<dyn std::error::Error + 'static + Send + Sync>> {
+        #[derive(Debug, serde::Deserialize)]
+        struct Translation {
+            text: String,
+        }
+
+        #[derive(Debug, serde::Deserialize)]
+        struct TranslateResponse {
+            translations: Vec<Translation>,
+        }
+
+        let endpoint = self
+            .base_url
+            .join("/v2/translate")
+            .expect("invalid translate endpoint");
+        let body = serde_json::json!({
+            "text": texts,
+            "source_lang": source_lang,
+            "target_lang": target_lang,
+        });
+
+        let response = self
+            .inner
+            .post(endpoint)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(serde_json::to_string(&body)?)
+            .send()
+            .await?;
+
+        let status = response.status();
+        let response_bytes = response.bytes().await?;
+
+        match serde_json::from_slice::<Response<TranslateResponse>>(&response_bytes) {
+            Ok(Response::Ok(response)) if response.translations.len() == texts.len() => {
+                Ok(response
+                    .translations
+                    .into_iter()
+                    .map(|translation| translation.text)
+                    .collect())
+            }
+            Ok(Response::Ok(_)) => Err("deepl returned unexpected number of translations".into()),
+            Ok(Response::Error(error)) => Err(error.into()),
+            // quota errors (456) come with an empty body
+            Err(_) if !status.is_success() => Err(format!("deepl responded with {status}").into()),
+            Err(error) => Err(error.into()),
+        }
+    }
+}
+
+fn source_lang(lang_code: &feeds::LanguageCode) -> String {
+    lang_code.to_string().to_uppercase()
+}
+
+/// english needs a variant as a target language
+fn target_lang(lang_code: &feeds::LanguageCode) -> String {
+    match lang_code {
+        feeds::LanguageCode::EN => "EN-US".to_string(),
+        lang_code => source_lang(lang_code),
+    }
+}
+
+#[async_trait::async_trait]
+impl providers::Translator for Client {
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn translate(
+        &self,
+        from: &feeds::LanguageCode,
+        to: &feeds::LanguageCode,
+        value: &str,
+    ) -> Result<String, Box<dyn std::error::Error + 'static + Send + Sync>> {
+        let mut translations = self
+            .translate_texts(&[value.to_string()], &source_lang(from), &target_lang(to))
+            .await?;
+        Ok(translations.swap_remove(0))
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn translate_batch(
+        &self,
+        from: &feeds::LanguageCode,
+        to: &feeds::LanguageCode,
+        values: &[String],
+    ) -> Result<Vec<String>, Box<dyn std::error::Error + 'static + Send + Sync>> {
+        let mut translations = Vec::with_capacity(values.len());
+        for chunk in values.chunks(MAX_TEXTS) {
+            translations.extend(
+                self.translate_texts(chunk, &source_lang(from), &target_lang(to))
+                    .await?,
+            );
+        }
+        Ok(translations)
+    }
+}
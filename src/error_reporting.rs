@@ -0,0 +1,37 @@
+use sentry::integrations::tracing::EventFilter;
+use tracing_subscriber::{registry::LookupSpan, Layer as _};
+
+const TARGET: &str = "sverige_news";
+
+/// http clients the reports are sent with, their errors would report themselves
+const QUIET_TARGETS: [&str; 3] = ["reqwest", "hyper", "h2"];
+
+pub type Dsn = sentry::types::Dsn;
+
+pub fn init(dsn: &Dsn) -> sentry::ClientInitGuard {
+    sentry::init(sentry::ClientOptions {
+        dsn: Some(dsn.clone()),
+        release: sentry::release_name!(),
+        shutdown_timeout: std::time::Duration::from_secs(2),
+        ..Default::default()
+    })
+}
+
+pub fn layer<S>() -> impl tracing_subscriber::Layer<S>
+where
+    S: tracing::Subscriber + for<'a> LookupSpan<'a>,
+{
+    let targets = QUIET_TARGETS.iter().fold(
+        tracing_subscriber::filter::Targets::new()
+            .with_target(TARGET, tracing::Level::DEBUG)
+            .with_default(tracing::Level::ERROR),
+        |targets, target| targets.with_target(*target, tracing::level_filters::LevelFilter::OFF),
+    );
+    sentry::integrations::tracing::layer()
+        .event_filter(|metadata| match *metadata.level() {
+            tracing::Level::ERROR => EventFilter::Event,
+            _ => EventFilter::Ignore,
+        })
+        .span_filter(|metadata| metadata.target().starts_with(TARGET))
+        .with_filter(targets)
+}
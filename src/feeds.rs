@@ -1,19 +1,49 @@
 pub mod abc;
 pub mod aftonbladet;
+pub mod bonnier;
 pub mod dagen;
 pub mod dn;
 pub mod expressen;
+pub mod hbl;
+pub mod http;
 pub mod nkpg;
+pub mod ntm;
+pub mod nyheteridag;
+pub mod robots;
 pub mod scaraborgs;
 pub mod svd;
 pub mod svt;
+pub mod svt_lokalt;
+pub mod thelocal;
+pub mod tt;
 pub mod tv4;
 
 use crate::{id::Id, md5_hash::Md5Hash, persisted::Persisted, url::Url};
+use select::predicate::{Attr, Name, Predicate};
 
 #[derive(Debug, Clone, sqlx::FromRow)]
 pub struct Feed {
     pub title: String,
+    pub wire_service: bool,
+    /// articles are behind a paywall, unless their pages say otherwise
+    pub paywalled: bool,
+    pub country: Country,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Country {
+    SE,
+    FI,
+}
+
+impl Country {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::SE => "se",
+            Self::FI => "fi",
+        }
+    }
 }
 
 #[derive(Debug, Clone, sqlx::FromRow)]
@@ -21,9 +51,72 @@ pub struct Entry {
     pub feed_id: Id<Feed>,
     pub href: Url,
     pub published_at: chrono::DateTime<chrono::Utc>,
+    #[sqlx(default)]
+    pub image_href: Option<Url>,
+    #[sqlx(default)]
+    pub region: Option<String>,
+}
+
+pub fn image_href(entry: &feed_rs::model::Entry) -> Option<Url> {
+    let thumbnails = entry
+        .media
+        .iter()
+        .flat_map(|media| &media.thumbnails)
+        .filter_map(|thumbnail| thumbnail.image.uri.parse().ok());
+    let contents = entry
+        .media
+        .iter()
+        .flat_map(|media| &media.content)
+        .filter(|content| {
+            content
+                .content_type
+                .as_ref()
+                .map_or(true, |content_type| content_type.type_() == "image")
+        })
+        .filter_map(|content| content.url.clone().map(Url::from));
+    thumbnails.chain(contents).next()
+}
+
+pub fn is_paywalled(html: &str) -> Option<bool> {
+    let document = select::document::Document::from(html);
+    let content_tier = document
+        .find(Name("meta").and(Attr("property", "article:content_tier")))
+        .find_map(|node| node.attr("content"));
+    match content_tier.map(str::to_lowercase).as_deref() {
+        Some("locked" | "metered") => return Some(true),
+        Some("free") => return Some(false),
+        _ => {}
+    }
+    // json-ld, for example `"isAccessibleForFree": "False"`
+    let compact = html
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>()
+        .to_lowercase();
+    if compact.contains(r#""isaccessibleforfree":false"#)
+        || compact.contains(r#""isaccessibleforfree":"false""#)
+    {
+        Some(true)
+    } else if compact.contains(r#""isaccessibleforfree":true"#)
+        || compact.contains(r#""isaccessibleforfree":"true""#)
+    {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+pub fn og_image_href(html: &str, base: &url::Url) -> Option<Url> {
+    select::document::Document::from(html)
+        .find(Name("meta").and(Attr("property", "og:image")))
+        .find_map(|node| node.attr("content"))
+        .and_then(|href| base.join(href.trim()).ok())
+        .map(Url::from)
 }
 
-#[derive(Debug, Clone)]
+pub type CrawledEntry = (Entry, Vec<(FieldName, LanguageCode, String)>);
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum FieldName {
     Title,
     Description,
@@ -84,10 +177,39 @@ impl std::fmt::Display for FieldName {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum LanguageCode {
     EN,
     SV,
+    UK,
+    AR,
+    FI,
+    /// easy swedish, lättläst, for language learners
+    Lattlast,
+}
+
+impl LanguageCode {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::EN => "English",
+            Self::SV => "Swedish",
+            Self::UK => "Ukrainian",
+            Self::AR => "Arabic",
+            Self::FI => "Finnish",
+            Self::Lattlast => "Easy Swedish",
+        }
+    }
+
+    pub fn native_name(&self) -> &'static str {
+        match self {
+            Self::EN => "English",
+            Self::SV => "Svenska",
+            Self::UK => "Українська",
+            Self::AR => "العربية",
+            Self::FI => "Suomi",
+            Self::Lattlast => "Lättläst",
+        }
+    }
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -128,6 +250,10 @@ impl std::str::FromStr for LanguageCode {
         match s {
             "en" => Ok(Self::EN),
             "sv" => Ok(Self::SV),
+            "uk" => Ok(Self::UK),
+            "ar" => Ok(Self::AR),
+            "fi" => Ok(Self::FI),
+            "sv-x-lattlast" => Ok(Self::Lattlast),
             _ => Err(InvalidLanguageCode(s.to_owned())),
         }
     }
@@ -138,6 +264,10 @@ impl std::fmt::Display for LanguageCode {
         match self {
             Self::EN => write!(f, "en"),
             Self::SV => write!(f, "sv"),
+            Self::UK => write!(f, "uk"),
+            Self::AR => write!(f, "ar"),
+            Self::FI => write!(f, "fi"),
+            Self::Lattlast => write!(f, "sv-x-lattlast"),
         }
     }
 }
@@ -150,6 +280,14 @@ pub struct Field {
     pub md5_hash: Md5Hash,
 }
 
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct Fetch {
+    pub feed_id: Id<Feed>,
+    pub duration_ms: u32,
+    pub entries_count: u32,
+    pub error: Option<String>,
+}
+
 #[derive(Debug, Clone, sqlx::FromRow)]
 pub struct Translation {
     pub md5_hash: Md5Hash,
@@ -157,7 +295,7 @@ pub struct Translation {
 }
 
 pub static LIST: once_cell::sync::Lazy<Vec<Persisted<Feed>>> = once_cell::sync::Lazy::new(|| {
-    vec![
+    let mut list = vec![
         svt::FEED.clone(),
         dn::FEED.clone(),
         expressen::FEED.clone(),
@@ -168,13 +306,13 @@ pub static LIST: once_cell::sync::Lazy<Vec<Persisted<Feed>>> = once_cell::sync::
         dagen::FEED.clone(),
         svd::FEED.clone(),
         aftonbladet::FEED.clone(),
-        // Persisted {
-        //     id: Id::from(8),
-        //     created_at,
-        //     value: Feed {
-        //         title: "Nyheter Idag".to_string(),
-        //         href: "https://nyheteridag.se/feed".parse().expect("valid url"),
-        //     },
-        // },
-    ]
+        nyheteridag::FEED.clone(),
+        tt::FEED.clone(),
+        thelocal::FEED.clone(),
+        hbl::FEED.clone(),
+        svt_lokalt::FEED.clone(),
+    ];
+    list.extend(ntm::FEEDS.iter().cloned());
+    list.extend(bonnier::FEEDS.iter().cloned());
+    list
 });
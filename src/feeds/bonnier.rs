@@ -0,0 +1,130 @@
+use crate::feeds;
+use crate::id::Id;
+use crate::persisted::Persisted;
+
+struct Paper {
+    id: u32,
+    title: &'static str,
+    rss_url: &'static str,
+}
+
+static PAPERS: [Paper; 2] = [
+    Paper {
+        id: 24,
+        title: "GT",
+        rss_url: "https://feeds.expressen.se/gt/",
+    },
+    Paper {
+        id: 25,
+        title: "Kvällsposten",
+        rss_url: "https://feeds.expressen.se/kvallsposten/",
+    },
+];
+
+pub static FEEDS: once_cell::sync::Lazy<Vec<Persisted<feeds::Feed>>> =
+    once_cell::sync::Lazy::new(|| {
+        let created_at = chrono::DateTime::parse_from_rfc3339("2024-03-18T09:00:00+01:00")
+            .expect("valid timestamp")
+            .with_timezone(&chrono::Utc);
+        PAPERS
+            .iter()
+            .map(|paper| Persisted {
+                id: Id::from(paper.id),
+                created_at,
+                value: feeds::Feed {
+                    title: paper.title.to_string(),
+                    wire_service: false,
+                    paywalled: true,
+                    country: feeds::Country::SE,
+                },
+            })
+            .collect()
+    });
+
+/// crawls a single paper, `feed_id` must be one of [`FEEDS`].
+pub async fn crawl(
+    http_client: &feeds::http::Client,
+    feed_id: Id<feeds::Feed>,
+) -> Result<Vec<feeds::CrawledEntry>, Box<dyn std::error::Error + 'static + Send + Sync>> {
+    let paper = PAPERS
+        .iter()
+        .find(|paper| Id::from(paper.id) == feed_id)
+        .expect("unknown bonnier feed");
+    http_client
+        .fetch_entries(feed_id, paper.rss_url, |entry| parse_entry(feed_id, entry))
+        .await
+}
+
+#[derive(Debug, thiserror::Error)]
+enum ParseError {
+    #[error("no description")]
+    NoDescription,
+    #[error("no title")]
+    NoTitle,
+    #[error("no link")]
+    NoLink,
+    #[error("no date")]
+    NoDate,
+}
+
+fn parse_entry(
+    feed_id: Id<feeds::Feed>,
+    entry: &feed_rs::model::Entry,
+) -> Result<feeds::CrawledEntry, ParseError> {
+    let fields = vec![
+        entry
+            .title
+            .as_ref()
+            .map(|t| {
+                (
+                    feeds::FieldName::Title,
+                    feeds::LanguageCode::SV,
+                    t.content.clone(),
+                )
+            })
+            .ok_or(ParseError::NoTitle)?,
+        entry
+            .summary
+            .as_ref()
+            .map(|summary| {
+                (
+                    feeds::FieldName::Description,
+                    feeds::LanguageCode::SV,
+                    html2text(&summary.content),
+                )
+            })
+            .filter(|(_, _, description)| !description.is_empty())
+            .ok_or(ParseError::NoDescription)?,
+    ];
+    let entry = feeds::Entry {
+        feed_id,
+        href: entry
+            .links
+            .first()
+            .map(|link| link.href.as_str())
+            .and_then(|href| href.parse().ok())
+            .ok_or(ParseError::NoLink)?,
+        published_at: entry
+            .published
+            .or(entry.updated)
+            .ok_or(ParseError::NoDate)?,
+        image_href: feeds::image_href(entry),
+        region: None,
+    };
+    Ok((entry, fields))
+}
+
+fn html2text(html: &str) -> String {
+    use select::document::Document;
+    use select::predicate::Name;
+
+    Document::from(html)
+        .find(Name("p"))
+        .map(|node| node.first_child())
+        .filter_map(|node| node.and_then(|node| node.as_text()))
+        .map(ToString::to_string)
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
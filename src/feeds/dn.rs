@@ -15,12 +15,15 @@ pub static FEED: once_cell::sync::Lazy<Persisted<feeds::Feed>> = once_cell::sync
         created_at,
         value: feeds::Feed {
             title: "Dagens Nyheter".to_string(),
+            wire_service: false,
+            paywalled: false,
+            country: feeds::Country::SE,
         },
     }
 });
 
 pub async fn crawl(
-    http_client: &reqwest::Client,
+    http_client: &feeds::http::Client,
 ) -> Result<
     Vec<(
         feeds::Entry,
@@ -28,8 +31,12 @@ pub async fn crawl(
     )>,
     Box<dyn std::error::Error + 'static + Send + Sync>,
 > {
-    let response = http_client.get("https://www.dn.se/direkt/").send().await?;
-    let bytes = response.bytes().await?;
+    let Some(bytes) = http_client
+        .fetch(FEED.id, "https://www.dn.se/direkt/")
+        .await?
+    else {
+        return Ok(vec![]);
+    };
     let body = std::str::from_utf8(&bytes)?;
 
     let doc = Document::from(body);
@@ -115,6 +122,12 @@ fn parse_entry(
             .and_then(|date| chrono::DateTime::parse_from_rfc3339(date).ok())
             .map(|date| date.with_timezone(&chrono::Utc))
             .ok_or(ParseError::NoPublishDate)?,
+        image_href: node
+            .find(Name("img"))
+            .next()
+            .and_then(|node| node.attr("src"))
+            .and_then(|href| href.parse().ok()),
+        region: None,
     };
     Ok((entry, fields))
 }
@@ -11,6 +11,9 @@ pub static FEED: once_cell::sync::Lazy<Persisted<feeds::Feed>> = once_cell::sync
         created_at,
         value: feeds::Feed {
             title: "Expressen".to_string(),
+            wire_service: false,
+            paywalled: false,
+            country: feeds::Country::SE,
         },
     }
 });
@@ -18,7 +21,7 @@ pub static FEED: once_cell::sync::Lazy<Persisted<feeds::Feed>> = once_cell::sync
 static RSS_URL: &str = "https://feeds.expressen.se/nyheter/";
 
 pub async fn crawl(
-    http_client: &reqwest::Client,
+    http_client: &feeds::http::Client,
 ) -> Result<
     Vec<(
         feeds::Entry,
@@ -26,14 +29,13 @@ pub async fn crawl(
     )>,
     Box<dyn std::error::Error + 'static + Send + Sync>,
 > {
-    let response = http_client.get(RSS_URL).send().await?;
-    let bytes = response.bytes().await?;
+    let Some(bytes) = http_client.fetch(FEED.id, RSS_URL).await? else {
+        return Ok(vec![]);
+    };
     let parser = feed_rs::parser::Builder::new()
         .base_uri(Some(RSS_URL))
         .build();
-    let entries = parser
-        .parse(bytes.to_vec().as_slice())
-        .map(|feed| feed.entries)?;
+    let entries = parser.parse(bytes.as_slice()).map(|feed| feed.entries)?;
     let entries = entries
         .iter()
         .filter_map(|e| {
@@ -105,6 +107,8 @@ fn parse_entry(
             .updated
             .or(entry.published)
             .ok_or(ParseError::NoDate)?,
+        image_href: feeds::image_href(entry),
+        region: None,
     };
     Ok((entry, fields))
 }
@@ -0,0 +1,273 @@
+use crate::{db, feeds, feeds::robots, id::Id};
+
+pub const USER_AGENT: &str = "svergie news crawler";
+
+const ROBOTS_TTL: chrono::Duration = chrono::Duration::days(1);
+
+/// http client that makes conditional requests using validators from previous responses,
+/// so that unchanged feeds are not downloaded and parsed again. requests follow robots.txt rules
+/// of publishers, and are spaced out per host.
+pub struct Client {
+    inner: reqwest::Client,
+    db: db::Client,
+    config: Config,
+    validators: std::sync::Mutex<Vec<Validator>>,
+    origins: std::sync::Mutex<
+        std::collections::HashMap<String, std::sync::Arc<tokio::sync::Mutex<Origin>>>,
+    >,
+}
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub crawl_delay: std::time::Duration,
+    pub feed_crawl_delays: Vec<FeedCrawlDelay>,
+    pub ignore_robots_feeds: Vec<Id<feeds::Feed>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FeedCrawlDelay {
+    pub feed_id: Id<feeds::Feed>,
+    pub delay: std::time::Duration,
+}
+
+pub fn parse_feed_crawl_delay(value: &str) -> Result<FeedCrawlDelay, String> {
+    let (feed_id, delay) = value
+        .split_once('=')
+        .ok_or_else(|| format!("expected <feed id>=<milliseconds>, got {value}"))?;
+    let feed_id = parse_feed_id(feed_id)?;
+    let delay = delay.parse::<u64>().map_err(|error| error.to_string())?;
+    Ok(FeedCrawlDelay {
+        feed_id,
+        delay: std::time::Duration::from_millis(delay),
+    })
+}
+
+pub fn parse_feed_id(value: &str) -> Result<Id<feeds::Feed>, String> {
+    let feed_id = Id::from(value.parse::<u32>().map_err(|error| error.to_string())?);
+    if !feeds::LIST.iter().any(|feed| feed.id == feed_id) {
+        return Err(format!("unknown feed id {feed_id}"));
+    }
+    Ok(feed_id)
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct RobotsTxt {
+    pub origin: String,
+    pub body: String,
+    pub fetched_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("{0} is disallowed by robots.txt")]
+pub struct Disallowed(String);
+
+#[derive(Debug, Default)]
+struct Origin {
+    rules: Option<robots::Rules>,
+    next_request_at: Option<tokio::time::Instant>,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct Validator {
+    pub href: String,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+impl Client {
+    pub fn new(inner: reqwest::Client, db: db::Client, config: Config) -> Self {
+        Self {
+            inner,
+            db,
+            config,
+            validators: std::sync::Mutex::new(vec![]),
+            origins: std::sync::Mutex::new(std::collections::HashMap::new()),
+        }
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn fetch(
+        &self,
+        feed_id: Id<feeds::Feed>,
+        href: &str,
+    ) -> Result<Option<Vec<u8>>, Box<dyn std::error::Error + 'static + Send + Sync>> {
+        self.wait_for_turn(feed_id, &href.parse()?).await?;
+
+        let mut request = self.inner.get(href);
+        if let Some(validator) = self.db.find_feed_validator_by_href(href).await? {
+            if let Some(etag) = validator.value.etag {
+                request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = validator.value.last_modified {
+                request = request.header(reqwest::header::IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+
+        let response = request.send().await?;
+        if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+            tracing::debug!(href, "not modified");
+            return Ok(None);
+        }
+        let response = response.error_for_status()?;
+
+        let header = |name| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(ToString::to_string)
+        };
+        let validator = Validator {
+            href: href.to_string(),
+            etag: header(reqwest::header::ETAG),
+            last_modified: header(reqwest::header::LAST_MODIFIED),
+        };
+
+        let bytes = response.bytes().await?;
+
+        if validator.etag.is_some() || validator.last_modified.is_some() {
+            self.validators
+                .lock()
+                .expect("failed to lock validators")
+                .push(validator);
+        }
+
+        Ok(Some(bytes.to_vec()))
+    }
+
+    /// errors name the href, so that feeds crawled from several hrefs can be told apart
+    pub async fn fetch_entries<E: std::fmt::Debug>(
+        &self,
+        feed_id: Id<feeds::Feed>,
+        href: &str,
+        parse_entry: impl Fn(&feed_rs::model::Entry) -> Result<feeds::CrawledEntry, E>,
+    ) -> Result<Vec<feeds::CrawledEntry>, Box<dyn std::error::Error + 'static + Send + Sync>> {
+        let bytes = match self.fetch(feed_id, href).await {
+            Ok(Some(bytes)) => bytes,
+            Ok(None) => return Ok(vec![]),
+            Err(error) => return Err(format!("{href}: {error}").into()),
+        };
+        let parser = feed_rs::parser::Builder::new().base_uri(Some(href)).build();
+        let entries = parser
+            .parse(bytes.as_slice())
+            .map_err(|error| format!("{href}: {error}"))?
+            .entries;
+        Ok(entries
+            .iter()
+            .filter_map(|entry| {
+                parse_entry(entry)
+                    .map_err(|error| tracing::warn!(?error, href, "failed to parse entry"))
+                    .ok()
+            })
+            .collect())
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn get(
+        &self,
+        feed_id: Id<feeds::Feed>,
+        href: &str,
+    ) -> Result<Vec<u8>, Box<dyn std::error::Error + 'static + Send + Sync>> {
+        self.wait_for_turn(feed_id, &href.parse()?).await?;
+        let response = self.inner.get(href).send().await?.error_for_status()?;
+        Ok(response.bytes().await?.to_vec())
+    }
+
+    async fn wait_for_turn(
+        &self,
+        feed_id: Id<feeds::Feed>,
+        url: &url::Url,
+    ) -> Result<(), Box<dyn std::error::Error + 'static + Send + Sync>> {
+        let origin_name = url.origin().ascii_serialization();
+        let origin = self
+            .origins
+            .lock()
+            .expect("failed to lock origins")
+            .entry(origin_name.clone())
+            .or_default()
+            .clone();
+        // held while robots.txt is fetched, so that it is fetched once per origin
+        let mut origin = origin.lock().await;
+
+        let mut delay = self.config.crawl_delay;
+        if !self.config.ignore_robots_feeds.contains(&feed_id) {
+            if origin.rules.is_none() {
+                origin.rules = Some(self.robots(&origin_name).await?);
+            }
+            let rules = origin.rules.as_ref().expect("rules are loaded");
+            let path = match url.query() {
+                Some(query) => format!("{}?{query}", url.path()),
+                None => url.path().to_string(),
+            };
+            if !rules.is_allowed(&path) {
+                return Err(Box::new(Disallowed(url.to_string())));
+            }
+            delay = delay.max(rules.crawl_delay.unwrap_or_default());
+        }
+        if let Some(feed_crawl_delay) = self
+            .config
+            .feed_crawl_delays
+            .iter()
+            .find(|feed_crawl_delay| feed_crawl_delay.feed_id == feed_id)
+        {
+            delay = feed_crawl_delay.delay;
+        }
+
+        let now = tokio::time::Instant::now();
+        let request_at = origin.next_request_at.map_or(now, |at| at.max(now));
+        origin.next_request_at = Some(request_at + delay);
+        drop(origin);
+
+        tokio::time::sleep_until(request_at).await;
+        Ok(())
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn robots(
+        &self,
+        origin: &str,
+    ) -> Result<robots::Rules, Box<dyn std::error::Error + 'static + Send + Sync>> {
+        let now = chrono::Utc::now();
+        let stored = self.db.find_robots_txt_by_origin(origin).await?;
+        let body = match stored {
+            Some(robots_txt) if now - robots_txt.value.fetched_at < ROBOTS_TTL => {
+                robots_txt.value.body
+            }
+            _ => {
+                let response = self
+                    .inner
+                    .get(format!("{origin}/robots.txt"))
+                    .send()
+                    .await?;
+                // missing robots.txt allows everything, while server errors fail the crawl
+                let body = if response.status().is_client_error() {
+                    String::new()
+                } else {
+                    response.error_for_status()?.text().await?
+                };
+                tracing::debug!(origin, "fetched robots.txt");
+                self.db
+                    .upsert_robots_txt(&RobotsTxt {
+                        origin: origin.to_string(),
+                        body,
+                        fetched_at: now,
+                    })
+                    .await?
+                    .value
+                    .body
+            }
+        };
+        Ok(robots::Rules::parse(&body, USER_AGENT))
+    }
+
+    /// persists validators of fetched responses. should be called once the responses are
+    /// processed, otherwise failed entries will not be fetched again until feed changes.
+    pub async fn save_validators(&self) -> Result<(), sqlx::Error> {
+        let validators =
+            std::mem::take(&mut *self.validators.lock().expect("failed to lock validators"));
+        for validator in validators {
+            self.db.upsert_feed_validator(&validator).await?;
+        }
+        Ok(())
+    }
+}
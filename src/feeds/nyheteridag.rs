@@ -0,0 +1,104 @@
+use crate::feeds;
+use crate::id::Id;
+use crate::persisted::Persisted;
+
+pub static FEED: once_cell::sync::Lazy<Persisted<feeds::Feed>> = once_cell::sync::Lazy::new(|| {
+    let created_at = chrono::DateTime::parse_from_rfc3339("2024-02-29T10:01:20+01:00")
+        .expect("valid timestamp")
+        .with_timezone(&chrono::Utc);
+    Persisted {
+        id: Id::from(8),
+        created_at,
+        value: feeds::Feed {
+            title: "Nyheter Idag".to_string(),
+            wire_service: false,
+            paywalled: false,
+            country: feeds::Country::SE,
+        },
+    }
+});
+
+static RSS_URL: &str = "https://nyheteridag.se/feed";
+
+pub async fn crawl(
+    http_client: &feeds::http::Client,
+) -> Result<Vec<feeds::CrawledEntry>, Box<dyn std::error::Error + 'static + Send + Sync>> {
+    http_client
+        .fetch_entries(FEED.id, RSS_URL, parse_entry)
+        .await
+}
+
+#[derive(Debug, thiserror::Error)]
+enum ParseError {
+    #[error("no description")]
+    NoDescription,
+    #[error("no title")]
+    NoTitle,
+    #[error("no link")]
+    NoLink,
+    #[error("no date")]
+    NoDate,
+}
+
+fn parse_entry(entry: &feed_rs::model::Entry) -> Result<feeds::CrawledEntry, ParseError> {
+    let fields = vec![
+        entry
+            .title
+            .as_ref()
+            .map(|t| {
+                (
+                    feeds::FieldName::Title,
+                    feeds::LanguageCode::SV,
+                    t.content.clone(),
+                )
+            })
+            .ok_or(ParseError::NoTitle)?,
+        entry
+            .summary
+            .as_ref()
+            .map(|summary| {
+                (
+                    feeds::FieldName::Description,
+                    feeds::LanguageCode::SV,
+                    html2text(&summary.content),
+                )
+            })
+            .ok_or(ParseError::NoDescription)?,
+    ];
+    let entry = feeds::Entry {
+        feed_id: FEED.id,
+        href: entry
+            .links
+            .first()
+            .map(|link| link.href.as_str())
+            .and_then(|href| href.parse().ok())
+            .ok_or(ParseError::NoLink)?,
+        published_at: entry
+            .updated
+            .or(entry.published)
+            .ok_or(ParseError::NoDate)?,
+        image_href: feeds::image_href(entry),
+        region: None,
+    };
+    Ok((entry, fields))
+}
+
+fn html2text(html: &str) -> String {
+    use select::document::Document;
+    use select::predicate::Name;
+
+    let parts = Document::from(html)
+        .find(Name("p"))
+        .map(|node| node.text())
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        // the last paragraph is always "Inlägget ... dök först upp på Nyheter Idag."
+        .filter(|s| !s.starts_with("Inlägget "))
+        .collect::<Vec<_>>();
+
+    parts
+        .join("\n")
+        .trim_end_matches("[…]")
+        .trim_end()
+        .to_string()
+}
@@ -0,0 +1,200 @@
+#[derive(Debug, Clone, Default)]
+pub struct Rules {
+    patterns: Vec<(String, bool)>,
+    pub crawl_delay: Option<std::time::Duration>,
+}
+
+#[derive(Debug, Default)]
+struct Group {
+    user_agents: Vec<String>,
+    rules: Rules,
+}
+
+impl Rules {
+    pub fn parse(body: &str, user_agent: &str) -> Self {
+        let user_agent = user_agent.to_lowercase();
+        let mut groups: Vec<Group> = vec![];
+        // consecutive user-agent lines share a group
+        let mut in_user_agents = false;
+        for line in body.lines() {
+            let line = line.split('#').next().unwrap_or_default();
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+            match key.trim().to_lowercase().as_str() {
+                "user-agent" => {
+                    if !in_user_agents {
+                        groups.push(Group::default());
+                        in_user_agents = true;
+                    }
+                    if let Some(group) = groups.last_mut() {
+                        group.user_agents.push(value.to_lowercase());
+                    }
+                }
+                key => {
+                    in_user_agents = false;
+                    let Some(group) = groups.last_mut() else {
+                        continue;
+                    };
+                    match key {
+                        "allow" | "disallow" if !value.is_empty() => {
+                            group
+                                .rules
+                                .patterns
+                                .push((value.to_string(), key == "allow"));
+                        }
+                        "crawl-delay" => {
+                            group.rules.crawl_delay =
+                                value.parse::<f64>().ok().and_then(|seconds| {
+                                    std::time::Duration::try_from_secs_f64(seconds).ok()
+                                });
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        let named = |group: &&Group| {
+            group
+                .user_agents
+                .iter()
+                .any(|name| name != "*" && user_agent.contains(name.as_str()))
+        };
+        let any = |group: &&Group| group.user_agents.iter().any(|name| name == "*");
+        let matching = if groups.iter().any(|group| named(&group)) {
+            groups.iter().filter(named).collect::<Vec<_>>()
+        } else {
+            groups.iter().filter(any).collect::<Vec<_>>()
+        };
+        matching
+            .into_iter()
+            .fold(Self::default(), |mut rules, group| {
+                rules.patterns.extend(group.rules.patterns.iter().cloned());
+                rules.crawl_delay = rules.crawl_delay.max(group.rules.crawl_delay);
+                rules
+            })
+    }
+
+    /// the longest matching pattern wins, allow wins between equally long ones. paths without
+    /// matching patterns are allowed.
+    pub fn is_allowed(&self, path: &str) -> bool {
+        self.patterns
+            .iter()
+            .filter(|(pattern, _)| matches(pattern, path))
+            .max_by_key(|(pattern, allow)| (pattern.len(), *allow))
+            .map_or(true, |(_, allow)| *allow)
+    }
+}
+
+/// patterns match path prefixes, `*` matches any sequence of characters, and `$` at the end
+/// matches the end of the path.
+fn matches(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(pattern) => (pattern, true),
+        None => (pattern, false),
+    };
+    let mut pieces = pattern.split('*');
+    let Some(first) = pieces.next() else {
+        return true;
+    };
+    let Some(mut rest) = path.strip_prefix(first) else {
+        return false;
+    };
+    let pieces = pieces.collect::<Vec<_>>();
+    let Some((last, middle)) = pieces.split_last() else {
+        return !anchored || rest.is_empty();
+    };
+    for piece in middle {
+        let Some(position) = rest.find(piece) else {
+            return false;
+        };
+        rest = &rest[position + piece.len()..];
+    }
+    if anchored {
+        rest.ends_with(last)
+    } else {
+        rest.contains(last)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const USER_AGENT: &str = "Sverige-News/1.0 (+https://example.com)";
+
+    #[test]
+    fn named_groups_win_over_any() {
+        let rules = Rules::parse(
+            "User-agent: *\nDisallow: /\n\nUser-agent: sverige-news\nDisallow: /private\n",
+            USER_AGENT,
+        );
+        assert!(rules.is_allowed("/news"));
+        assert!(!rules.is_allowed("/private/1"));
+
+        let rules = Rules::parse(
+            "User-agent: other\nDisallow: /\n\nUser-agent: *\nDisallow: /private\n",
+            USER_AGENT,
+        );
+        assert!(rules.is_allowed("/news"));
+        assert!(!rules.is_allowed("/private"));
+    }
+
+    #[test]
+    fn consecutive_user_agents_share_a_group() {
+        let rules = Rules::parse(
+            "User-agent: other\nUser-agent: Sverige-News\nDisallow: /private\n\nUser-agent: other\nDisallow: /\n",
+            USER_AGENT,
+        );
+        assert!(rules.is_allowed("/news"));
+        assert!(!rules.is_allowed("/private"));
+    }
+
+    #[test]
+    fn longest_match_wins_and_allow_breaks_ties() {
+        let rules = Rules::parse(
+            "User-agent: *\nDisallow: /news\nAllow: /news/public\nDisallow: /a\nAllow: /a\n",
+            USER_AGENT,
+        );
+        assert!(!rules.is_allowed("/news/1"));
+        assert!(rules.is_allowed("/news/public/1"));
+        assert!(rules.is_allowed("/a"));
+    }
+
+    #[test]
+    fn wildcards_and_anchors() {
+        let rules = Rules::parse(
+            "User-agent: *\nDisallow: /*.pdf$\nDisallow: /search*q=\n",
+            USER_AGENT,
+        );
+        assert!(!rules.is_allowed("/files/report.pdf"));
+        assert!(rules.is_allowed("/files/report.pdf?download=1"));
+        assert!(!rules.is_allowed("/search?lang=sv&q=news"));
+        assert!(rules.is_allowed("/search?lang=sv"));
+    }
+
+    #[test]
+    fn empty_disallow_allows_everything() {
+        let rules = Rules::parse("User-agent: *\nDisallow:\n", USER_AGENT);
+        assert!(rules.is_allowed("/"));
+        assert!(Rules::parse("", USER_AGENT).is_allowed("/"));
+    }
+
+    #[test]
+    fn crawl_delays() {
+        let rules = Rules::parse("User-agent: *\nCrawl-delay: 2.5 # seconds\n", USER_AGENT);
+        assert_eq!(
+            rules.crawl_delay,
+            Some(std::time::Duration::from_millis(2500))
+        );
+        for delay in ["-1", "soon", "inf"] {
+            let rules = Rules::parse(
+                &format!("User-agent: *\nCrawl-delay: {delay}\n"),
+                USER_AGENT,
+            );
+            assert_eq!(rules.crawl_delay, None);
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use crate::feeds;
+use crate::id::Id;
+use crate::persisted::Persisted;
+
+pub static FEED: once_cell::sync::Lazy<Persisted<feeds::Feed>> = once_cell::sync::Lazy::new(|| {
+    let created_at = chrono::DateTime::parse_from_rfc3339("2024-03-18T09:00:00+01:00")
+        .expect("valid timestamp")
+        .with_timezone(&chrono::Utc);
+    Persisted {
+        id: Id::from(26),
+        created_at,
+        value: feeds::Feed {
+            title: "SVT Lokalt".to_string(),
+            wire_service: false,
+            paywalled: false,
+            country: feeds::Country::SE,
+        },
+    }
+});
+
+/// crawls local news of the given region, for example `stockholm` or `vast`.
+pub async fn crawl(
+    http_client: &feeds::http::Client,
+    region: &str,
+) -> Result<Vec<feeds::CrawledEntry>, Box<dyn std::error::Error + 'static + Send + Sync>> {
+    let rss_url = format!("https://www.svt.se/nyheter/lokalt/{region}/rss.xml");
+    http_client
+        .fetch_entries(FEED.id, &rss_url, |entry| parse_entry(region, entry))
+        .await
+}
+
+#[derive(Debug, thiserror::Error)]
+enum ParseError {
+    #[error("no content")]
+    NoContent,
+    #[error("no title")]
+    NoTitle,
+    #[error("no link")]
+    NoLink,
+    #[error("no date")]
+    NoDate,
+}
+
+fn parse_entry(
+    region: &str,
+    entry: &feed_rs::model::Entry,
+) -> Result<feeds::CrawledEntry, ParseError> {
+    let fields = vec![
+        entry
+            .title
+            .as_ref()
+            .map(|t| {
+                (
+                    feeds::FieldName::Title,
+                    feeds::LanguageCode::SV,
+                    t.content.clone(),
+                )
+            })
+            .ok_or(ParseError::NoTitle)?,
+        entry
+            .summary
+            .as_ref()
+            .map(|summary| {
+                (
+                    feeds::FieldName::Description,
+                    feeds::LanguageCode::SV,
+                    remove_empty_lines(&summary.content),
+                )
+            })
+            .ok_or(ParseError::NoContent)?,
+    ];
+    let entry = feeds::Entry {
+        feed_id: FEED.id,
+        href: entry
+            .links
+            .first()
+            .map(|link| link.href.as_str())
+            .and_then(|href| href.parse().ok())
+            .ok_or(ParseError::NoLink)?,
+        published_at: entry
+            .updated
+            .or(entry.published)
+            .ok_or(ParseError::NoDate)?,
+        image_href: feeds::image_href(entry),
+        region: Some(region.to_string()),
+    };
+    Ok((entry, fields))
+}
+
+fn remove_empty_lines(s: &str) -> String {
+    s.lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
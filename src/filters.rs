@@ -0,0 +1,53 @@
+use crate::{feeds, id::Id, url::Url};
+
+/// keywords crawled entries are filtered by. keywords match titles and descriptions regardless
+/// of case, anywhere in a word, so that `horoskop` matches both "Dagens horoskop" and
+/// "Horoskopet för veckan".
+#[derive(Debug, Clone, Default)]
+pub struct Keywords {
+    blocked: Vec<String>,
+    /// entries mentioning any of these are kept, even when they mention a blocked keyword
+    allowed: Vec<String>,
+}
+
+impl Keywords {
+    pub fn new(blocked: &[String], allowed: &[String]) -> Self {
+        let lowercase = |keywords: &[String]| {
+            keywords
+                .iter()
+                .map(|keyword| keyword.trim().to_lowercase())
+                .filter(|keyword| !keyword.is_empty())
+                .collect()
+        };
+        Self {
+            blocked: lowercase(blocked),
+            allowed: lowercase(allowed),
+        }
+    }
+
+    pub fn blocked_keyword(&self, (_, fields): &feeds::CrawledEntry) -> Option<&str> {
+        if self.blocked.is_empty() {
+            return None;
+        }
+        let text = fields
+            .iter()
+            .map(|(_, _, value)| value.to_lowercase())
+            .collect::<Vec<_>>()
+            .join("\n");
+        if self.allowed.iter().any(|keyword| text.contains(keyword)) {
+            return None;
+        }
+        self.blocked
+            .iter()
+            .find(|keyword| text.contains(keyword.as_str()))
+            .map(String::as_str)
+    }
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct Hit {
+    pub feed_id: Id<feeds::Feed>,
+    pub href: Url,
+    pub keyword: String,
+    pub title: String,
+}
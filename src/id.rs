@@ -6,6 +6,12 @@ impl<T> From<u32> for Id<T> {
     }
 }
 
+impl<T> From<Id<T>> for u32 {
+    fn from(id: Id<T>) -> Self {
+        id.0
+    }
+}
+
 impl<T> Eq for Id<T> {
     fn assert_receiver_is_total_eq(&self) {
         self.0.assert_receiver_is_total_eq()
@@ -40,6 +46,15 @@ impl<'de, T> serde::Deserialize<'de> for Id<T> {
     }
 }
 
+impl<T> serde::Serialize for Id<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.0.serialize(serializer)
+    }
+}
+
 impl<T> Clone for Id<T> {
     fn clone(&self) -> Self {
         *self
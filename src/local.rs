@@ -0,0 +1,44 @@
+use crate::providers;
+
+/// E5 models expect texts to be prefixed with their purpose, headlines are compared to each other
+const PREFIX: &str = "query: ";
+
+#[derive(Clone)]
+pub struct Embedder {
+    model: std::sync::Arc<fastembed::TextEmbedding>,
+}
+
+impl Embedder {
+    pub fn new() -> Result<Self, Box<dyn std::error::Error + 'static + Send + Sync>> {
+        let model = fastembed::TextEmbedding::try_new(fastembed::InitOptions {
+            model_name: fastembed::EmbeddingModel::MultilingualE5Small,
+            show_download_progress: false,
+            ..Default::default()
+        })?;
+        Ok(Self {
+            model: std::sync::Arc::new(model),
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl providers::Embedder for Embedder {
+    fn model(&self) -> &'static str {
+        "multilingual-e5-small"
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn embed(
+        &self,
+        input: &str,
+    ) -> Result<Vec<f32>, Box<dyn std::error::Error + 'static + Send + Sync>> {
+        let model = self.model.clone();
+        let input = format!("{PREFIX}{input}");
+        // inference is cpu-bound, it would block the runtime
+        let mut embeddings =
+            tokio::task::spawn_blocking(move || model.embed(vec![input], None)).await??;
+        embeddings
+            .pop()
+            .ok_or_else(|| "no embedding returned".into())
+    }
+}
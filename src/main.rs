@@ -1,51 +1,763 @@
+mod alerts;
 mod background;
+mod backup;
+mod bluesky;
 mod clustering;
+mod dataset;
 mod db;
+mod deepl;
+#[cfg(feature = "error-reporting")]
+mod error_reporting;
 mod feeds;
+mod filters;
 mod id;
+#[cfg(feature = "local-embeddings")]
+mod local;
+mod mastodon;
 mod md5_hash;
 mod normalizer;
+mod ollama;
 mod openai;
+mod otlp;
 mod persisted;
+mod providers;
+mod push;
+mod quantization;
+mod ranking;
+mod retry;
+mod shutdown;
+mod simhash;
+mod social;
 mod url;
 mod web;
 
 use ::url::Url;
-use clap::Parser;
+use clap::{Parser, Subcommand};
 
 #[derive(Parser)]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
     #[arg(long, default_value = "database.sqlite3")]
     database_file: std::path::PathBuf,
+    /// overrides --database-file, for example `sqlite://database.sqlite3`. only sqlite databases
+    /// are supported, queries and migrations are written for sqlite
+    #[arg(long, env, conflicts_with = "database_file")]
+    database_url: Option<String>,
+    /// max number of open database connections
+    #[arg(long, default_value = "10")]
+    db_max_connections: u32,
+    /// how long to wait for a locked database before failing, in milliseconds
+    #[arg(long, default_value = "5000")]
+    db_busy_timeout_ms: u64,
+    #[arg(long, value_enum, default_value = "all")]
+    mode: Mode,
+    /// where to run translations, and embeddings unless --embedding-provider is set
+    #[arg(long, value_enum, default_value = "openai")]
+    llm_provider: LlmProvider,
+    /// required when openai is used
     #[arg(long, env)]
-    openai_token: String,
+    openai_token: Option<String>,
     #[arg(long, default_value = "https://api.openai.com/")]
     openai_base_url: Url,
+    #[arg(long, value_enum, default_value = "dbscan")]
+    clustering_algorithm: clustering::Algorithm,
+    /// min number of entries in a group
+    #[arg(
+        long,
+        default_value = "3",
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+    )]
+    cluster_min_points: usize,
+    /// dbscan tolerances to search, when it can not be derived from the data
+    #[arg(long, value_parser = clustering::parse_tolerance_range, default_value = "0.9..1.1")]
+    cluster_tolerance_range: std::ops::RangeInclusive<f32>,
+    /// number of tolerances to try in --cluster-tolerance-range
+    #[arg(long, default_value = "50")]
+    cluster_samples: usize,
+    /// metric to compare tolerances in --cluster-tolerance-range by
+    #[arg(long, value_enum, default_value = "silhouette")]
+    cluster_score_metric: clustering::ScoreMetric,
+    /// add new entries to today's existing groups instead of regrouping the whole day, so that
+    /// group links stay valid
+    #[arg(long)]
+    incremental_clustering: bool,
+    /// group entries published within this many hours before each run instead of only today's,
+    /// so that stories spanning midnight are not split
+    #[arg(long)]
+    cluster_window_hours: Option<u32>,
+    /// max number of headlines translated in a single request
+    #[arg(long, default_value = "10")]
+    translation_batch_size: usize,
+    /// languages to translate headlines into besides english, for example `uk,ar,fi`
+    #[arg(long, value_delimiter = ',')]
+    translation_languages: Vec<feeds::LanguageCode>,
+    /// max number of requests sent to openai per minute
+    #[arg(long, default_value = "500", value_parser = clap::value_parser!(u32).range(1..))]
+    openai_requests_per_minute: u32,
+    /// max number of tokens sent to openai per minute
+    #[arg(long, default_value = "200000", value_parser = clap::value_parser!(u32).range(1..))]
+    openai_tokens_per_minute: u32,
+    /// stop embedding and translating with openai for the rest of the day once this much is spent
+    #[arg(long)]
+    daily_budget_usd: Option<f64>,
+    #[arg(long, default_value = "http://127.0.0.1:11434/")]
+    ollama_base_url: Url,
+    /// required when deepl is used
+    #[arg(long, env)]
+    deepl_token: Option<String>,
+    #[arg(long, default_value = "https://api-free.deepl.com/")]
+    deepl_base_url: Url,
+    /// chat completion model used for translations
+    #[arg(long, default_value = "gpt-3.5-turbo")]
+    completion_model: String,
+    /// where to translate headlines, defaults to --llm-provider
+    #[arg(long, value_enum)]
+    translation_provider: Option<TranslationProvider>,
+    /// texts to write about every group with --llm-provider
+    #[arg(long, value_enum, value_delimiter = ',')]
+    group_enrichments: Vec<background::Enrichment>,
+    /// where to generate embeddings, defaults to --llm-provider
+    #[arg(long, value_enum)]
+    embedding_provider: Option<EmbeddingProvider>,
+    /// precision to store new embeddings with, lower precision takes less space
+    #[arg(long, value_enum, default_value = "f32")]
+    embedding_precision: quantization::Precision,
+    /// embeddings model, embeddings of different models are never clustered together
+    #[arg(long, default_value = "text-embedding-3-large")]
+    embedding_model: String,
+    /// max number of concurrent embedding and translation requests
+    #[arg(long, default_value = "8")]
+    openai_concurrency: usize,
     #[arg(long, default_value = "127.0.0.1:8080")]
     address: String,
+    /// address to serve the grpc service of groups on, for example `127.0.0.1:50051`
+    #[cfg(feature = "grpc")]
+    #[arg(long)]
+    grpc_address: Option<String>,
+    /// pem encoded certificate chain to serve https with, together with `--tls-key`
+    #[cfg(feature = "tls")]
+    #[arg(long, requires = "tls_key")]
+    tls_cert: Option<std::path::PathBuf>,
+    /// pem encoded private key of `--tls-cert`
+    #[cfg(feature = "tls")]
+    #[arg(long, requires = "tls_cert")]
+    tls_key: Option<std::path::PathBuf>,
+    /// number of days back to search entries in
+    #[arg(long, default_value = "7")]
+    search_days: u32,
+    /// order of groups on the index, unless chosen with `?sort=`
+    #[arg(long, value_enum, default_value = "size")]
+    default_sort: ranking::Sort,
+    /// weights of feeds when sorting by diversity, as `<feed id>=<weight>`, for example `1=2` to
+    /// count SVT twice. feeds weigh 1 by default
+    #[arg(long, value_delimiter = ',', value_parser = ranking::parse_feed_weight)]
+    feed_weights: Vec<ranking::FeedWeight>,
+    /// show a free entry of a group on the index instead of its center, if the center is
+    /// paywalled
+    #[arg(long)]
+    deprioritize_paywalled: bool,
+    /// categories to leave out of the index, for example `sports,culture`. they are still shown
+    /// when chosen with `?category=`
+    #[arg(long, value_enum, value_delimiter = ',')]
+    hidden_categories: Vec<clustering::Category>,
+    /// number of seconds entries of the index are kept in memory, unless a new report is
+    /// generated. 0 disables the cache
+    #[arg(long, default_value = "60")]
+    index_cache_seconds: u64,
+    /// number of requests per second a client can make to the website. not limited by default
+    #[arg(long)]
+    rate_limit_rps: Option<f64>,
+    /// number of requests a client can make at once before it is limited by `--rate-limit-rps`
+    #[arg(long, default_value = "20")]
+    rate_limit_burst: u32,
+    /// addresses or networks of reverse proxies in front of the website, for example
+    /// `127.0.0.1,fdaa::/16`. client addresses and schemes are taken from `X-Forwarded-For` and
+    /// `X-Forwarded-Proto` headers of requests they forward
+    #[arg(long, value_delimiter = ',')]
+    trusted_proxies: Vec<web::Network>,
+    /// keys of admin pages as `<name>=<key>`, for example `alice=secret`. requests must have an
+    /// `Authorization: Bearer <key>` header, and are logged with the name of the key. admin
+    /// pages are not served when there are no keys
+    #[arg(long, env = "API_KEYS", value_delimiter = ',', hide_env_values = true)]
+    api_keys: Vec<web::ApiKey>,
+    /// secret cookies of reader preferences are signed with. a random one is used when missing,
+    /// so preferences are forgotten when the server restarts
+    #[arg(long, env = "COOKIE_SECRET", hide_env_values = true)]
+    cookie_secret: Option<String>,
+    /// public url of the website, used to build absolute links
+    #[arg(long, default_value = "http://127.0.0.1:8080/")]
+    base_url: Url,
+    #[arg(
+        long,
+        value_delimiter = ',',
+        default_value = "stockholm,vast,skane,uppsala,ost"
+    )]
+    svt_regions: Vec<String>,
+    /// least time between crawler requests to the same host, robots.txt of the host can ask for
+    /// more
+    #[arg(long, default_value = "1000")]
+    crawl_delay_ms: u64,
+    /// crawl delays of feeds as `<feed id>=<milliseconds>`, used instead of `--crawl-delay-ms`
+    /// and robots.txt ones, for example `1=0` to not wait between SVT requests
+    #[arg(long, value_delimiter = ',', value_parser = feeds::http::parse_feed_crawl_delay)]
+    feed_crawl_delays: Vec<feeds::http::FeedCrawlDelay>,
+    /// fetch article pages of new entries without pictures in their feeds to find their
+    /// `og:image`s. slows crawls down, as requests to a host are spaced by crawl delays
+    #[arg(long)]
+    article_images: bool,
+    /// fetch article pages of new entries to tell whether they are paywalled by their structured
+    /// data, instead of only by the flags of their feeds
+    #[arg(long)]
+    paywall_heuristics: bool,
+    /// skip crawled entries whose titles or descriptions mention any of these keywords, for
+    /// example `horoskop,lotto`. skipped entries are listed on /status/filters
+    #[arg(long, value_delimiter = ',')]
+    blocked_keywords: Vec<String>,
+    /// keep crawled entries mentioning any of these keywords, even when they mention blocked ones
+    #[arg(long, value_delimiter = ',')]
+    allowed_keywords: Vec<String>,
+    /// ids of feeds to crawl regardless of robots.txt, for example by agreement with the
+    /// publisher
+    #[arg(long, value_delimiter = ',', value_parser = feeds::http::parse_feed_id)]
+    ignore_robots_feeds: Vec<id::Id<feeds::Feed>>,
+    /// webhook to post alerts to, for example when a feed keeps failing
+    #[arg(long, env)]
+    alert_webhook_url: Option<Url>,
+    /// number of consecutive failed crawls of a feed before an alert is sent
+    #[arg(long, default_value = "3")]
+    alert_after_failures: u32,
+    /// stories covered by at least this many feeds within `--breaking-window-minutes` are
+    /// marked as breaking news
+    #[arg(
+        long,
+        default_value = "4",
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(2..)
+    )]
+    breaking_sources: usize,
+    #[arg(long, default_value = "60", value_parser = clap::value_parser!(u32).range(1..))]
+    breaking_window_minutes: u32,
+    /// webhook to post breaking news to, for notification integrations
+    #[arg(long, env)]
+    breaking_webhook_url: Option<Url>,
+    /// vapid private key to sign push notifications of breaking news with, as url safe base64 of
+    /// the raw p-256 key. requires `--vapid-public-key`
+    #[arg(long, env, hide_env_values = true, requires = "vapid_public_key")]
+    vapid_private_key: Option<push::PrivateKey>,
+    /// vapid public key browsers subscribe with, as url safe base64 of the uncompressed point
+    #[arg(long, env, requires = "vapid_private_key")]
+    vapid_public_key: Option<String>,
+    /// `mailto:` or `https:` contact push services can reach the sender at, `--base-url` by
+    /// default
+    #[arg(long, env)]
+    vapid_subject: Option<String>,
+    /// read the top stories of the day out loud with openai text to speech, every few hours
+    #[arg(long)]
+    briefing: bool,
+    /// mastodon instance to announce major stories on, for example `https://mastodon.social`.
+    /// requires `--mastodon-access-token`
+    #[arg(long, env, requires = "mastodon_access_token")]
+    mastodon_instance_url: Option<Url>,
+    /// access token of the account to post with, needs the `write:statuses` scope
+    #[arg(long, env, hide_env_values = true)]
+    mastodon_access_token: Option<String>,
+    /// handle of the bluesky account to announce major stories on, for example
+    /// `sverige.bsky.social`. requires `--bluesky-app-password`
+    #[arg(long, env, requires = "bluesky_app_password")]
+    bluesky_identifier: Option<String>,
+    /// app password of the bluesky account
+    #[arg(long, env, hide_env_values = true)]
+    bluesky_app_password: Option<String>,
+    /// personal data server of the bluesky account
+    #[arg(long, env, default_value = "https://bsky.social/")]
+    bluesky_service_url: Url,
+    /// stories covered by at least this many feeds are announced on social networks
+    #[arg(long, default_value = "5")]
+    announce_sources: usize,
+    /// categories of stories to announce behind content warnings, for example `crime`
+    #[arg(long, value_enum, value_delimiter = ',', default_value = "crime")]
+    content_warnings: Vec<clustering::Category>,
+    /// OpenTelemetry collector to export traces to over OTLP/HTTP, for example
+    /// `http://localhost:4318`
+    #[arg(long, env = "OTEL_EXPORTER_OTLP_ENDPOINT")]
+    otlp_endpoint: Option<Url>,
+    /// sentry dsn to report errors and panics to
+    #[cfg(feature = "error-reporting")]
+    #[arg(long, env = "SENTRY_DSN")]
+    sentry_dsn: Option<error_reporting::Dsn>,
+}
+
+/// what serve runs. web and worker processes can share a database.
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum Mode {
+    /// web server and background jobs in one process
+    All,
+    /// web server only
+    Web,
+    /// background jobs only
+    Worker,
+}
+
+#[derive(Clone, clap::ValueEnum)]
+enum LlmProvider {
+    Openai,
+    Ollama,
+}
+
+#[derive(Clone, clap::ValueEnum)]
+enum TranslationProvider {
+    Openai,
+    Ollama,
+    Deepl,
+}
+
+#[derive(Clone, clap::ValueEnum)]
+enum EmbeddingProvider {
+    Openai,
+    Ollama,
+    /// runs a multilingual sentence transformer in-process, needs the `local-embeddings` feature
+    #[cfg(feature = "local-embeddings")]
+    Local,
+}
+
+#[derive(Clone, Subcommand)]
+enum Command {
+    /// run the web server along with background jobs, the default
+    Serve,
+    /// crawl feeds and group entries, without running the web server
+    Crawl {
+        /// crawl once and exit, instead of on a schedule
+        #[arg(long)]
+        once: bool,
+    },
+    /// group entries published between the given dates anew with the current clustering
+    /// parameters, inclusive
+    Recluster {
+        #[arg(long, alias = "date")]
+        from: chrono::NaiveDate,
+        /// defaults to --from
+        #[arg(long)]
+        to: Option<chrono::NaiveDate>,
+    },
+    /// apply database migrations and exit
+    Migrate,
+    /// generate embeddings with the configured embedding model for entries published between
+    /// the given dates, inclusive
+    Reembed {
+        #[arg(long)]
+        from: chrono::NaiveDate,
+        #[arg(long)]
+        to: chrono::NaiveDate,
+    },
+    /// write a backup of the database into a directory, while the server keeps running
+    Backup {
+        #[arg(long)]
+        out: std::path::PathBuf,
+        /// compress the backup with gzip
+        #[arg(long)]
+        gzip: bool,
+        /// remove all but this many latest backups of the directory
+        #[arg(long)]
+        keep: Option<usize>,
+    },
+    /// write entries, their fields and embeddings into a json lines file
+    Export {
+        #[arg(long)]
+        out: std::path::PathBuf,
+    },
+    /// load a file written by export, skipping entries that already exist
+    Import {
+        #[arg(long)]
+        input: std::path::PathBuf,
+    },
+    /// render the index, pages of the latest days and their stories into static html files along
+    /// with the assets, to host the site without the server
+    ExportSite {
+        #[arg(long)]
+        out: std::path::PathBuf,
+        /// number of latest days to render pages of, including today
+        #[arg(long, default_value = "7")]
+        days: u32,
+    },
 }
 
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let subscriber = tracing_subscriber::fmt::fmt()
-        .with_span_events(
-            tracing_subscriber::fmt::format::FmtSpan::NEW
-                | tracing_subscriber::fmt::format::FmtSpan::CLOSE,
+/// creates clients of the configured providers on first use, so that tokens are only required
+/// for providers that are used
+struct Providers<'a> {
+    cli: &'a Cli,
+    db: db::Client,
+    /// clones share the rate limiter
+    openai_client: once_cell::unsync::OnceCell<openai::Client>,
+}
+
+impl Providers<'_> {
+    fn openai_client(&self) -> openai::Client {
+        self.openai_client
+            .get_or_init(|| {
+                openai::Client::new(
+                    &self.cli.openai_base_url,
+                    self.cli
+                        .openai_token
+                        .as_deref()
+                        .expect("--openai-token is required to use openai"),
+                    &self.cli.completion_model,
+                    &self.cli.embedding_model,
+                    openai::RateLimiter::new(
+                        self.cli.openai_requests_per_minute,
+                        self.cli.openai_tokens_per_minute,
+                    ),
+                    self.db.clone(),
+                    self.cli.daily_budget_usd,
+                )
+            })
+            .clone()
+    }
+
+    fn ollama_client(&self) -> ollama::Client {
+        ollama::Client::new(
+            &self.cli.ollama_base_url,
+            &self.cli.completion_model,
+            &self.cli.embedding_model,
         )
-        .finish();
+    }
+
+    fn embedder(&self) -> std::sync::Arc<dyn providers::Embedder> {
+        let embedding_provider =
+            self.cli
+                .embedding_provider
+                .clone()
+                .unwrap_or(match self.cli.llm_provider {
+                    LlmProvider::Openai => EmbeddingProvider::Openai,
+                    LlmProvider::Ollama => EmbeddingProvider::Ollama,
+                });
+        match embedding_provider {
+            EmbeddingProvider::Openai => std::sync::Arc::new(self.openai_client()),
+            EmbeddingProvider::Ollama => std::sync::Arc::new(self.ollama_client()),
+            #[cfg(feature = "local-embeddings")]
+            EmbeddingProvider::Local => std::sync::Arc::new(
+                local::Embedder::new().expect("failed to load local embedding model"),
+            ),
+        }
+    }
+
+    fn translator(&self) -> std::sync::Arc<dyn providers::Translator> {
+        let translation_provider =
+            self.cli
+                .translation_provider
+                .clone()
+                .unwrap_or(match self.cli.llm_provider {
+                    LlmProvider::Openai => TranslationProvider::Openai,
+                    LlmProvider::Ollama => TranslationProvider::Ollama,
+                });
+        match translation_provider {
+            TranslationProvider::Openai => std::sync::Arc::new(self.openai_client()),
+            TranslationProvider::Ollama => std::sync::Arc::new(self.ollama_client()),
+            TranslationProvider::Deepl => std::sync::Arc::new(deepl::Client::new(
+                &self.cli.deepl_base_url,
+                self.cli
+                    .deepl_token
+                    .as_deref()
+                    .expect("--deepl-token is required to use deepl"),
+            )),
+        }
+    }
+
+    fn summarizer(&self) -> Option<std::sync::Arc<dyn providers::Summarizer>> {
+        (!self.cli.group_enrichments.is_empty()).then(|| match self.cli.llm_provider {
+            LlmProvider::Openai => std::sync::Arc::new(self.openai_client()) as _,
+            LlmProvider::Ollama => std::sync::Arc::new(self.ollama_client()) as _,
+        })
+    }
+}
+
+fn init_tracing(cli: &Cli) -> Option<otlp::Exporter> {
+    use tracing_subscriber::{layer::SubscriberExt, Layer};
+
+    let (otlp_layer, otlp_exporter) = cli.otlp_endpoint.as_ref().map(otlp::new).unzip();
+    #[cfg(feature = "error-reporting")]
+    let error_reporting_layer = cli.sentry_dsn.as_ref().map(|_| error_reporting::layer());
+    #[cfg(not(feature = "error-reporting"))]
+    let error_reporting_layer: Option<tracing_subscriber::layer::Identity> = None;
+    let subscriber = tracing_subscriber::registry()
+        .with(
+            tracing_subscriber::fmt::layer()
+                .with_span_events(
+                    tracing_subscriber::fmt::format::FmtSpan::NEW
+                        | tracing_subscriber::fmt::format::FmtSpan::CLOSE,
+                )
+                .with_filter(tracing_subscriber::filter::LevelFilter::INFO),
+        )
+        .with(otlp_layer)
+        .with(error_reporting_layer);
     tracing::subscriber::set_global_default(subscriber).expect("setting default subscriber failed");
+    otlp_exporter
+}
+
+async fn connect_db(cli: &Cli) -> db::Client {
+    let db_config = db::Config {
+        max_connections: cli.db_max_connections,
+        busy_timeout: std::time::Duration::from_millis(cli.db_busy_timeout_ms),
+    };
+    match &cli.database_url {
+        Some(url) => db::Client::connect(url, &db_config).await,
+        None => db::Client::new(&cli.database_file, &db_config).await,
+    }
+    .expect("failed to create db client")
+}
+
+fn background_config(cli: &Cli) -> background::Config {
+    background::Config {
+        svt_regions: cli.svt_regions.clone(),
+        crawler: feeds::http::Config {
+            crawl_delay: std::time::Duration::from_millis(cli.crawl_delay_ms),
+            feed_crawl_delays: cli.feed_crawl_delays.clone(),
+            ignore_robots_feeds: cli.ignore_robots_feeds.clone(),
+        },
+        article_images: cli.article_images,
+        paywall_heuristics: cli.paywall_heuristics,
+        keywords: filters::Keywords::new(&cli.blocked_keywords, &cli.allowed_keywords),
+        alerts_client: cli.alert_webhook_url.as_ref().map(alerts::Client::new),
+        alert_after_failures: cli.alert_after_failures,
+        openai_concurrency: cli.openai_concurrency,
+        translation_batch_size: cli.translation_batch_size,
+        translation_languages: translation_languages(cli),
+        clustering: clustering::Params {
+            algorithm: cli.clustering_algorithm,
+            min_points: cli.cluster_min_points,
+            tolerance_range: cli.cluster_tolerance_range.clone(),
+            samples: cli.cluster_samples,
+            score_metric: cli.cluster_score_metric,
+        },
+        incremental_clustering: cli.incremental_clustering,
+        clustering_window: cli
+            .cluster_window_hours
+            .map(|hours| chrono::Duration::hours(hours.into())),
+        group_enrichments: cli.group_enrichments.clone(),
+        breaking_sources: cli.breaking_sources,
+        breaking_window: chrono::Duration::minutes(cli.breaking_window_minutes.into()),
+        breaking_client: cli.breaking_webhook_url.as_ref().map(alerts::Client::new),
+        push_client: cli.vapid_private_key.as_ref().map(|private_key| {
+            let subject = cli
+                .vapid_subject
+                .clone()
+                .unwrap_or_else(|| cli.base_url.to_string());
+            push::Client::new(private_key, &subject)
+        }),
+        posters: posters(cli),
+        briefing_client: None,
+        announce_sources: cli.announce_sources,
+        content_warnings: cli.content_warnings.clone(),
+        base_url: cli.base_url.clone(),
+        embedding_precision: cli.embedding_precision,
+        instance_id: format!("{:016x}", rand::random::<u64>()),
+        report_updates: None,
+    }
+}
+
+fn posters(cli: &Cli) -> Vec<social::Poster> {
+    let mastodon = cli
+        .mastodon_instance_url
+        .as_ref()
+        .zip(cli.mastodon_access_token.as_deref())
+        .map(|(instance_url, access_token)| {
+            social::Poster::Mastodon(mastodon::Client::new(instance_url, access_token))
+        });
+    let bluesky = cli
+        .bluesky_identifier
+        .as_deref()
+        .zip(cli.bluesky_app_password.as_deref())
+        .map(|(identifier, app_password)| {
+            social::Poster::Bluesky(bluesky::Client::new(
+                &cli.bluesky_service_url,
+                identifier,
+                app_password,
+            ))
+        });
+    mastodon.into_iter().chain(bluesky).collect()
+}
+
+fn translation_languages(cli: &Cli) -> Vec<feeds::LanguageCode> {
+    let mut languages = vec![feeds::LanguageCode::EN];
+    for lang_code in &cli.translation_languages {
+        if !languages.contains(lang_code) && *lang_code != feeds::LanguageCode::SV {
+            languages.push(lang_code.clone());
+        }
+    }
+    languages
+}
 
+async fn serve(
+    cli: &Cli,
+    db: db::Client,
+    providers: &Providers<'_>,
+    config: background::Config,
+    mode: Mode,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let embedder = providers.embedder();
+    let (report_updates, report_updates_receiver) = tokio::sync::watch::channel(None);
+    let report_updates_receiver = match mode {
+        // reports are written by another process
+        Mode::Web => web::poll_report_updates(db.clone()),
+        Mode::All | Mode::Worker => report_updates_receiver,
+    };
+    let config = background::Config {
+        report_updates: Some(std::sync::Arc::new(report_updates)),
+        ..config
+    };
+    let web = || {
+        web::serve(
+            db.clone(),
+            &cli.address,
+            &cli.base_url,
+            embedder.clone(),
+            web::Config {
+                report_updates: Some(report_updates_receiver.clone()),
+                ..web_config(cli, &config)
+            },
+        )
+    };
+    let worker = || {
+        background::run(
+            db.clone(),
+            embedder.clone(),
+            providers.translator(),
+            providers.summarizer(),
+            config.clone(),
+        )
+    };
+    match mode {
+        Mode::All => {
+            futures::future::try_join(web(), worker()).await?;
+        }
+        Mode::Web => web().await?,
+        Mode::Worker => worker().await?,
+    }
+    Ok(())
+}
+
+fn web_config(cli: &Cli, config: &background::Config) -> web::Config {
+    web::Config {
+        search_window: chrono::Duration::days(cli.search_days.into()),
+        default_sort: cli.default_sort,
+        feed_weights: ranking::FeedWeights::new(&cli.feed_weights),
+        deprioritize_paywalled: cli.deprioritize_paywalled,
+        hidden_categories: cli.hidden_categories.clone(),
+        index_cache_ttl: std::time::Duration::from_secs(cli.index_cache_seconds),
+        report_updates: None,
+        rate_limit_per_second: cli.rate_limit_rps.filter(|rps| *rps > 0.0),
+        rate_limit_burst: cli.rate_limit_burst,
+        trusted_proxies: cli.trusted_proxies.clone(),
+        api_keys: cli.api_keys.clone(),
+        vapid_public_key: cli.vapid_public_key.clone(),
+        #[cfg(feature = "grpc")]
+        grpc_address: cli.grpc_address.clone(),
+        #[cfg(feature = "tls")]
+        tls: cli
+            .tls_cert
+            .clone()
+            .zip(cli.tls_key.clone())
+            .map(|(cert, key)| web::Tls { cert, key }),
+        // titles are available in the original swedish and in the translations
+        languages: std::iter::once(feeds::LanguageCode::SV)
+            .chain(config.translation_languages.clone())
+            .collect(),
+        cookie_secret: cli.cookie_secret.clone(),
+    }
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
     let cli = Cli::parse();
-    let db = db::Client::new(cli.database_file)
-        .await
-        .expect("failed to create db client");
-    let openai_client = openai::Client::new(&cli.openai_base_url, &cli.openai_token);
-
-    futures::future::try_join(
-        web::serve(db.clone(), &cli.address),
-        background::run(db, openai_client),
-    )
-    .await?;
+    #[cfg(feature = "error-reporting")]
+    let _error_reporting = cli.sentry_dsn.as_ref().map(error_reporting::init);
+    let otlp_exporter = init_tracing(&cli);
+    shutdown::listen()?;
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()?
+        .block_on(async {
+            if let Some(exporter) = otlp_exporter.clone() {
+                tokio::spawn(exporter.run());
+            }
+            let result = run(cli).await;
+            if let Some(exporter) = otlp_exporter {
+                exporter.flush().await;
+            }
+            result
+        })
+}
+
+async fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
+    let db = connect_db(&cli).await;
+    let providers = Providers {
+        cli: &cli,
+        db: db.clone(),
+        openai_client: once_cell::unsync::OnceCell::new(),
+    };
+    let config = background::Config {
+        briefing_client: cli.briefing.then(|| providers.openai_client()),
+        ..background_config(&cli)
+    };
+
+    match cli.command.clone().unwrap_or(Command::Serve) {
+        Command::Serve => serve(&cli, db, &providers, config, cli.mode).await?,
+        Command::Crawl { once: false } => {
+            serve(&cli, db, &providers, config, Mode::Worker).await?;
+        }
+        Command::Crawl { once: true } => {
+            background::fetch(
+                &db,
+                providers.embedder().as_ref(),
+                providers.translator().as_ref(),
+                providers.summarizer().as_deref(),
+                &config,
+            )
+            .await
+            .map_err(|error| error as Box<dyn std::error::Error>)?;
+        }
+        Command::Recluster { from, to } => {
+            background::recluster(
+                &db,
+                providers.embedder().as_ref(),
+                providers.translator().as_ref(),
+                providers.summarizer().as_deref(),
+                &config,
+                from,
+                to.unwrap_or(from),
+            )
+            .await
+            .map_err(|error| error as Box<dyn std::error::Error>)?;
+        }
+        // migrations are applied on connect
+        Command::Migrate => tracing::info!("database is up to date"),
+        Command::Reembed { from, to } => {
+            background::reembed(&db, providers.embedder().as_ref(), &config, from, to)
+                .await
+                .map_err(|error| error as Box<dyn std::error::Error>)?;
+        }
+        Command::Backup { out, gzip, keep } => {
+            backup::run(&db, &out, gzip, keep).await?;
+        }
+        Command::Export { out } => {
+            let mut file = std::io::BufWriter::new(std::fs::File::create(out)?);
+            let count = dataset::export(&db, &mut file)
+                .await
+                .map_err(|error| error as Box<dyn std::error::Error>)?;
+            std::io::Write::flush(&mut file)?;
+            tracing::info!(count, "exported entries");
+        }
+        Command::Import { input } => {
+            let file = std::io::BufReader::new(std::fs::File::open(input)?);
+            let count = dataset::import(&db, file, config.embedding_precision)
+                .await
+                .map_err(|error| error as Box<dyn std::error::Error>)?;
+            tracing::info!(count, "imported entries");
+        }
+        Command::ExportSite { out, days } => {
+            let count =
+                web::export_site(db, &cli.base_url, web_config(&cli, &config), &out, days).await?;
+            tracing::info!(count, "exported pages");
+        }
+    }
 
     Ok(())
 }
@@ -0,0 +1,45 @@
+#[derive(Clone)]
+pub struct Client {
+    inner: reqwest::Client,
+    statuses_url: url::Url,
+    access_token: String,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct Status {
+    #[serde(rename = "status")]
+    pub text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spoiler_text: Option<String>,
+    pub language: &'static str,
+}
+
+impl Client {
+    pub fn new(instance_url: &url::Url, access_token: &str) -> Self {
+        Self {
+            inner: reqwest::Client::new(),
+            statuses_url: instance_url.join("api/v1/statuses").expect("valid url"),
+            access_token: access_token.to_string(),
+        }
+    }
+
+    /// posts the status once per idempotency key, so that retries of the same request do not
+    /// post duplicates
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn post(
+        &self,
+        status: &Status,
+        idempotency_key: &str,
+    ) -> Result<(), Box<dyn std::error::Error + 'static + Send + Sync>> {
+        self.inner
+            .post(self.statuses_url.clone())
+            .bearer_auth(&self.access_token)
+            .header("Idempotency-Key", idempotency_key)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(serde_json::to_string(status)?)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
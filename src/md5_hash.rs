@@ -1,4 +1,4 @@
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub struct Md5Hash(md5::Digest);
 
 pub fn compute<T: AsRef<[u8]>>(data: T) -> Md5Hash {
@@ -0,0 +1,225 @@
+use crate::{clustering, feeds, providers};
+
+#[derive(Clone)]
+pub struct Client {
+    base_url: url::Url,
+    inner: reqwest::Client,
+    completion_model: String,
+    embedding_model: String,
+}
+
+#[derive(Debug, serde::Deserialize, thiserror::Error)]
+#[error("{error}")]
+pub struct ErrorResponse {
+    error: String,
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(untagged)]
+enum Response<T> {
+    Ok(T),
+    Error(ErrorResponse),
+}
+
+impl Client {
+    pub fn new(base_url: &url::Url, completion_model: &str, embedding_model: &str) -> Self {
+        Self {
+            base_url: base_url.clone(),
+            inner: reqwest::Client::new(),
+            completion_model: completion_model.to_string(),
+            embedding_model: embedding_model.to_string(),
+        }
+    }
+
+    async fn post<T: serde::de::DeserializeOwned>(
+        &self,
+        path: &str,
+        body: &serde_json::Value,
+    ) -> Result<T, Box<dyn std::error::Error + 'static + Send + Sync>> {
+        let endpoint = self.base_url.join(path).expect("invalid ollama endpoint");
+        let response = self
+            .inner
+            .post(endpoint)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(serde_json::to_string(body)?)
+            .send()
+            .await?;
+
+        let response_bytes = response.bytes().await?;
+
+        match serde_json::from_slice::<Response<T>>(&response_bytes) {
+            Ok(Response::Ok(response)) => Ok(response),
+            Ok(Response::Error(error)) => Err(error.into()),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    pub async fn chat(
+        &self,
+        task: &str,
+        input: &str,
+        format: Option<&str>,
+    ) -> Result<String, Box<dyn std::error::Error + 'static + Send + Sync>> {
+        #[derive(Debug, serde::Deserialize)]
+        struct ChatMessage {
+            content: String,
+        }
+
+        #[derive(Debug, serde::Deserialize)]
+        struct ChatResponse {
+            message: ChatMessage,
+        }
+
+        let mut body = serde_json::json!({
+            "model": self.completion_model,
+            "messages": [
+                {"role": "system", "content": task},
+                {"role": "user", "content": input}
+            ],
+            "stream": false,
+            "options": {"temperature": 0},
+        });
+        if let Some(format) = format {
+            body["format"] = format.into();
+        }
+
+        let response = self.post::<ChatResponse>("/api/chat", &body).await?;
+        Ok(response.message.content)
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn embeddings(
+        &self,
+        input: &str,
+    ) -> Result<Vec<f32>, Box<dyn std::error::Error + 'static + Send + Sync>> {
+        #[derive(Debug, serde::Deserialize)]
+        struct EmbeddingResponse {
+            embedding: Vec<f32>,
+        }
+
+        let body = serde_json::json!({"model": self.embedding_model, "prompt": input});
+
+        let response = self
+            .post::<EmbeddingResponse>("/api/embeddings", &body)
+            .await?;
+        Ok(response.embedding)
+    }
+}
+
+#[async_trait::async_trait]
+impl providers::Embedder for Client {
+    fn model(&self) -> &str {
+        &self.embedding_model
+    }
+
+    async fn embed(
+        &self,
+        input: &str,
+    ) -> Result<Vec<f32>, Box<dyn std::error::Error + 'static + Send + Sync>> {
+        self.embeddings(input).await
+    }
+}
+
+#[async_trait::async_trait]
+impl providers::Translator for Client {
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn translate(
+        &self,
+        from: &feeds::LanguageCode,
+        to: &feeds::LanguageCode,
+        value: &str,
+    ) -> Result<String, Box<dyn std::error::Error + 'static + Send + Sync>> {
+        let output = self
+            .chat(
+                &providers::translate_task(from, to),
+                &providers::translation_input(value),
+                Some("json"),
+            )
+            .await?;
+        providers::parse_translation(&output, value)
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn translate_batch(
+        &self,
+        from: &feeds::LanguageCode,
+        to: &feeds::LanguageCode,
+        values: &[String],
+    ) -> Result<Vec<String>, Box<dyn std::error::Error + 'static + Send + Sync>> {
+        let output = self
+            .chat(
+                &providers::translate_batch_task(from, to),
+                &providers::batch_translation_input(values),
+                Some("json"),
+            )
+            .await?;
+        let translations = providers::parse_translations(&output, values);
+        providers::translate_missing(self, from, to, translations, values).await
+    }
+}
+
+#[async_trait::async_trait]
+impl providers::Summarizer for Client {
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn headline(
+        &self,
+        titles: &[String],
+    ) -> Result<Option<String>, Box<dyn std::error::Error + 'static + Send + Sync>> {
+        let output = self
+            .chat(
+                providers::HEADLINE_TASK,
+                &providers::headline_input(titles),
+                Some("json"),
+            )
+            .await?;
+        Ok(providers::parse_headline(&output))
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn summary(
+        &self,
+        descriptions: &[String],
+    ) -> Result<Option<String>, Box<dyn std::error::Error + 'static + Send + Sync>> {
+        let output = self
+            .chat(
+                providers::SUMMARY_TASK,
+                &providers::summary_input(descriptions),
+                Some("json"),
+            )
+            .await?;
+        Ok(providers::parse_summary(&output))
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn category(
+        &self,
+        titles: &[String],
+    ) -> Result<Option<clustering::Category>, Box<dyn std::error::Error + 'static + Send + Sync>>
+    {
+        let output = self
+            .chat(
+                providers::CATEGORY_TASK,
+                &providers::headline_input(titles),
+                Some("json"),
+            )
+            .await?;
+        Ok(providers::parse_category(&output))
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn lattlast(
+        &self,
+        titles: &[String],
+        descriptions: &[String],
+    ) -> Result<Option<providers::Lattlast>, Box<dyn std::error::Error + 'static + Send + Sync>>
+    {
+        let output = self
+            .chat(
+                providers::LATTLAST_TASK,
+                &providers::lattlast_input(titles, descriptions),
+                Some("json"),
+            )
+            .await?;
+        Ok(providers::parse_lattlast(&output))
+    }
+}
@@ -1,37 +1,68 @@
-use std::collections::HashSet;
+mod rate_limiter;
+
+use crate::{clustering, db, feeds, providers, retry};
+
+pub use rate_limiter::RateLimiter;
 
 use reqwest_middleware::{ClientBuilder, ClientWithMiddleware};
-use reqwest_retry::{
-    policies::ExponentialBackoff, RetryTransientMiddleware, Retryable, RetryableStrategy,
-};
 
 #[derive(Clone)]
 pub struct Client {
     base_url: url::Url,
-    http_client: ClientWithMiddleware,
+    inner: ClientWithMiddleware,
+    completion_model: String,
+    embedding_model: String,
+    rate_limiter: std::sync::Arc<RateLimiter>,
+    db: db::Client,
+    daily_budget_usd: Option<f64>,
 }
 
-struct RetryStatusCodes(HashSet<reqwest::StatusCode>);
-
-impl RetryStatusCodes {
-    fn new(status_codes: Vec<reqwest::StatusCode>) -> Self {
-        Self(status_codes.into_iter().collect())
-    }
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct Usage {
+    pub model: String,
+    pub prompt_tokens: u32,
+    pub completion_tokens: u32,
+    pub cost_usd: f64,
 }
 
-impl RetryableStrategy for RetryStatusCodes {
-    fn handle(&self, res: &reqwest_middleware::Result<reqwest::Response>) -> Option<Retryable> {
-        match res {
-            Ok(response) if self.0.contains(&response.status()) => Some(Retryable::Transient),
-            Ok(_) => None,
-            Err(error) => reqwest_retry::default_on_request_failure(error),
-        }
-    }
+#[derive(Debug, thiserror::Error)]
+#[error("daily budget of {0} usd is exceeded")]
+pub struct BudgetExceeded(f64);
+
+/// usd per million of prompt and completion tokens. models are matched by prefix, so that dated
+/// snapshots are priced too.
+static PRICES: [(&str, f64, f64); 8] = [
+    ("gpt-3.5-turbo", 0.5, 1.5),
+    ("gpt-4o-mini", 0.15, 0.6),
+    ("gpt-4o", 2.5, 10.0),
+    ("gpt-4-turbo", 10.0, 30.0),
+    ("text-embedding-3-small", 0.02, 0.0),
+    ("text-embedding-3-large", 0.13, 0.0),
+    ("text-embedding-ada-002", 0.1, 0.0),
+    // speech is priced by characters of the input, they are counted as prompt tokens
+    ("tts-1", 15.0, 0.0),
+];
+
+const SPEECH_MODEL: &str = "tts-1";
+const SPEECH_VOICE: &str = "alloy";
+
+#[derive(Debug, Default, serde::Deserialize)]
+struct ResponseUsage {
+    prompt_tokens: u32,
+    #[serde(default)]
+    completion_tokens: u32,
 }
 
 impl Client {
-    pub fn new(base_url: &url::Url, token: &str) -> Self {
-        let retry_policy = ExponentialBackoff::builder().build_with_max_retries(3);
+    pub fn new(
+        base_url: &url::Url,
+        token: &str,
+        completion_model: &str,
+        embedding_model: &str,
+        rate_limiter: RateLimiter,
+        db: db::Client,
+        daily_budget_usd: Option<f64>,
+    ) -> Self {
         let http_client = {
             let mut headers = reqwest::header::HeaderMap::new();
             headers.insert(
@@ -44,22 +75,76 @@ impl Client {
                 .build()
                 .expect("failed to build reqwest client");
             ClientBuilder::new(client)
-                .with(RetryTransientMiddleware::new_with_policy_and_strategy(
-                    retry_policy,
-                    RetryStatusCodes::new(vec![reqwest::StatusCode::SERVICE_UNAVAILABLE]),
+                .with(retry::Middleware::new(
+                    vec![
+                        reqwest::StatusCode::TOO_MANY_REQUESTS,
+                        reqwest::StatusCode::INTERNAL_SERVER_ERROR,
+                        reqwest::StatusCode::BAD_GATEWAY,
+                        reqwest::StatusCode::SERVICE_UNAVAILABLE,
+                        reqwest::StatusCode::GATEWAY_TIMEOUT,
+                    ],
+                    3,
                 ))
                 .build()
         };
         Self {
             base_url: base_url.clone(),
-            http_client,
+            inner: http_client,
+            completion_model: completion_model.to_string(),
+            embedding_model: embedding_model.to_string(),
+            rate_limiter: std::sync::Arc::new(rate_limiter),
+            db,
+            daily_budget_usd,
         }
     }
 
+    async fn check_budget(&self) -> Result<(), Box<dyn std::error::Error + 'static + Send + Sync>> {
+        let Some(daily_budget_usd) = self.daily_budget_usd else {
+            return Ok(());
+        };
+        let spent_usd = self
+            .db
+            .sum_openai_cost_by_date(&chrono::Utc::now().date_naive())
+            .await?;
+        if spent_usd >= daily_budget_usd {
+            return Err(BudgetExceeded(daily_budget_usd).into());
+        }
+        Ok(())
+    }
+
+    async fn record_usage(
+        &self,
+        model: &str,
+        usage: &ResponseUsage,
+    ) -> Result<(), Box<dyn std::error::Error + 'static + Send + Sync>> {
+        let (prompt_price, completion_price) = PRICES
+            .iter()
+            .find(|(prefix, _, _)| model.starts_with(prefix))
+            .map_or_else(
+                || {
+                    tracing::warn!(model, "unknown model price, usage is not accounted for");
+                    (0.0, 0.0)
+                },
+                |(_, prompt_price, completion_price)| (*prompt_price, *completion_price),
+            );
+        self.db
+            .insert_openai_usage(&Usage {
+                model: model.to_string(),
+                prompt_tokens: usage.prompt_tokens,
+                completion_tokens: usage.completion_tokens,
+                cost_usd: (f64::from(usage.prompt_tokens) * prompt_price
+                    + f64::from(usage.completion_tokens) * completion_price)
+                    / 1_000_000.0,
+            })
+            .await?;
+        Ok(())
+    }
+
     pub async fn comptetions(
         &self,
         task: &str,
         input: &str,
+        response_format: Option<&serde_json::Value>,
     ) -> Result<String, Box<dyn std::error::Error + 'static + Send + Sync>> {
         #[derive(Debug, serde::Deserialize)]
         struct ChatCompletionMessage {
@@ -74,23 +159,33 @@ impl Client {
         #[derive(Debug, serde::Deserialize)]
         struct ChatCompletionResponse {
             choices: Vec<ChatCompletionChoice>,
+            #[serde(default)]
+            usage: ResponseUsage,
         }
 
         let endpoint = self
             .base_url
             .join("/v1/chat/completions")
             .expect("invald chat completions endpoint");
-        let body = serde_json::json!({
-            "model": "gpt-3.5-turbo",
+        let mut body = serde_json::json!({
+            "model": self.completion_model,
             "messages": [
                 {"role": "system", "content": task},
                 {"role": "user", "content": input}
             ],
             "temperature": 0,
         });
+        if let Some(response_format) = response_format {
+            body["response_format"] = response_format.clone();
+        }
 
+        self.check_budget().await?;
+        // translations are about as long as the input
+        self.rate_limiter
+            .acquire(rate_limiter::estimate_tokens(task) + 2 * rate_limiter::estimate_tokens(input))
+            .await;
         let response = self
-            .http_client
+            .inner
             .post(endpoint)
             .header(reqwest::header::CONTENT_TYPE, "application/json")
             .body(serde_json::to_string(&body)?)
@@ -101,7 +196,11 @@ impl Client {
 
         let response = serde_json::from_slice::<Response<ChatCompletionResponse>>(&response_bytes);
         match response {
-            Ok(Response::Ok(completion)) => Ok(completion.choices[0].message.content.clone()),
+            Ok(Response::Ok(completion)) => {
+                self.record_usage(&self.completion_model, &completion.usage)
+                    .await?;
+                Ok(completion.choices[0].message.content.clone())
+            }
             Ok(Response::Error { error }) => Err(error.into()),
             Err(error) => Err(error.into()),
         }
@@ -115,6 +214,8 @@ impl Client {
         #[derive(Debug, serde::Deserialize)]
         struct ListResponse<T> {
             data: Vec<T>,
+            #[serde(default)]
+            usage: ResponseUsage,
         }
 
         #[derive(Debug, serde::Deserialize)]
@@ -126,10 +227,14 @@ impl Client {
             .base_url
             .join("/v1/embeddings")
             .expect("invald embeddngs endpoint");
-        let body = serde_json::json!({"model": "text-embedding-3-large", "input": input});
+        let body = serde_json::json!({"model": self.embedding_model, "input": input});
 
+        self.check_budget().await?;
+        self.rate_limiter
+            .acquire(rate_limiter::estimate_tokens(input))
+            .await;
         let response = self
-            .http_client
+            .inner
             .post(endpoint)
             .header(reqwest::header::CONTENT_TYPE, "application/json")
             .body(serde_json::to_string(&body)?)
@@ -142,11 +247,63 @@ impl Client {
             serde_json::from_slice::<Response<ListResponse<EmbeddingResponse>>>(&response_bytes);
 
         match response {
-            Ok(Response::Ok(list)) => Ok(list.data[0].embedding.clone()),
+            Ok(Response::Ok(list)) => {
+                self.record_usage(&self.embedding_model, &list.usage)
+                    .await?;
+                Ok(list.data[0].embedding.clone())
+            }
             Ok(Response::Error { error }) => Err(error.into()),
             Err(error) => Err(error.into()),
         }
     }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn speech(
+        &self,
+        input: &str,
+    ) -> Result<Vec<u8>, Box<dyn std::error::Error + 'static + Send + Sync>> {
+        let endpoint = self
+            .base_url
+            .join("/v1/audio/speech")
+            .expect("invalid speech endpoint");
+        let body = serde_json::json!({
+            "model": SPEECH_MODEL,
+            "voice": SPEECH_VOICE,
+            "input": input,
+            "response_format": "mp3",
+        });
+
+        self.check_budget().await?;
+        self.rate_limiter
+            .acquire(rate_limiter::estimate_tokens(input))
+            .await;
+        let response = self
+            .inner
+            .post(endpoint)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(serde_json::to_string(&body)?)
+            .send()
+            .await?;
+
+        let status = response.status();
+        let response_bytes = response.bytes().await?;
+        if !status.is_success() {
+            return match serde_json::from_slice::<Response<()>>(&response_bytes) {
+                Ok(Response::Error { error }) => Err(error.into()),
+                _ => Err(format!("unexpected speech response: {status}").into()),
+            };
+        }
+
+        self.record_usage(
+            SPEECH_MODEL,
+            &ResponseUsage {
+                prompt_tokens: u32::try_from(input.chars().count()).unwrap_or(u32::MAX),
+                completion_tokens: 0,
+            },
+        )
+        .await?;
+        Ok(response_bytes.to_vec())
+    }
 }
 
 #[derive(Debug, serde::Deserialize, thiserror::Error)]
@@ -162,21 +319,120 @@ enum Response<T> {
     Error { error: ErrorResponse },
 }
 
-pub struct Translator<'a> {
-    client: &'a Client,
-}
+#[async_trait::async_trait]
+impl providers::Embedder for Client {
+    fn model(&self) -> &str {
+        &self.embedding_model
+    }
 
-impl<'a> Translator<'a> {
-    pub fn new(client: &'a Client) -> Self {
-        Self { client }
+    async fn embed(
+        &self,
+        input: &str,
+    ) -> Result<Vec<f32>, Box<dyn std::error::Error + 'static + Send + Sync>> {
+        self.embeddings(input).await
     }
+}
 
+#[async_trait::async_trait]
+impl providers::Translator for Client {
     #[tracing::instrument(level = "debug", skip(self))]
-    pub async fn translate_sv_to_en(
+    async fn translate(
         &self,
+        from: &feeds::LanguageCode,
+        to: &feeds::LanguageCode,
         value: &str,
     ) -> Result<String, Box<dyn std::error::Error + 'static + Send + Sync>> {
-        let task = "You are a highly skilled and concise professional translator. When you receive a sentence in Swedish, your task is to translate it into English. VERY IMPORTANT: Do not output any notes, explanations, alternatives or comments after or before the translation.";
-        self.client.comptetions(task, value).await
+        let output = self
+            .comptetions(
+                &providers::translate_task(from, to),
+                &providers::translation_input(value),
+                Some(&serde_json::json!({"type": "json_object"})),
+            )
+            .await?;
+        providers::parse_translation(&output, value)
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn translate_batch(
+        &self,
+        from: &feeds::LanguageCode,
+        to: &feeds::LanguageCode,
+        values: &[String],
+    ) -> Result<Vec<String>, Box<dyn std::error::Error + 'static + Send + Sync>> {
+        let output = self
+            .comptetions(
+                &providers::translate_batch_task(from, to),
+                &providers::batch_translation_input(values),
+                Some(&serde_json::json!({"type": "json_object"})),
+            )
+            .await?;
+        let translations = providers::parse_translations(&output, values);
+        providers::translate_missing(self, from, to, translations, values).await
+    }
+}
+
+#[async_trait::async_trait]
+impl providers::Summarizer for Client {
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn headline(
+        &self,
+        titles: &[String],
+    ) -> Result<Option<String>, Box<dyn std::error::Error + 'static + Send + Sync>> {
+        let output = self
+            .comptetions(
+                providers::HEADLINE_TASK,
+                &providers::headline_input(titles),
+                Some(&serde_json::json!({"type": "json_object"})),
+            )
+            .await?;
+        Ok(providers::parse_headline(&output))
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn summary(
+        &self,
+        descriptions: &[String],
+    ) -> Result<Option<String>, Box<dyn std::error::Error + 'static + Send + Sync>> {
+        let output = self
+            .comptetions(
+                providers::SUMMARY_TASK,
+                &providers::summary_input(descriptions),
+                Some(&serde_json::json!({"type": "json_object"})),
+            )
+            .await?;
+        Ok(providers::parse_summary(&output))
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn category(
+        &self,
+        titles: &[String],
+    ) -> Result<Option<clustering::Category>, Box<dyn std::error::Error + 'static + Send + Sync>>
+    {
+        let output = self
+            .comptetions(
+                providers::CATEGORY_TASK,
+                &providers::headline_input(titles),
+                Some(&serde_json::json!({"type": "json_object"})),
+            )
+            .await?;
+        Ok(providers::parse_category(&output))
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn lattlast(
+        &self,
+        titles: &[String],
+        descriptions: &[String],
+    ) -> Result<Option<providers::Lattlast>, Box<dyn std::error::Error + 'static + Send + Sync>>
+    {
+        let output = self
+            .comptetions(
+                providers::LATTLAST_TASK,
+                &providers::lattlast_input(titles, descriptions),
+                Some(&serde_json::json!({"type": "json_object"})),
+            )
+            .await?;
+        Ok(providers::parse_lattlast(&output))
     }
 }
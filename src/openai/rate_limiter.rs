@@ -0,0 +1,81 @@
+use std::time::{Duration, Instant};
+
+#[derive(Debug)]
+pub struct RateLimiter {
+    buckets: tokio::sync::Mutex<(Bucket, Bucket)>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    capacity: f64,
+    available: f64,
+    refill_per_second: f64,
+    refilled_at: Instant,
+}
+
+impl Bucket {
+    fn new(per_minute: u32) -> Self {
+        let capacity = f64::from(per_minute);
+        Self {
+            capacity,
+            available: capacity,
+            refill_per_second: capacity / 60.0,
+            refilled_at: Instant::now(),
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.duration_since(self.refilled_at).as_secs_f64();
+        self.available = (self.available + elapsed * self.refill_per_second).min(self.capacity);
+        self.refilled_at = now;
+    }
+
+    fn wait_time(&self, amount: f64) -> Duration {
+        if self.available >= amount {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64((amount - self.available) / self.refill_per_second)
+        }
+    }
+}
+
+impl RateLimiter {
+    pub fn new(requests_per_minute: u32, tokens_per_minute: u32) -> Self {
+        Self {
+            buckets: tokio::sync::Mutex::new((
+                Bucket::new(requests_per_minute),
+                Bucket::new(tokens_per_minute),
+            )),
+        }
+    }
+
+    pub async fn acquire(&self, tokens: u32) {
+        loop {
+            let wait_time = {
+                let (requests, budget) = &mut *self.buckets.lock().await;
+                let now = Instant::now();
+                requests.refill(now);
+                budget.refill(now);
+
+                // a request larger than the whole budget would never be sent otherwise
+                let tokens = f64::from(tokens).min(budget.capacity);
+                let wait_time = requests.wait_time(1.0).max(budget.wait_time(tokens));
+                if wait_time.is_zero() {
+                    requests.available -= 1.0;
+                    budget.available -= tokens;
+                    return;
+                }
+                wait_time
+            };
+            tracing::debug!(?wait_time, "rate limited");
+            tokio::time::sleep(wait_time).await;
+        }
+    }
+}
+
+/// rough number of tokens in the text, openai's rule of thumb is four characters per token.
+pub fn estimate_tokens(text: &str) -> u32 {
+    (text.chars().count() / 4 + 1)
+        .try_into()
+        .unwrap_or(u32::MAX)
+}
@@ -0,0 +1,266 @@
+use tracing_subscriber::{registry::LookupSpan, Layer as _};
+
+const SERVICE_NAME: &str = "sverige-news";
+
+/// spans of other crates are not exported, spans of this crate are exported down to debug level
+const TARGET: &str = "sverige_news";
+
+const MAX_BUFFERED_SPANS: usize = 10_000;
+
+const EXPORT_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+type Buffer = std::sync::Arc<std::sync::Mutex<Vec<ExportedSpan>>>;
+
+pub fn new<S>(endpoint: &url::Url) -> (impl tracing_subscriber::Layer<S>, Exporter)
+where
+    S: tracing::Subscriber + for<'a> LookupSpan<'a>,
+{
+    let buffer = Buffer::default();
+    let layer = Layer {
+        buffer: buffer.clone(),
+    }
+    .with_filter(
+        tracing_subscriber::filter::Targets::new().with_target(TARGET, tracing::Level::DEBUG),
+    );
+    let exporter = Exporter {
+        http_client: reqwest::Client::new(),
+        traces_url: endpoint
+            .join("v1/traces")
+            .expect("failed to build traces url"),
+        buffer,
+    };
+    (layer, exporter)
+}
+
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportedSpan {
+    trace_id: String,
+    span_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parent_span_id: Option<String>,
+    name: String,
+    kind: u8,
+    start_time_unix_nano: String,
+    end_time_unix_nano: String,
+    attributes: Vec<Attribute>,
+    status: Status,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct Attribute {
+    key: String,
+    value: AttributeValue,
+}
+
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AttributeValue {
+    string_value: String,
+}
+
+#[derive(Debug, Default, serde::Serialize)]
+struct Status {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<String>,
+    /// 0 is unset, 2 is error
+    code: u8,
+}
+
+const SPAN_KIND_INTERNAL: u8 = 1;
+const STATUS_CODE_ERROR: u8 = 2;
+
+struct OpenSpan {
+    trace_id: u128,
+    span_id: u64,
+    parent_span_id: Option<u64>,
+    start: std::time::SystemTime,
+    attributes: Vec<Attribute>,
+    status: Status,
+}
+
+struct Visitor<'a>(&'a mut Vec<Attribute>);
+
+impl tracing::field::Visit for Visitor<'_> {
+    fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
+        self.0.push(Attribute::new(field.name(), value.to_string()));
+    }
+
+    fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+        self.0
+            .push(Attribute::new(field.name(), format!("{value:?}")));
+    }
+}
+
+impl Attribute {
+    fn new(key: &str, value: String) -> Self {
+        Self {
+            key: key.to_string(),
+            value: AttributeValue {
+                string_value: value,
+            },
+        }
+    }
+}
+
+pub struct Layer {
+    buffer: Buffer,
+}
+
+impl<S> tracing_subscriber::Layer<S> for Layer
+where
+    S: tracing::Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(
+        &self,
+        attrs: &tracing::span::Attributes<'_>,
+        id: &tracing::span::Id,
+        ctx: tracing_subscriber::layer::Context<'_, S>,
+    ) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        // nearest exported ancestor, spans of other crates are skipped
+        let parent = span.scope().skip(1).find_map(|ancestor| {
+            ancestor
+                .extensions()
+                .get::<OpenSpan>()
+                .map(|parent| (parent.trace_id, parent.span_id))
+        });
+        let mut attributes = Vec::new();
+        attrs.record(&mut Visitor(&mut attributes));
+        span.extensions_mut().insert(OpenSpan {
+            trace_id: parent.map_or_else(rand::random, |(trace_id, _)| trace_id),
+            span_id: rand::random(),
+            parent_span_id: parent.map(|(_, span_id)| span_id),
+            start: std::time::SystemTime::now(),
+            attributes,
+            status: Status::default(),
+        });
+    }
+
+    fn on_record(
+        &self,
+        id: &tracing::span::Id,
+        values: &tracing::span::Record<'_>,
+        ctx: tracing_subscriber::layer::Context<'_, S>,
+    ) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut extensions = span.extensions_mut();
+        if let Some(open) = extensions.get_mut::<OpenSpan>() {
+            values.record(&mut Visitor(&mut open.attributes));
+        }
+    }
+
+    fn on_event(&self, event: &tracing::Event<'_>, ctx: tracing_subscriber::layer::Context<'_, S>) {
+        if *event.metadata().level() != tracing::Level::ERROR {
+            return;
+        }
+        let Some(span) = ctx.event_span(event) else {
+            return;
+        };
+        let Some(span) = span
+            .scope()
+            .find(|span| span.extensions().get::<OpenSpan>().is_some())
+        else {
+            return;
+        };
+        let mut attributes = Vec::new();
+        event.record(&mut Visitor(&mut attributes));
+        let message = attributes
+            .into_iter()
+            .map(|attribute| format!("{}={}", attribute.key, attribute.value.string_value))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let mut extensions = span.extensions_mut();
+        if let Some(open) = extensions.get_mut::<OpenSpan>() {
+            open.status = Status {
+                message: Some(message),
+                code: STATUS_CODE_ERROR,
+            };
+        }
+    }
+
+    fn on_close(&self, id: tracing::span::Id, ctx: tracing_subscriber::layer::Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let Some(open) = span.extensions_mut().remove::<OpenSpan>() else {
+            return;
+        };
+        let mut buffer = self.buffer.lock().expect("failed to lock spans buffer");
+        if buffer.len() >= MAX_BUFFERED_SPANS {
+            return;
+        }
+        buffer.push(ExportedSpan {
+            trace_id: format!("{:032x}", open.trace_id),
+            span_id: format!("{:016x}", open.span_id),
+            parent_span_id: open.parent_span_id.map(|span_id| format!("{span_id:016x}")),
+            name: span.name().to_string(),
+            kind: SPAN_KIND_INTERNAL,
+            start_time_unix_nano: unix_nanos(open.start),
+            end_time_unix_nano: unix_nanos(std::time::SystemTime::now()),
+            attributes: open.attributes,
+            status: open.status,
+        });
+    }
+}
+
+fn unix_nanos(time: std::time::SystemTime) -> String {
+    time.duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos()
+        .to_string()
+}
+
+#[derive(Clone)]
+pub struct Exporter {
+    http_client: reqwest::Client,
+    traces_url: url::Url,
+    buffer: Buffer,
+}
+
+impl Exporter {
+    pub async fn run(self) {
+        let mut interval = tokio::time::interval(EXPORT_INTERVAL);
+        loop {
+            interval.tick().await;
+            self.flush().await;
+        }
+    }
+
+    pub async fn flush(&self) {
+        let spans = std::mem::take(&mut *self.buffer.lock().expect("failed to lock spans buffer"));
+        if spans.is_empty() {
+            return;
+        }
+        let count = spans.len();
+        if let Err(error) = self.export(spans).await {
+            tracing::warn!(%error, count, "failed to export spans");
+        }
+    }
+
+    async fn export(&self, spans: Vec<ExportedSpan>) -> Result<(), Box<dyn std::error::Error>> {
+        let body = serde_json::json!({
+            "resourceSpans": [{
+                "resource": {
+                    "attributes": [Attribute::new("service.name", SERVICE_NAME.to_string())],
+                },
+                "scopeSpans": [{
+                    "scope": { "name": SERVICE_NAME },
+                    "spans": spans,
+                }],
+            }],
+        });
+        self.http_client
+            .post(self.traces_url.clone())
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(serde_json::to_string(&body)?)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
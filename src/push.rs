@@ -0,0 +1,142 @@
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, sqlx::FromRow)]
+pub struct Subscription {
+    pub endpoint: String,
+    #[sqlx(flatten)]
+    pub keys: Keys,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, sqlx::FromRow)]
+pub struct Keys {
+    pub p256dh: String,
+    pub auth: String,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct Notification {
+    pub title: String,
+    pub body: String,
+    pub url: url::Url,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("web push: {0}")]
+    WebPush(#[from] web_push::WebPushError),
+    #[error("http: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("json: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("subscription is gone")]
+    Gone,
+}
+
+#[derive(Clone)]
+pub struct Client {
+    inner: reqwest::Client,
+    signature: web_push::PartialVapidSignatureBuilder,
+    subject: String,
+}
+
+const TTL_SECONDS: u32 = 60 * 60;
+
+/// hosts of push services of browsers, subscriptions elsewhere are not accepted so that the
+/// server does not send requests wherever it is told to. hosts starting with a dot match their
+/// subdomains
+const SERVICE_HOSTS: [&str; 4] = [
+    "fcm.googleapis.com",
+    "updates.push.services.mozilla.com",
+    ".push.apple.com",
+    ".notify.windows.com",
+];
+
+pub fn is_service_endpoint(endpoint: &str) -> bool {
+    let Ok(endpoint) = url::Url::parse(endpoint) else {
+        return false;
+    };
+    let Some(host) = endpoint.host_str() else {
+        return false;
+    };
+    endpoint.scheme() == "https"
+        && SERVICE_HOSTS.iter().any(|service| {
+            if service.starts_with('.') {
+                host.ends_with(service)
+            } else {
+                host == *service
+            }
+        })
+}
+
+#[derive(Clone)]
+pub struct PrivateKey(web_push::PartialVapidSignatureBuilder);
+
+impl std::str::FromStr for PrivateKey {
+    type Err = web_push::WebPushError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        web_push::VapidSignatureBuilder::from_base64_no_sub(value, web_push::URL_SAFE_NO_PAD)
+            .map(Self)
+    }
+}
+
+impl Client {
+    pub fn new(private_key: &PrivateKey, subject: &str) -> Self {
+        // a push service that does not respond must not hold up the others
+        let http_client = reqwest::Client::builder()
+            .connect_timeout(std::time::Duration::from_secs(5))
+            .timeout(std::time::Duration::from_secs(10))
+            .build()
+            .expect("failed to build http client");
+        Self {
+            inner: http_client,
+            signature: private_key.0.clone(),
+            subject: subject.to_string(),
+        }
+    }
+
+    #[tracing::instrument(level = "debug", skip(self, subscription), fields(endpoint = %subscription.endpoint))]
+    pub async fn send(
+        &self,
+        subscription: &Subscription,
+        notification: &Notification,
+    ) -> Result<(), Error> {
+        let subscription_info = web_push::SubscriptionInfo::new(
+            &subscription.endpoint,
+            &subscription.keys.p256dh,
+            &subscription.keys.auth,
+        );
+        let content = serde_json::to_vec(notification)?;
+        let mut builder = web_push::WebPushMessageBuilder::new(&subscription_info);
+        builder.set_payload(web_push::ContentEncoding::Aes128Gcm, &content);
+        builder.set_ttl(TTL_SECONDS);
+        let mut signature = self.signature.clone().add_sub_info(&subscription_info);
+        signature.add_claim("sub", self.subject.as_str());
+        builder.set_vapid_signature(signature.build()?);
+        let message = builder.build()?;
+
+        let mut request = self
+            .inner
+            .post(message.endpoint.to_string())
+            .header("TTL", message.ttl.to_string());
+        if let Some(payload) = message.payload {
+            request = request
+                .header(
+                    reqwest::header::CONTENT_ENCODING,
+                    payload.content_encoding.to_str(),
+                )
+                .header(reqwest::header::CONTENT_TYPE, "application/octet-stream");
+            for (name, value) in payload.crypto_headers {
+                request = request.header(name, value);
+            }
+            request = request.body(payload.content);
+        }
+        let response = request.send().await?;
+        if matches!(
+            response.status(),
+            reqwest::StatusCode::NOT_FOUND | reqwest::StatusCode::GONE
+        ) {
+            return Err(Error::Gone);
+        }
+        response.error_for_status()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,227 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum, sqlx::Type)]
+#[sqlx(rename_all = "lowercase")]
+pub enum Precision {
+    /// little-endian f32s, lossless
+    F32,
+    /// little-endian half precision floats, half the size of f32
+    F16,
+    /// i8s multiplied by a scale, quarter the size of f32
+    Int8,
+}
+
+impl Precision {
+    fn bytes(self) -> usize {
+        match self {
+            Precision::F32 => 4,
+            Precision::F16 => 2,
+            Precision::Int8 => 1,
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("invalid embedding length: {0} bytes, expected a multiple of {1}")]
+    InvalidLength(usize, usize),
+    #[error("scale is required to decode int8 embeddings")]
+    MissingScale,
+}
+
+pub fn encode(value: &[f32], precision: Precision) -> (Vec<u8>, Option<f32>) {
+    match precision {
+        Precision::F32 => (
+            value.iter().flat_map(|value| value.to_le_bytes()).collect(),
+            None,
+        ),
+        Precision::F16 => (
+            value
+                .iter()
+                .flat_map(|value| f32_to_f16(*value).to_le_bytes())
+                .collect(),
+            None,
+        ),
+        Precision::Int8 => {
+            let max = value
+                .iter()
+                .fold(0.0_f32, |max, value| max.max(value.abs()));
+            let scale = if max > 0.0 { max / 127.0 } else { 1.0 };
+            let bytes = value
+                .iter()
+                .flat_map(|value| {
+                    num_traits::ToPrimitive::to_i8(&(value / scale).round())
+                        .expect("scaled value fits in i8")
+                        .to_le_bytes()
+                })
+                .collect();
+            (bytes, Some(scale))
+        }
+    }
+}
+
+pub fn decode(bytes: &[u8], precision: Precision, scale: Option<f32>) -> Result<Vec<f32>, Error> {
+    let chunks = bytes.chunks_exact(precision.bytes());
+    if !chunks.remainder().is_empty() {
+        return Err(Error::InvalidLength(bytes.len(), precision.bytes()));
+    }
+    match precision {
+        Precision::F32 => Ok(chunks
+            .map(|chunk| f32::from_le_bytes(chunk.try_into().expect("chunk of 4 bytes")))
+            .collect()),
+        Precision::F16 => Ok(chunks
+            .map(|chunk| {
+                f16_to_f32(u16::from_le_bytes(
+                    chunk.try_into().expect("chunk of 2 bytes"),
+                ))
+            })
+            .collect()),
+        Precision::Int8 => {
+            let scale = scale.ok_or(Error::MissingScale)?;
+            Ok(chunks
+                .map(|chunk| f32::from(i8::from_le_bytes([chunk[0]])) * scale)
+                .collect())
+        }
+    }
+}
+
+fn f32_to_f16(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = (bits >> 16) & 0x8000;
+    let exponent = i32::try_from((bits >> 23) & 0xff).expect("8 bits fit in i32");
+    let mantissa = bits & 0x7f_ffff;
+
+    let half = if exponent == 0xff {
+        // infinity stays infinity, nan stays nan
+        sign | 0x7c00 | if mantissa == 0 { 0 } else { 0x200 }
+    } else {
+        let exponent = exponent - 127 + 15;
+        if exponent >= 0x1f {
+            // too large, becomes infinity
+            sign | 0x7c00
+        } else if exponent <= 0 {
+            if exponent < -10 {
+                // too small, becomes zero
+                sign
+            } else {
+                // subnormal, implicit leading bit becomes explicit
+                let mantissa = mantissa | 0x80_0000;
+                let shift = u32::try_from(14 - exponent).expect("shift is positive");
+                sign | round(mantissa, shift)
+            }
+        } else {
+            let exponent = u32::try_from(exponent).expect("exponent is positive");
+            // mantissa overflow on rounding carries into the exponent, as it should
+            (sign | (exponent << 10)) + round(mantissa, 13)
+        }
+    };
+    u16::try_from(half).expect("half fits in 16 bits")
+}
+
+fn round(value: u32, shift: u32) -> u32 {
+    let round_bit = 1 << (shift - 1);
+    let shifted = value >> shift;
+    // round up when more than half is cut off, or exactly half and the result is odd
+    if value & round_bit != 0 && value & (3 * round_bit - 1) != 0 {
+        shifted + 1
+    } else {
+        shifted
+    }
+}
+
+fn f16_to_f32(half: u16) -> f32 {
+    let negative = half & 0x8000 != 0;
+    let exponent = (half >> 10) & 0x1f;
+    let mantissa = half & 0x3ff;
+
+    let value = match exponent {
+        0 => f32::from(mantissa) * 2.0_f32.powi(-24),
+        0x1f if mantissa == 0 => f32::INFINITY,
+        0x1f => f32::NAN,
+        _ => f32::from_bits((u32::from(exponent) + 127 - 15) << 23 | u32::from(mantissa) << 13),
+    };
+    if negative {
+        -value
+    } else {
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn embedding() -> Vec<f32> {
+        (0..256_u16)
+            .map(|i| (f32::from(i) * 0.37).sin() * 0.2)
+            .chain([0.0, -0.0, 1.0, -1.0, 0.001, -0.000_01])
+            .collect()
+    }
+
+    fn round_trip(value: &[f32], precision: Precision) -> Vec<f32> {
+        let (bytes, scale) = encode(value, precision);
+        assert_eq!(bytes.len(), value.len() * precision.bytes());
+        decode(&bytes, precision, scale).expect("failed to decode")
+    }
+
+    #[test]
+    fn f32_is_lossless() {
+        let value = embedding();
+        assert_eq!(round_trip(&value, Precision::F32), value);
+    }
+
+    #[test]
+    fn f16_keeps_three_significant_digits() {
+        let value = embedding();
+        for (original, decoded) in value.iter().zip(round_trip(&value, Precision::F16)) {
+            // half of the last bit of a 10 bit mantissa, or of the smallest subnormal
+            let bound = (original.abs() * 2.0_f32.powi(-11)).max(2.0_f32.powi(-25));
+            assert!(
+                (original - decoded).abs() <= bound,
+                "{original} decoded as {decoded}"
+            );
+        }
+    }
+
+    #[test]
+    fn f16_special_values() {
+        let value = [f32::INFINITY, f32::NEG_INFINITY, 1e6, -1e6, 1e-10];
+        let decoded = round_trip(&value, Precision::F16);
+        assert_eq!(
+            decoded,
+            [
+                f32::INFINITY,
+                f32::NEG_INFINITY,
+                f32::INFINITY,
+                f32::NEG_INFINITY,
+                0.0
+            ]
+        );
+        assert!(round_trip(&[f32::NAN], Precision::F16)[0].is_nan());
+    }
+
+    #[test]
+    fn int8_error_is_within_half_a_step() {
+        let value = embedding();
+        let (_, scale) = encode(&value, Precision::Int8);
+        let scale = scale.expect("int8 has a scale");
+        assert!((scale - 1.0 / 127.0).abs() < f32::EPSILON);
+        for (original, decoded) in value.iter().zip(round_trip(&value, Precision::Int8)) {
+            assert!(
+                (original - decoded).abs() <= scale / 2.0 + f32::EPSILON,
+                "{original} decoded as {decoded}"
+            );
+        }
+        assert_eq!(round_trip(&[0.0; 4], Precision::Int8), [0.0; 4]);
+    }
+
+    #[test]
+    fn invalid_input() {
+        assert!(matches!(
+            decode(&[0; 3], Precision::F32, None),
+            Err(Error::InvalidLength(3, 4))
+        ));
+        assert!(matches!(
+            decode(&[0; 4], Precision::Int8, None),
+            Err(Error::MissingScale)
+        ));
+    }
+}
@@ -0,0 +1,137 @@
+use crate::{feeds, id::Id};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Sort {
+    /// groups with more entries first, copies of wire stories do not count
+    #[default]
+    Size,
+    /// groups with the latest entries first
+    Recency,
+    /// groups covered by more different feeds first, so that many updates of a single outlet do
+    /// not outrank a story covered by several. feeds count by their weights.
+    Diversity,
+    /// groups whose articles were clicked the most first
+    Popularity,
+}
+
+impl Sort {
+    pub const ALL: [Sort; 4] = [Sort::Size, Sort::Recency, Sort::Diversity, Sort::Popularity];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Sort::Size => "size",
+            Sort::Recency => "recency",
+            Sort::Diversity => "diversity",
+            Sort::Popularity => "popularity",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FeedWeight {
+    pub feed_id: Id<feeds::Feed>,
+    pub weight: f32,
+}
+
+pub fn parse_feed_weight(value: &str) -> Result<FeedWeight, String> {
+    let (feed_id, weight) = value
+        .split_once('=')
+        .ok_or_else(|| format!("expected <feed id>=<weight>, got {value}"))?;
+    let feed_id = Id::from(feed_id.parse::<u32>().map_err(|error| error.to_string())?);
+    if !feeds::LIST.iter().any(|feed| feed.id == feed_id) {
+        return Err(format!("unknown feed id {feed_id}"));
+    }
+    let weight = weight.parse::<f32>().map_err(|error| error.to_string())?;
+    if !weight.is_finite() || weight < 0.0 {
+        return Err(format!(
+            "weight must be a non-negative number, got {weight}"
+        ));
+    }
+    Ok(FeedWeight { feed_id, weight })
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct FeedWeights(std::collections::HashMap<u32, f32>);
+
+impl FeedWeights {
+    pub fn new(weights: &[FeedWeight]) -> Self {
+        Self(
+            weights
+                .iter()
+                .map(|weight| (u32::from(weight.feed_id), weight.weight))
+                .collect(),
+        )
+    }
+
+    fn get(&self, feed_id: Id<feeds::Feed>) -> f32 {
+        self.0.get(&u32::from(feed_id)).copied().unwrap_or(1.0)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Stats {
+    pub size: usize,
+    pub unique_size: usize,
+    pub diversity: f32,
+    pub latest_published_at: chrono::DateTime<chrono::Utc>,
+    pub clicks: u32,
+}
+
+impl Stats {
+    pub fn new<'a>(
+        entries: impl IntoIterator<
+            Item = (
+                &'a Id<feeds::Feed>,
+                chrono::DateTime<chrono::Utc>,
+                bool,
+                u32,
+            ),
+        >,
+        weights: &FeedWeights,
+    ) -> Self {
+        let mut size = 0;
+        let mut clicks = 0;
+        let mut unique_size = 0;
+        let mut feed_ids = std::collections::BTreeSet::new();
+        let mut latest_published_at = chrono::DateTime::<chrono::Utc>::MIN_UTC;
+        for (feed_id, published_at, is_duplicate, entry_clicks) in entries {
+            size += 1;
+            clicks += entry_clicks;
+            if !is_duplicate {
+                unique_size += 1;
+            }
+            feed_ids.insert(u32::from(*feed_id));
+            latest_published_at = latest_published_at.max(published_at);
+        }
+        Self {
+            size,
+            unique_size,
+            diversity: feed_ids
+                .into_iter()
+                .map(|feed_id| weights.get(Id::from(feed_id)))
+                .sum(),
+            latest_published_at,
+            clicks,
+        }
+    }
+}
+
+/// sorts groups, the most important first. ties are broken by size, then by recency.
+pub fn sort<T>(groups: &mut [T], sort: Sort, stats: impl Fn(&T) -> &Stats) {
+    groups.sort_by(|a, b| {
+        let (a, b) = (stats(a), stats(b));
+        let by_size = b.unique_size.cmp(&a.unique_size).then(b.size.cmp(&a.size));
+        let by_recency = b.latest_published_at.cmp(&a.latest_published_at);
+        match sort {
+            Sort::Size => by_size.then(by_recency),
+            Sort::Recency => by_recency.then(by_size),
+            Sort::Diversity => b
+                .diversity
+                .total_cmp(&a.diversity)
+                .then(by_size)
+                .then(by_recency),
+            Sort::Popularity => b.clicks.cmp(&a.clicks).then(by_size).then(by_recency),
+        }
+    });
+}
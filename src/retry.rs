@@ -0,0 +1,104 @@
+use std::{collections::HashSet, time::Duration};
+
+use reqwest_middleware::{Next, Result};
+use reqwest_retry::Retryable;
+use task_local_extensions::Extensions;
+
+static MAX_RETRY_AFTER: Duration = Duration::from_secs(5 * 60);
+
+pub struct Middleware {
+    status_codes: HashSet<reqwest::StatusCode>,
+    max_retries: u32,
+}
+
+impl Middleware {
+    pub fn new(status_codes: Vec<reqwest::StatusCode>, max_retries: u32) -> Self {
+        Self {
+            status_codes: status_codes.into_iter().collect(),
+            max_retries,
+        }
+    }
+
+    fn is_transient(&self, result: &Result<reqwest::Response>) -> bool {
+        match result {
+            Ok(response) => self.status_codes.contains(&response.status()),
+            Err(error) => matches!(
+                reqwest_retry::default_on_request_failure(error),
+                Some(Retryable::Transient)
+            ),
+        }
+    }
+
+    async fn execute_with_retry<'a>(
+        &'a self,
+        request: reqwest::Request,
+        next: Next<'a>,
+        extensions: &'a mut Extensions,
+    ) -> Result<reqwest::Response> {
+        let mut past_retries = 0;
+        loop {
+            // requests with streaming bodies can not be retried
+            let Some(duplicate_request) = request.try_clone() else {
+                return next.run(request, extensions).await;
+            };
+
+            let result = next.clone().run(duplicate_request, extensions).await;
+            if past_retries >= self.max_retries || !self.is_transient(&result) {
+                return result;
+            }
+
+            let wait_time = match result.as_ref().ok().and_then(retry_after) {
+                Some(retry_after) if retry_after > MAX_RETRY_AFTER => return result,
+                Some(retry_after) => retry_after,
+                None => Duration::from_secs(2_u64.pow(past_retries)),
+            };
+            tracing::warn!(
+                attempt = past_retries + 1,
+                ?wait_time,
+                "retrying request to {}",
+                request.url()
+            );
+            tokio::time::sleep(wait_time).await;
+            past_retries += 1;
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl reqwest_middleware::Middleware for Middleware {
+    async fn handle(
+        &self,
+        request: reqwest::Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> Result<reqwest::Response> {
+        self.execute_with_retry(request, next, extensions).await
+    }
+}
+
+/// parses `Retry-After`, which is either a number of seconds or a date. openai also sends
+/// `retry-after-ms` with more precision.
+fn retry_after(response: &reqwest::Response) -> Option<Duration> {
+    let headers = response.headers();
+    if let Some(milliseconds) = headers
+        .get("retry-after-ms")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<f64>().ok())
+    {
+        // waits that do not fit a duration are longer than any that is retried
+        return Some(
+            Duration::try_from_secs_f64(milliseconds.max(0.0) / 1000.0).unwrap_or(Duration::MAX),
+        );
+    }
+
+    let value = headers.get(reqwest::header::RETRY_AFTER)?.to_str().ok()?;
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    Some(
+        (date.with_timezone(&chrono::Utc) - chrono::Utc::now())
+            .to_std()
+            .unwrap_or_default(),
+    )
+}
@@ -0,0 +1,42 @@
+use nix::sys::signal::{SigSet, Signal};
+
+static SENDER: once_cell::sync::OnceCell<tokio::sync::watch::Sender<bool>> =
+    once_cell::sync::OnceCell::new();
+
+const INTERRUPTED: i32 = 130;
+
+/// starts listening for signals. must be called before any other thread is spawned, so that
+/// they inherit the blocked signals.
+pub fn listen() -> Result<(), nix::Error> {
+    let mut signals = SigSet::empty();
+    signals.add(Signal::SIGINT);
+    signals.add(Signal::SIGTERM);
+    signals.thread_block()?;
+
+    let sender = SENDER.get_or_init(|| tokio::sync::watch::channel(false).0);
+    std::thread::spawn(move || {
+        let mut received = false;
+        while let Ok(signal) = signals.wait() {
+            if received || sender.receiver_count() == 0 {
+                tracing::info!(signal = signal.as_str(), "exiting");
+                std::process::exit(INTERRUPTED);
+            }
+            tracing::info!(
+                signal = signal.as_str(),
+                "shutting down, send again to exit immediately"
+            );
+            received = true;
+            sender.send_replace(true);
+        }
+    });
+    Ok(())
+}
+
+pub async fn signal() {
+    let mut receiver = SENDER
+        .get()
+        .expect("shutdown::listen must be called first")
+        .subscribe();
+    // sender lives in a static, so it is never dropped
+    let _ = receiver.wait_for(|stopped| *stopped).await;
+}
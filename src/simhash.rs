@@ -0,0 +1,42 @@
+//! simhash fingerprints of texts. texts that differ in a few words have fingerprints that differ
+//! in a few bits, so that copies of the same wire story can be found across outlets.
+
+pub const MAX_DISTANCE: u32 = 3;
+
+const SHINGLE_SIZE: usize = 3;
+
+/// shorter texts are too similar by chance to tell copies apart
+const MIN_WORDS: usize = 8;
+
+pub fn compute(text: &str) -> Option<u64> {
+    let words = text.split_whitespace().collect::<Vec<_>>();
+    if words.len() < MIN_WORDS {
+        return None;
+    }
+
+    let mut weights = [0_i32; 64];
+    for shingle in words.windows(SHINGLE_SIZE) {
+        // md5 rather than the std hasher, which is not stable across releases
+        let digest = md5::compute(shingle.join(" "));
+        let hash = u64::from_le_bytes(digest.0[..8].try_into().expect("md5 digest is 16 bytes"));
+        for (bit, weight) in weights.iter_mut().enumerate() {
+            if hash >> bit & 1 == 1 {
+                *weight += 1;
+            } else {
+                *weight -= 1;
+            }
+        }
+    }
+
+    Some(
+        weights
+            .iter()
+            .enumerate()
+            .filter(|(_, weight)| **weight > 0)
+            .fold(0, |fingerprint, (bit, _)| fingerprint | 1 << bit),
+    )
+}
+
+pub fn distance(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
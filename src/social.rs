@@ -0,0 +1,71 @@
+use crate::{bluesky, clustering, id::Id, mastodon};
+
+type Error = Box<dyn std::error::Error + 'static + Send + Sync>;
+
+/// social network groups are announced on, each group is announced once per network
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[sqlx(rename_all = "lowercase")]
+pub enum Network {
+    Mastodon,
+    Bluesky,
+}
+
+#[derive(Debug)]
+pub struct Announcement {
+    pub group_id: Id<clustering::ReportGroup>,
+    pub title: String,
+    pub url: url::Url,
+    pub sources: usize,
+    pub content_warning: Option<String>,
+}
+
+#[derive(Clone)]
+pub enum Poster {
+    Mastodon(mastodon::Client),
+    Bluesky(bluesky::Client),
+}
+
+impl Poster {
+    pub fn network(&self) -> Network {
+        match self {
+            Poster::Mastodon(_) => Network::Mastodon,
+            Poster::Bluesky(_) => Network::Bluesky,
+        }
+    }
+
+    pub async fn post(&self, announcement: &Announcement) -> Result<(), Error> {
+        let footer = format!("{} sources: {}", announcement.sources, announcement.url);
+        match self {
+            Poster::Mastodon(client) => {
+                let status = mastodon::Status {
+                    text: format!("{}\n\n{footer}", announcement.title),
+                    spoiler_text: announcement.content_warning.clone(),
+                    language: "en",
+                };
+                client
+                    .post(&status, &format!("group-{}", announcement.group_id))
+                    .await
+            }
+            Poster::Bluesky(client) => {
+                // bluesky has no content warnings for text, they are written before the title
+                let title = match &announcement.content_warning {
+                    Some(warning) => format!("CW: {warning}\n\n{}", announcement.title),
+                    None => announcement.title.clone(),
+                };
+                let max_title_len = bluesky::MAX_LENGTH.saturating_sub(footer.chars().count() + 2);
+                let title = if title.chars().count() > max_title_len {
+                    let truncated = title
+                        .chars()
+                        .take(max_title_len.saturating_sub(1))
+                        .collect::<String>();
+                    format!("{}…", truncated.trim_end())
+                } else {
+                    title
+                };
+                client
+                    .post(&format!("{title}\n\n{footer}"), &announcement.url)
+                    .await
+            }
+        }
+    }
+}
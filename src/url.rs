@@ -1,14 +1,59 @@
 use std::str::FromStr;
 
+/// href of an entry. it is canonical, so that an article linked with tracking parameters or with
+/// a different scheme is recognized as the same entry.
 #[derive(Clone)]
 pub struct Url(url::Url);
 
+const TRACKING_PARAMS: &[&str] = &[
+    "fbclid", "gclid", "dclid", "msclkid", "mc_cid", "mc_eid", "cmpid", "xtor", "igshid",
+];
+
+fn canonicalize(mut url: url::Url) -> url::Url {
+    if url.scheme() == "http" {
+        // only fails for urls that can not have https scheme, they are kept as they are
+        let _ = url.set_scheme("https");
+    }
+    url.set_fragment(None);
+
+    // some sites use `ref` for real parameters, it is only dropped when it names the feed
+    let is_tracking = |name: &str, value: &str| {
+        name.starts_with("utm_")
+            || TRACKING_PARAMS.contains(&name)
+            || (name == "ref" && matches!(value, "rss" | "feed"))
+    };
+    // other parameters are re-encoded only when there is something to drop
+    if url
+        .query_pairs()
+        .any(|(name, value)| is_tracking(&name, &value))
+    {
+        let params = url
+            .query_pairs()
+            .filter(|(name, value)| !is_tracking(name, value))
+            .map(|(name, value)| (name.into_owned(), value.into_owned()))
+            .collect::<Vec<_>>();
+        if params.is_empty() {
+            url.set_query(None);
+        } else {
+            url.query_pairs_mut().clear().extend_pairs(params);
+        }
+    } else if url.query() == Some("") {
+        url.set_query(None);
+    }
+
+    if url.path().len() > 1 && url.path().ends_with('/') {
+        let path = url.path().trim_end_matches('/').to_string();
+        url.set_path(&path);
+    }
+    url
+}
+
 impl FromStr for Url {
     type Err = url::ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let url = url::Url::parse(s)?;
-        Ok(Url(url))
+        Ok(Url(canonicalize(url)))
     }
 }
 
@@ -26,7 +71,7 @@ impl std::fmt::Display for Url {
 
 impl From<url::Url> for Url {
     fn from(url: url::Url) -> Self {
-        Url(url)
+        Url(canonicalize(url))
     }
 }
 
@@ -60,3 +105,58 @@ impl sqlx::Decode<'_, sqlx::sqlite::Sqlite> for Url {
         Ok(Url(url))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn canonical(href: &str) -> String {
+        href.parse::<Url>().expect("valid url").to_string()
+    }
+
+    #[test]
+    fn drops_tracking_params() {
+        assert_eq!(
+            canonical("https://example.com/a?utm_source=rss&id=1&fbclid=x"),
+            "https://example.com/a?id=1"
+        );
+        assert_eq!(
+            canonical("https://example.com/a?utm_medium=social"),
+            "https://example.com/a"
+        );
+        assert_eq!(canonical("https://example.com/a?"), "https://example.com/a");
+    }
+
+    #[test]
+    fn keeps_ref_unless_it_names_the_feed() {
+        assert_eq!(
+            canonical("https://example.com/a?ref=rss"),
+            "https://example.com/a"
+        );
+        assert_eq!(
+            canonical("https://example.com/a?ref=feed"),
+            "https://example.com/a"
+        );
+        assert_eq!(
+            canonical("https://example.com/a?ref=sport"),
+            "https://example.com/a?ref=sport"
+        );
+    }
+
+    #[test]
+    fn keeps_other_params_as_they_are() {
+        assert_eq!(
+            canonical("https://example.com/a?b=%20&a=1"),
+            "https://example.com/a?b=%20&a=1"
+        );
+    }
+
+    #[test]
+    fn normalizes_scheme_fragment_and_trailing_slash() {
+        assert_eq!(
+            canonical("http://example.com/a/#top"),
+            "https://example.com/a"
+        );
+        assert_eq!(canonical("http://example.com/"), "https://example.com/");
+    }
+}
@@ -1,10 +1,37 @@
-use axum::extract::{Path, State};
-use axum::http::header::CONTENT_TYPE;
-use axum::http::Uri;
+mod atom;
+mod auth;
+mod caching;
+mod diff;
+mod entries_cache;
+mod export;
+#[cfg(feature = "grpc")]
+mod grpc;
+mod json_feed;
+mod language;
+mod oembed;
+mod preferences;
+mod proxy;
+mod rate_limit;
+#[cfg(feature = "tls")]
+mod tls;
+mod visits;
+
+pub use auth::ApiKey;
+pub use proxy::Network;
+#[cfg(feature = "tls")]
+pub use tls::Tls;
+
+use axum::extract::{Path, Query, State};
+use axum::http::header::{
+    CACHE_CONTROL, CONTENT_LANGUAGE, CONTENT_TYPE, LOCATION, SET_COOKIE, VARY,
+};
+use axum::http::{StatusCode, Uri};
+use axum::response::sse::{Event, KeepAlive, Sse};
 use axum::response::{Html, IntoResponse};
-use axum::routing::get;
+use axum::routing::{get, post};
 use axum::Router;
-use chrono::TimeZone;
+use chrono::{Datelike, TimeZone};
+use futures::StreamExt;
 use rust_embed::RustEmbed;
 use tower_http::compression::CompressionLayer;
 use tower_http::trace::{self, TraceLayer};
@@ -12,22 +39,172 @@ use tracing::Level;
 
 use crate::clustering::ReportGroup;
 use crate::id::Id;
-use crate::{clustering, db, feeds};
+use crate::{clustering, db, feeds, persisted::Persisted, providers, push, ranking, shutdown};
+
+use language::Language;
+use preferences::HiddenFeeds;
+use visits::Visit;
 
 #[derive(Clone)]
 struct AppState {
     db: db::Client,
+    base_url: url::Url,
+    // static copies of the site have no search, and no embedder for it
+    embedder: Option<std::sync::Arc<dyn providers::Embedder>>,
+    search_window: chrono::Duration,
+    default_sort: ranking::Sort,
+    feed_weights: std::sync::Arc<ranking::FeedWeights>,
+    deprioritize_paywalled: bool,
+    hidden_categories: std::sync::Arc<Vec<clustering::Category>>,
+    entries_cache: std::sync::Arc<entries_cache::Cache>,
+    report_updates: tokio::sync::watch::Receiver<Option<chrono::DateTime<chrono::Utc>>>,
+    vapid_public_key: Option<std::sync::Arc<str>>,
+    preferences_key: std::sync::Arc<preferences::Key>,
+    count_clicks: bool,
+}
+
+pub struct Config {
+    pub search_window: chrono::Duration,
+    pub default_sort: ranking::Sort,
+    pub feed_weights: ranking::FeedWeights,
+    pub deprioritize_paywalled: bool,
+    pub hidden_categories: Vec<clustering::Category>,
+    pub index_cache_ttl: std::time::Duration,
+    pub report_updates: Option<tokio::sync::watch::Receiver<Option<chrono::DateTime<chrono::Utc>>>>,
+    pub rate_limit_per_second: Option<f64>,
+    pub rate_limit_burst: u32,
+    pub trusted_proxies: Vec<proxy::Network>,
+    /// keys admin pages are protected with, admin pages are not served when there are none
+    pub api_keys: Vec<ApiKey>,
+    pub vapid_public_key: Option<String>,
+    #[cfg(feature = "grpc")]
+    pub grpc_address: Option<String>,
+    #[cfg(feature = "tls")]
+    pub tls: Option<Tls>,
+    pub languages: Vec<feeds::LanguageCode>,
+    pub cookie_secret: Option<String>,
+}
+
+impl AppState {
+    fn new(
+        db: db::Client,
+        base_url: &url::Url,
+        embedder: Option<std::sync::Arc<dyn providers::Embedder>>,
+        config: Config,
+    ) -> Self {
+        language::init(config.languages);
+        Self {
+            db,
+            base_url: base_url.clone(),
+            embedder,
+            search_window: config.search_window,
+            default_sort: config.default_sort,
+            feed_weights: std::sync::Arc::new(config.feed_weights),
+            deprioritize_paywalled: config.deprioritize_paywalled,
+            hidden_categories: std::sync::Arc::new(config.hidden_categories),
+            entries_cache: std::sync::Arc::new(entries_cache::Cache::new(config.index_cache_ttl)),
+            report_updates: config
+                .report_updates
+                .unwrap_or_else(|| tokio::sync::watch::channel(None).1),
+            vapid_public_key: config.vapid_public_key.map(std::sync::Arc::from),
+            preferences_key: std::sync::Arc::new(preferences::Key::new(
+                config.cookie_secret.as_deref(),
+            )),
+            count_clicks: true,
+        }
+    }
 }
 
 #[tracing::instrument(level = "debug", skip_all)]
-pub async fn serve(db: db::Client, address: &str) -> Result<(), Box<dyn std::error::Error>> {
-    let state = AppState { db };
-    let router = Router::new()
+pub async fn serve(
+    db: db::Client,
+    address: &str,
+    base_url: &url::Url,
+    embedder: std::sync::Arc<dyn providers::Embedder>,
+    config: Config,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let limiter = config.rate_limit_per_second.map(|per_second| {
+        std::sync::Arc::new(rate_limit::Limiter::new(
+            per_second,
+            config.rate_limit_burst,
+        ))
+    });
+    #[cfg(feature = "tls")]
+    let tls = config.tls.clone();
+    #[cfg(feature = "tls")]
+    let scheme = if tls.is_some() {
+        proxy::Scheme::Https
+    } else {
+        proxy::Scheme::Http
+    };
+    #[cfg(not(feature = "tls"))]
+    let scheme = proxy::Scheme::Http;
+    let proxies = std::sync::Arc::new(proxy::TrustedProxies::new(
+        config.trusted_proxies.clone(),
+        scheme,
+    ));
+    let api_keys = std::sync::Arc::new(auth::ApiKeys::new(config.api_keys.clone()));
+    #[cfg(feature = "grpc")]
+    let grpc_address = config.grpc_address.clone();
+    let state = AppState::new(db, base_url, Some(embedder), config);
+    #[cfg(feature = "grpc")]
+    let grpc_state = state.clone();
+    // admin pages are not served at all without keys to protect them with
+    let admin = if api_keys.is_empty() {
+        Router::new()
+    } else {
+        Router::new()
+            .route("/status/jobs", get(render_job_runs))
+            .route("/status/filters", get(render_filter_hits))
+            .route("/status/tasks/retry", post(retry_failed_tasks))
+            .route_layer(axum::middleware::from_fn_with_state(
+                api_keys,
+                auth::require_key,
+            ))
+    };
+    let mut router = Router::new()
         .route("/", get(render_index))
+        .route("/feed.xml", get(render_atom_feed))
+        .route("/feed.json", get(render_json_feed))
+        .route("/breaking/feed.json", get(render_breaking_json_feed))
         .route("/:year/:month/:day", get(render_index_for_date))
+        .route("/:year/:month/:day/briefing.mp3", get(serve_briefing))
+        .route("/archive", get(render_archive))
+        .route("/archive/:year/:month", get(render_archive_for_month))
+        .route("/search", get(render_search))
+        .route("/lattlast", get(render_lattlast))
+        .route("/week", get(render_week))
+        .route("/other", get(render_other))
+        .route("/other/feed.json", get(render_other_json_feed))
+        .route("/entries/:id", get(render_entry))
+        .route("/out/:id", get(follow_entry))
         .route("/groups/:id", get(render_group))
+        .route("/groups/:id/feed.xml", get(render_group_atom_feed))
+        .route("/embed/groups/:id", get(render_embedded_group))
+        .route("/oembed", get(render_oembed))
+        .route("/status", get(render_status))
+        .route("/status/popular", get(render_popular))
+        .route(
+            "/preferences",
+            get(render_preferences).post(save_preferences),
+        )
+        .merge(admin)
+        .route("/events", get(stream_events))
+        .route(
+            "/push/subscriptions",
+            post(subscribe_push).delete(unsubscribe_push),
+        )
         .fallback(serve_asset)
         .with_state(state)
+        .layer(axum::middleware::from_fn(caching::not_modified))
+        .layer(axum::middleware::from_fn(language::remember));
+    if let Some(limiter) = limiter {
+        router = router.layer(axum::middleware::from_fn_with_state(
+            limiter,
+            rate_limit::limit,
+        ));
+    }
+    let router = router
         .layer(
             CompressionLayer::new()
                 .br(true)
@@ -37,18 +214,102 @@ pub async fn serve(db: db::Client, address: &str) -> Result<(), Box<dyn std::err
         )
         .layer(
             TraceLayer::new_for_http()
-                .make_span_with(trace::DefaultMakeSpan::new().level(Level::DEBUG))
+                .make_span_with(make_request_span)
                 .on_response(trace::DefaultOnResponse::new().level(Level::DEBUG)),
-        );
-    let listener = tokio::net::TcpListener::bind(address).await?;
-    tracing::info!("listening on {}", listener.local_addr().unwrap());
-    axum::serve(listener, router).await?;
-    Ok(())
+        )
+        .layer(axum::middleware::from_fn_with_state(
+            proxies,
+            proxy::resolve,
+        ));
+    let http = async move {
+        #[cfg(feature = "tls")]
+        if let Some(tls) = tls {
+            return tls::serve(address, &tls, router).await;
+        }
+        let listener = tokio::net::TcpListener::bind(address).await?;
+        tracing::info!("listening on {}", listener.local_addr().unwrap());
+        // clients are resolved from addresses of connections
+        axum::serve(
+            listener,
+            router.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+        )
+        .with_graceful_shutdown(shutdown::signal())
+        .await?;
+        Ok::<_, Box<dyn std::error::Error>>(())
+    };
+    #[cfg(feature = "grpc")]
+    if let Some(grpc_address) = grpc_address {
+        futures::try_join!(http, grpc::serve(grpc_state, &grpc_address))?;
+        return Ok(());
+    }
+    http.await
+}
+
+fn make_request_span(request: &axum::extract::Request) -> tracing::Span {
+    let client = request.extensions().get::<proxy::Client>();
+    tracing::debug_span!(
+        "request",
+        method = %request.method(),
+        uri = %request.uri(),
+        version = ?request.version(),
+        client.address = client.map(|client| tracing::field::display(client.address)),
+        client.scheme = client.map(|client| tracing::field::display(client.scheme)),
+    )
+}
+
+const REPORT_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// checks the database for reports generated or updated by another process, for web servers
+/// that run without background jobs
+pub fn poll_report_updates(
+    db: db::Client,
+) -> tokio::sync::watch::Receiver<Option<chrono::DateTime<chrono::Utc>>> {
+    let (sender, receiver) = tokio::sync::watch::channel(None);
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(REPORT_POLL_INTERVAL);
+        while !sender.is_closed() {
+            interval.tick().await;
+            match db.find_latest_report().await {
+                Ok(report) => {
+                    let updated_at = report.as_ref().map(caching::report_updated_at);
+                    sender.send_if_modified(|current| {
+                        let modified = *current != updated_at;
+                        *current = updated_at;
+                        modified
+                    });
+                }
+                Err(error) => tracing::error!(%error, "failed to find latest report"),
+            }
+        }
+    });
+    receiver
+}
+
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn export_site(
+    db: db::Client,
+    base_url: &url::Url,
+    config: Config,
+    out: &std::path::Path,
+    days: u32,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let state = AppState::new(db, base_url, None, config);
+    export::run(&state, out, days).await
 }
 
 struct Page {
     title: String,
     body: maud::Markup,
+    language: feeds::LanguageCode,
+    description: Option<String>,
+    image_href: Option<String>,
+    canonical_href: Option<url::Url>,
+    /// set on pages that only change with new reports, so that they can be cached
+    validators: Option<caching::Validators>,
+    live: bool,
+    vapid_public_key: Option<std::sync::Arc<str>>,
+    oembed_href: Option<url::Url>,
+    embedded: bool,
 }
 
 impl Page {
@@ -56,61 +317,218 @@ impl Page {
         Self {
             title: title.to_string(),
             body,
+            language: feeds::LanguageCode::EN,
+            description: None,
+            image_href: None,
+            canonical_href: None,
+            validators: None,
+            live: false,
+            vapid_public_key: None,
+            oembed_href: None,
+            embedded: false,
         }
     }
+
+    pub fn with_language(mut self, language: feeds::LanguageCode) -> Self {
+        self.language = language;
+        self
+    }
+
+    pub fn with_description(mut self, description: Option<&str>) -> Self {
+        self.description = description.map(ToString::to_string);
+        self
+    }
+
+    pub fn with_image_href(mut self, image_href: Option<String>) -> Self {
+        self.image_href = image_href;
+        self
+    }
+
+    pub fn with_canonical_href(mut self, canonical_href: url::Url) -> Self {
+        self.canonical_href = Some(canonical_href);
+        self
+    }
+
+    pub fn with_validators(mut self, validators: Option<caching::Validators>) -> Self {
+        self.validators = validators;
+        self
+    }
+
+    pub fn with_live(mut self, live: bool) -> Self {
+        self.live = live;
+        self
+    }
+
+    pub fn with_vapid_public_key(mut self, vapid_public_key: Option<std::sync::Arc<str>>) -> Self {
+        self.vapid_public_key = vapid_public_key;
+        self
+    }
+
+    pub fn with_oembed_href(mut self, oembed_href: url::Url) -> Self {
+        self.oembed_href = Some(oembed_href);
+        self
+    }
+
+    pub fn with_embedded(mut self, embedded: bool) -> Self {
+        self.embedded = embedded;
+        self
+    }
 }
 
-impl axum::response::IntoResponse for Page {
-    fn into_response(self) -> axum::response::Response {
-        let page = maud::html! {
+impl Page {
+    fn render(&self) -> maud::Markup {
+        maud::html! {
             (maud::DOCTYPE)
-            head {
-                meta charset="utf-8";
-                meta name="viewport" content="width=device-width, initial-scale=1";
-                link rel="stylesheet" href="/css/pico.classless.yellow.min.css";
-                title { (self.title) }
-            }
-            body {
-                main {
-                    (self.body)
+            html lang=(self.language) dir=(language::direction(&self.language)) {
+                head {
+                    meta charset="utf-8";
+                    meta name="viewport" content="width=device-width, initial-scale=1";
+                    link rel="stylesheet" href=(asset_href("css/pico.classless.yellow.min.css"));
+                    link rel="alternate" type="application/atom+xml" title="Today in Sweden" href="/feed.xml";
+                    link rel="alternate" type="application/feed+json" title="Today in Sweden" href="/feed.json";
+                    title { (self.title) }
+                    @if let Some(oembed_href) = &self.oembed_href {
+                        link rel="alternate" type="application/json+oembed" href=(oembed_href.as_str()) title=(self.title);
+                    }
+                    @if self.embedded {
+                        // links open outside of the iframe
+                        base target="_blank";
+                    }
+                    @if self.live {
+                        script src=(asset_href("js/live.js")) defer {}
+                    }
+                    meta property="og:site_name" content="Today in Sweden";
+                    meta property="og:type" content="website";
+                    meta property="og:title" content=(self.title);
+                    meta name="twitter:title" content=(self.title);
+                    @if let Some(canonical_href) = &self.canonical_href {
+                        link rel="canonical" href=(canonical_href.as_str());
+                        meta property="og:url" content=(canonical_href.as_str());
+                    }
+                    @if let Some(description) = &self.description {
+                        meta name="description" content=(description);
+                        meta property="og:description" content=(description);
+                        meta name="twitter:description" content=(description);
+                    }
+                    @if let Some(image_href) = &self.image_href {
+                        meta property="og:image" content=(image_href);
+                        meta name="twitter:image" content=(image_href);
+                        meta name="twitter:card" content="summary_large_image";
+                    } @else {
+                        meta name="twitter:card" content="summary";
+                    }
                 }
-            }
-            footer {
-                nav {
-                    ul {
-                        li { a href="/about.html" { "About" } }
-                        li { a href="https://github.com/ngalaiko/sverige-news" { "GitHub" } }
+                body {
+                    main {
+                        (self.body)
+                    }
+                }
+                @if !self.embedded {
+                    footer {
+                        @if let Some(vapid_public_key) = &self.vapid_public_key {
+                            // shown by the script when the browser supports notifications
+                            button id="push" hidden data-vapid-public-key=(vapid_public_key) { "Notify me of breaking news" }
+                            script src=(asset_href("js/push.js")) defer {}
+                        }
+                        nav {
+                            ul {
+                                li { a href="/about.html" { "About" } }
+                                li { a href="/week" { "This week" } }
+                                li { a href="/archive" { "Archive" } }
+                                li { a href="/lattlast" lang="sv" { "Lättläst" } }
+                                li { a href="/other" { "Other news" } }
+                                li { a href="/search" { "Search" } }
+                                li { a href="/preferences" { "Preferences" } }
+                                li { a href="/status" { "Status" } }
+                                li { a href="https://github.com/ngalaiko/sverige-news" { "GitHub" } }
+                            }
+                            ul {
+                                @for language in language::available() {
+                                    li { a href=(format!("?lang={language}")) lang=(language) aria-current=[(self.language == *language).then_some("true")] { (language.native_name()) } }
+                                }
+                            }
+                        }
                     }
                 }
             }
-        };
-        Html(page.into_string()).into_response()
+        }
+    }
+}
+
+impl axum::response::IntoResponse for Page {
+    fn into_response(self) -> axum::response::Response {
+        let mut response = (
+            [
+                (CONTENT_LANGUAGE, self.language.to_string()),
+                (VARY, "Accept-Language, Cookie".to_string()),
+            ],
+            Html(self.render().into_string()),
+        )
+            .into_response();
+        if let Some(validators) = &self.validators {
+            validators.apply(response.headers_mut());
+        }
+        response
     }
 }
 
-struct ErrorPage(Box<dyn std::error::Error>);
+enum ErrorPage {
+    NotFound,
+    Internal(Box<dyn std::error::Error + Send + Sync>),
+}
 
 impl From<sqlx::Error> for ErrorPage {
     fn from(value: sqlx::Error) -> Self {
-        Self(Box::new(value))
+        match value {
+            sqlx::Error::RowNotFound => Self::NotFound,
+            error => Self::Internal(Box::new(error)),
+        }
+    }
+}
+
+impl From<Box<dyn std::error::Error + Send + Sync>> for ErrorPage {
+    fn from(value: Box<dyn std::error::Error + Send + Sync>) -> Self {
+        Self::Internal(value)
     }
 }
 
 impl From<NotFound> for ErrorPage {
-    fn from(value: NotFound) -> Self {
-        Self(Box::new(value))
+    fn from(_: NotFound) -> Self {
+        Self::NotFound
     }
 }
 
 impl axum::response::IntoResponse for ErrorPage {
     fn into_response(self) -> axum::response::Response {
-        Page::new(
-            "Error",
-            maud::html! {
-                p { (self.0) }
-            },
-        )
-        .into_response()
+        match self {
+            Self::NotFound => (
+                StatusCode::NOT_FOUND,
+                Page::new(
+                    "Not found",
+                    maud::html! {
+                        h2 { "Not found" }
+                        p { "There is nothing here." }
+                        p { a href="/" { "Back to main page" } }
+                    },
+                ),
+            )
+                .into_response(),
+            Self::Internal(error) => {
+                // details are only logged, they are not for the readers
+                tracing::error!(%error, "failed to render page");
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Page::new(
+                        "Error",
+                        maud::html! {
+                            h2 { "Something went wrong" }
+                            p { "Please try again later." }
+                        },
+                    ),
+                )
+                    .into_response()
+            }
+        }
     }
 }
 
@@ -123,6 +541,48 @@ struct GroupParams {
     id: Id<clustering::ReportGroup>,
 }
 
+#[derive(Clone, Default, serde::Deserialize)]
+struct IndexParams {
+    country: Option<feeds::Country>,
+    category: Option<clustering::Category>,
+    sort: Option<ranking::Sort>,
+    hours: Option<u32>,
+}
+
+const RIGHT_NOW_HOURS: u32 = 3;
+
+impl IndexParams {
+    fn href(&self) -> String {
+        let mut query = url::form_urlencoded::Serializer::new(String::new());
+        if let Some(country) = &self.country {
+            query.append_pair("country", country.as_str());
+        }
+        if let Some(category) = self.category {
+            query.append_pair("category", category.as_str());
+        }
+        if let Some(sort) = self.sort {
+            query.append_pair("sort", sort.as_str());
+        }
+        if let Some(hours) = self.hours {
+            query.append_pair("hours", &hours.to_string());
+        }
+        format!("?{}", query.finish())
+    }
+
+    fn matches(&self, entry: &GroupEntryView) -> bool {
+        let feed = feeds::LIST
+            .iter()
+            .find(|f| f.id == entry.feed_id)
+            .expect("feed must exist");
+        self.country
+            .as_ref()
+            .map_or(true, |country| feed.value.country == *country)
+            && self
+                .category
+                .map_or(true, |category| entry.category == Some(category))
+    }
+}
+
 #[derive(serde::Deserialize)]
 struct DateParams {
     year: i32,
@@ -130,37 +590,117 @@ struct DateParams {
     day: u32,
 }
 
-async fn render_index(State(state): State<AppState>) -> Result<Page, ErrorPage> {
-    let date = chrono_tz::Europe::Stockholm
+async fn render_index(
+    Query(index_params): Query<IndexParams>,
+    Language(language): Language,
+    hidden_feeds: HiddenFeeds,
+    visit: Visit,
+    State(state): State<AppState>,
+) -> Result<axum::response::Response, ErrorPage> {
+    let latest_report_id = state.db.find_latest_report().await?.map(|report| report.id);
+    let page = render_entries(
+        state,
+        today(),
+        &index_params,
+        language,
+        &hidden_feeds,
+        visit.since_report_id,
+    )
+    .await?;
+    let mut response = page.into_response();
+    if let Some(cookie) = latest_report_id.and_then(|id| visit.cookie(id, chrono::Utc::now())) {
+        response.headers_mut().append(SET_COOKIE, cookie);
+        caching::set_private(response.headers_mut());
+    }
+    Ok(response)
+}
+
+async fn serve_briefing(
+    Path(params): Path<DateParams>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, ErrorPage> {
+    let date =
+        chrono::NaiveDate::from_ymd_opt(params.year, params.month, params.day).ok_or(NotFound)?;
+    let audio = state
+        .db
+        .find_briefing_audio_by_date(date)
+        .await?
+        .ok_or(NotFound)?;
+    Ok((
+        [
+            (CONTENT_TYPE, "audio/mpeg"),
+            // briefings are linked by versioned urls
+            (CACHE_CONTROL, "public, max-age=300"),
+        ],
+        audio,
+    ))
+}
+
+fn today() -> chrono::NaiveDate {
+    SWEDEN_TZ
         .from_utc_datetime(&chrono::Utc::now().naive_utc())
-        .date_naive();
-    render_entries(state, date).await
+        .date_naive()
 }
 
 async fn render_index_for_date(
     Path(params): Path<DateParams>,
+    Query(index_params): Query<IndexParams>,
+    Language(language): Language,
+    hidden_feeds: HiddenFeeds,
     State(state): State<AppState>,
 ) -> Result<Page, ErrorPage> {
     let date =
         chrono::NaiveDate::from_ymd_opt(params.year, params.month, params.day).ok_or(NotFound)?;
-    render_entries(state, date).await
+    // visits are only tracked on the index of today, older days do not change
+    render_entries(state, date, &index_params, language, &hidden_feeds, None).await
 }
 
-async fn render_entries(state: AppState, date: chrono::NaiveDate) -> Result<Page, ErrorPage> {
-    let entries = state
-        .db
-        .list_report_group_entries_by_date_lang_code(date, &feeds::LanguageCode::EN)
-        .await?;
+async fn render_entries(
+    state: AppState,
+    date: chrono::NaiveDate,
+    params: &IndexParams,
+    language: feeds::LanguageCode,
+    hidden_feeds: &HiddenFeeds,
+    since_report_id: Option<Id<clustering::Report>>,
+) -> Result<Page, ErrorPage> {
+    let (previous_date, next_date, latest_report, briefing_created_at) = futures::try_join!(
+        state.db.find_report_date_before(date),
+        state.db.find_report_date_after(date),
+        state.db.find_latest_report(),
+        state.db.find_briefing_created_at_by_date(date),
+    )?;
+    let entries = list_entries(&state, date, params, &language, latest_report.as_ref()).await?;
+    // entries published within the past hours change as time passes, not only with new reports
+    let validators = latest_report
+        .as_ref()
+        .filter(|_| params.hours.is_none())
+        .map(|report| {
+            caching::Validators::new(report, &language)
+                .with_suffix(hidden_feeds.etag_suffix().as_deref())
+                .with_suffix(since_report_id.map(|id| format!("since{id}")).as_deref())
+                .with_private(!hidden_feeds.is_empty() || since_report_id.is_some())
+        });
+
+    if entries.is_empty() && params.hours.is_none() {
+        return Ok(render_no_report(date, previous_date, next_date).with_language(language));
+    }
+
+    let has_categories = entries.iter().any(|entry| entry.category.is_some());
+
+    let changes = list_group_changes(&state, date, since_report_id, latest_report.as_ref()).await?;
 
     let entries_feed_titles = entries
         .iter()
-        .map(|entry| {
-            let feed = feeds::LIST
-                .iter()
-                .find(|f| f.id == entry.feed_id)
-                .expect("feed must exist");
-            (entry, feed.value.title.clone())
+        .filter(|entry| params.matches(entry))
+        // hidden feeds do not count towards ranking either
+        .filter(|entry| !hidden_feeds.contains(entry.feed_id))
+        .filter(|entry| {
+            params.category.is_some()
+                || entry.category.map_or(true, |category| {
+                    !state.hidden_categories.contains(&category)
+                })
         })
+        .map(|entry| (entry, feed_title(entry.feed_id)))
         .collect::<Vec<_>>();
 
     let entries_by_group_id = entries_feed_titles.into_iter().fold(
@@ -171,140 +711,1919 @@ async fn render_entries(state: AppState, date: chrono::NaiveDate) -> Result<Page
         },
     );
 
-    let mut scored_groups = entries_by_group_id
-        .values()
-        .map(|entries| {
-            // score is the sum of minutes since the start of the day
-            let score: i64 = entries
-                .iter()
-                .map(|(e, _)| e.published_at.naive_utc())
-                .map(|dt| {
-                    let start_of_day = dt
-                        .date()
-                        .and_hms_opt(0, 0, 0)
-                        .expect("failed to get start of day");
-                    dt - start_of_day
-                })
-                .map(|delta| delta.num_minutes())
-                .sum();
-            let center_entry = entries
-                .iter()
-                .find(|(e, _)| e.is_center)
-                .expect("center is present");
-            (center_entry, entries.len(), score)
-        })
-        .collect::<Vec<_>>();
-    scored_groups.sort_by(|a, b| b.1.cmp(&a.1));
+    let mut ranked_groups = rank_groups(&state, &entries_by_group_id);
+    let sort = params.sort.unwrap_or(state.default_sort);
+    ranking::sort(&mut ranked_groups, sort, |(_, stats)| stats);
 
     let time = chrono_tz::Europe::Stockholm
         .from_local_date(&date)
         .single()
         .ok_or(NotFound)?
         .and_hms(0, 0, 0);
-    let title = time.format("%A in Sweden").to_string();
+    let title = match params.hours {
+        Some(hours) => format!("Past {hours} hours in Sweden"),
+        None => time.format("%A in Sweden").to_string(),
+    };
 
     let page = maud::html! {
         header {
+            (render_day_nav(previous_date, next_date))
             h2 {
-                time datetime=(time.to_rfc3339()) { (time.format("%A in Sweden")) }
+                time datetime=(time.to_rfc3339()) { (title) }
+            }
+            (render_index_filters(params, sort, has_categories))
+            @if let (Some(created_at), None) = (briefing_created_at, params.hours) {
+                figure {
+                    figcaption { "Listen to the top stories" }
+                    // a new briefing has a new url, so that browsers do not play a cached one
+                    audio controls preload="none" src=(format!("{}/briefing.mp3?v={}", date_path(date), created_at.timestamp())) {}
+                }
             }
         }
-        ol {
-            @for ((entry, feed_title), size, _) in scored_groups {
+        @if let (Some(hours), true) = (params.hours, ranked_groups.is_empty()) {
+            p { "Nothing was published in the past " (hours) " hours." }
+        }
+        (render_ranked_groups(&ranked_groups, &changes, state.count_clicks))
+    };
+
+    // previews of shared links show the top stories
+    let description = ranked_groups
+        .iter()
+        .take(3)
+        .map(|((entry, _), _)| entry.title.as_str())
+        .collect::<Vec<_>>()
+        .join(" · ");
+    let image_href = ranked_groups
+        .iter()
+        .find_map(|((entry, _), _)| entry.image_href.clone());
+    let canonical_href = if date == today() {
+        state.base_url.clone()
+    } else {
+        state
+            .base_url
+            .join(date_path(date).trim_start_matches('/'))
+            .expect("valid url")
+    };
+    Ok(Page::new(&title, page)
+        .with_language(language)
+        .with_description((!description.is_empty()).then_some(description.as_str()))
+        .with_image_href(image_href)
+        .with_canonical_href(canonical_href)
+        .with_validators(validators)
+        .with_live(date == today())
+        .with_vapid_public_key(state.vapid_public_key.clone()))
+}
+
+async fn list_entries(
+    state: &AppState,
+    date: chrono::NaiveDate,
+    params: &IndexParams,
+    language: &feeds::LanguageCode,
+    latest_report: Option<&Persisted<clustering::Report>>,
+) -> Result<std::sync::Arc<Vec<GroupEntryView>>, ErrorPage> {
+    let entries = if let Some(hours) = params.hours {
+        let now = chrono::Utc::now();
+        let since = chrono::Duration::try_hours(hours.into())
+            .and_then(|hours| now.checked_sub_signed(hours))
+            .ok_or(NotFound)?;
+        let published_at = since..now;
+        std::sync::Arc::new(
+            state
+                .db
+                .list_report_group_entries_by_date_lang_code_published_at(
+                    date,
+                    language,
+                    &published_at,
+                )
+                .await?,
+        )
+    } else if let Some(entries) =
+        latest_report.and_then(|report| state.entries_cache.get(date, language, report))
+    {
+        entries
+    } else {
+        let entries = std::sync::Arc::new(
+            state
+                .db
+                .list_report_group_entries_by_date_lang_code(date, language)
+                .await?,
+        );
+        if let Some(report) = latest_report {
+            state
+                .entries_cache
+                .insert(date, language, report, entries.clone());
+        }
+        entries
+    };
+    Ok(entries)
+}
+
+/// groups that are new or grew since the report the reader saw on their previous visit
+async fn list_group_changes(
+    state: &AppState,
+    date: chrono::NaiveDate,
+    since_report_id: Option<Id<clustering::Report>>,
+    latest_report: Option<&Persisted<clustering::Report>>,
+) -> Result<std::collections::BTreeMap<Id<ReportGroup>, GroupChangeView>, ErrorPage> {
+    match (since_report_id, latest_report) {
+        (Some(since_report_id), Some(report))
+            if date == today() && since_report_id != report.id =>
+        {
+            Ok(state
+                .db
+                .list_report_group_changes_by_report_id_since_report_id(report.id, since_report_id)
+                .await?
+                .into_iter()
+                .filter(|change| change.is_new() || change.has_grown())
+                .map(|change| (change.group_id, change))
+                .collect())
+        }
+        _ => Ok(std::collections::BTreeMap::new()),
+    }
+}
+
+fn rank_groups<'a>(
+    state: &AppState,
+    entries_by_group_id: &'a std::collections::BTreeMap<
+        Id<ReportGroup>,
+        Vec<(&'a GroupEntryView, String)>,
+    >,
+) -> Vec<(&'a (&'a GroupEntryView, String), ranking::Stats)> {
+    entries_by_group_id
+        .values()
+        .filter_map(|entries| {
+            // center entry might be filtered out, fallback to the latest one in that case
+            let center_entry = entries
+                .iter()
+                .find(|(e, _)| e.is_center)
+                .or_else(|| entries.iter().max_by_key(|(e, _)| e.published_at))?;
+            let center_entry = if state.deprioritize_paywalled && center_entry.0.is_paywalled {
+                entries
+                    .iter()
+                    .find(|(e, _)| !e.is_paywalled)
+                    .unwrap_or(center_entry)
+            } else {
+                center_entry
+            };
+            Some((
+                center_entry,
+                ranking::Stats::new(
+                    entries.iter().map(|(entry, _)| {
+                        (
+                            &entry.feed_id,
+                            entry.published_at,
+                            entry.is_duplicate,
+                            entry.clicks,
+                        )
+                    }),
+                    &state.feed_weights,
+                ),
+            ))
+        })
+        .collect::<Vec<_>>()
+}
+
+fn render_ranked_groups(
+    groups: &[(&(&GroupEntryView, String), ranking::Stats)],
+    changes: &std::collections::BTreeMap<Id<ReportGroup>, GroupChangeView>,
+    count_clicks: bool,
+) -> maud::Markup {
+    maud::html! {
+        ol id="groups" {
+            @for ((entry, feed_title), ranking::Stats { size, unique_size, .. }) in groups {
+                @let copies = size - unique_size;
+                @let others = unique_size.saturating_sub(1);
                 li {
-                    a href=(entry.href) { (entry.title) }
+                    @if entry.is_breaking {
+                        mark { "Breaking" }
+                        " "
+                    }
+                    @match changes.get(&entry.group_id) {
+                        Some(change) if change.is_new() => {
+                            mark { "New" }
+                            " "
+                        }
+                        Some(change) => {
+                            mark title="Since your last visit" { "+" (change.size - change.previous_size.unwrap_or_default()) }
+                            " "
+                        }
+                        None => {}
+                    }
+                    @if let Some(group_title) = &entry.group_title {
+                        strong { (group_title) }
+                        br;
+                    }
+                    @if let Some(image_href) = &entry.image_href {
+                        img src=(image_href) alt="" loading="lazy" width="160";
+                        br;
+                    }
+                    a href=(entry.outbound_href(count_clicks)) rel=[count_clicks.then_some("nofollow")] { (entry.title) }
+                    @if entry.is_paywalled {
+                        " "
+                        span title="Paywalled" { "🔒" }
+                    }
                     p {
                         date time=(entry.published_at.to_rfc3339()) { (entry.published_at.with_timezone(&SWEDEN_TZ).format("%H:%M")) }
                         " by "
                         (feed_title)
-                        " and "
-                        a href=(format!("/groups/{}", entry.group_id)) {
-                            @if size == 2 {
-                                "1 other"
-                            } @else {
-                                (size - 1) " others"
+                        @if others > 0 || copies > 0 {
+                            " and "
+                            a href=(format!("/groups/{}", entry.group_id)) {
+                                @match others {
+                                    0 => {}
+                                    1 => "1 other",
+                                    others => { (others) " others" }
+                                }
+                                @if others > 0 && copies > 0 {
+                                    " + "
+                                }
+                                @if copies > 0 {
+                                    (copies) " syndicated"
+                                }
                             }
                         }
                     }
                 }
             }
         }
+    }
+}
+
+fn render_index_filters(
+    params: &IndexParams,
+    sort: ranking::Sort,
+    has_categories: bool,
+) -> maud::Markup {
+    maud::html! {
+        @if has_categories {
+            nav {
+                ul {
+                    li { small { a href=(IndexParams { category: None, ..params.clone() }.href()) { "all" } } }
+                    @for category in clustering::Category::ALL {
+                        li { small { a href=(IndexParams { category: Some(category), ..params.clone() }.href()) { (category.as_str()) } } }
+                    }
+                }
+            }
+        }
+        nav {
+            ul {
+                li { small { a href=(IndexParams { hours: None, ..params.clone() }.href()) aria-current=[params.hours.is_none().then_some("true")] { "whole day" } } }
+                li { small { a href=(IndexParams { hours: Some(RIGHT_NOW_HOURS), ..params.clone() }.href()) aria-current=[(params.hours == Some(RIGHT_NOW_HOURS)).then_some("true")] { "right now" } } }
+            }
+        }
+        nav {
+            ul {
+                li { small { "sort by" } }
+                @for option in ranking::Sort::ALL {
+                    li {
+                        small {
+                            a href=(IndexParams { sort: Some(option), ..params.clone() }.href()) aria-current=[(option == sort).then_some("true")] { (option.as_str()) }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn render_no_report(
+    date: chrono::NaiveDate,
+    previous_date: Option<chrono::NaiveDate>,
+    next_date: Option<chrono::NaiveDate>,
+) -> Page {
+    let page = maud::html! {
+        header {
+            (render_day_nav(previous_date, next_date))
+            h2 { (date.format("%A, %-d %B %Y")) }
+        }
+        p { "No digest yet for this day." }
+        @if let Some(previous_date) = previous_date {
+            p {
+                a href=(date_path(previous_date)) {
+                    "Read the digest of " (previous_date.format("%A, %-d %B"))
+                }
+            }
+        }
     };
 
-    Ok(Page::new(&title, page))
+    Page::new("No digest yet", page)
 }
 
-const SWEDEN_TZ: chrono_tz::Tz = chrono_tz::Europe::Stockholm;
+#[derive(serde::Deserialize)]
+struct MonthParams {
+    year: i32,
+    month: u32,
+}
 
-#[derive(Debug, sqlx::FromRow)]
-pub struct GroupEntryView {
-    pub group_id: Id<clustering::ReportGroup>,
-    pub is_center: bool,
-    pub title: String,
-    pub href: String,
-    pub published_at: chrono::DateTime<chrono::Utc>,
-    pub feed_id: Id<feeds::Feed>,
+async fn render_archive(State(state): State<AppState>) -> Result<Page, ErrorPage> {
+    let today = today();
+    render_month(state, today.with_day(1).expect("first day of month exists")).await
 }
 
-async fn render_group(
+async fn render_archive_for_month(
     State(state): State<AppState>,
-    Path(params): Path<GroupParams>,
+    Path(params): Path<MonthParams>,
 ) -> Result<Page, ErrorPage> {
-    let groups = state
-        .db
-        .list_report_group_entries_by_id_lang_code(params.id, &feeds::LanguageCode::EN)
-        .await?;
+    let first_day =
+        chrono::NaiveDate::from_ymd_opt(params.year, params.month, 1).ok_or(NotFound)?;
+    render_month(state, first_day).await
+}
 
-    let groups = groups
+async fn render_month(state: AppState, first_day: chrono::NaiveDate) -> Result<Page, ErrorPage> {
+    let next_month = first_day
+        .checked_add_months(chrono::Months::new(1))
+        .ok_or(NotFound)?;
+    let previous_month = first_day
+        .checked_sub_months(chrono::Months::new(1))
+        .ok_or(NotFound)?;
+    let last_day = next_month.pred_opt().ok_or(NotFound)?;
+
+    let groups_counts = state
+        .db
+        .list_report_days(first_day, last_day)
+        .await?
         .into_iter()
-        .map(|group| {
-            let feed = feeds::LIST
-                .iter()
-                .find(|f| f.id == group.feed_id)
-                .expect("feed must exist");
-            (group, feed.value.title.clone())
-        })
+        .map(|day| (day.date, day.groups_count))
+        .collect::<std::collections::BTreeMap<_, _>>();
+
+    // weeks start on monday, days before the first day of the month are left blank
+    let blanks =
+        usize::try_from(first_day.weekday().num_days_from_monday()).expect("u32 -> usize failed");
+    let days = std::iter::repeat(None)
+        .take(blanks)
+        .chain(
+            first_day
+                .iter_days()
+                .take_while(|day| *day <= last_day)
+                .map(Some),
+        )
         .collect::<Vec<_>>();
 
+    let title = first_day.format("%B %Y").to_string();
     let page = maud::html! {
         header {
             nav {
                 ul {
-                    li { small { a href= "/" { "Back to main page" } } }
+                    li { small { a href=(month_path(previous_month)) { "← " (previous_month.format("%B")) } } }
+                }
+                ul {
+                    @if next_month <= today() {
+                        li { small { a href=(month_path(next_month)) { (next_month.format("%B")) " →" } } }
+                    }
                 }
             }
+            h2 { (title) }
         }
-        ol {
-            @for (group, feed_title) in &groups {
-                li {
-                    a href=(group.href) { (group.title) }
-                    p {
-                        time datetime=(group.published_at.to_rfc3339()) { (group.published_at.with_timezone(&SWEDEN_TZ).format("%H:%M")) }
-                        " by "
-                        (feed_title)
+        table {
+            thead {
+                tr {
+                    @for weekday in ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"] {
+                        th { (weekday) }
+                    }
+                }
+            }
+            tbody {
+                @for week in days.chunks(7) {
+                    tr {
+                        @for day in week {
+                            td {
+                                @if let Some(day) = day {
+                                    @if let Some(groups_count) = groups_counts.get(day) {
+                                        a href=(date_path(*day)) { (day.day()) }
+                                        br;
+                                        small {
+                                            @if *groups_count == 1 {
+                                                "1 story"
+                                            } @else {
+                                                (groups_count) " stories"
+                                            }
+                                        }
+                                    } @else {
+                                        (day.day())
+                                    }
+                                }
+                            }
+                        }
                     }
                 }
             }
         }
     };
 
-    let title = groups
-        .last()
-        .map(|(entry, _)| entry.title.as_str())
-        .expect("at least one entry is always present in a group");
+    Ok(Page::new(&format!("Archive of {title}"), page))
+}
 
-    Ok(Page::new(title, page))
+fn month_path(date: chrono::NaiveDate) -> String {
+    date.format("/archive/%Y/%m").to_string()
 }
 
-#[derive(RustEmbed)]
-#[folder = "assets"]
-struct Assets;
+fn render_day_nav(
+    previous_date: Option<chrono::NaiveDate>,
+    next_date: Option<chrono::NaiveDate>,
+) -> maud::Markup {
+    maud::html! {
+        nav {
+            ul {
+                @if let Some(previous_date) = previous_date {
+                    li { small { a href=(date_path(previous_date)) { "← " (previous_date.format("%A, %-d %B")) } } }
+                }
+            }
+            ul {
+                @if let Some(next_date) = next_date {
+                    li { small { a href=(date_path(next_date)) { (next_date.format("%A, %-d %B")) " →" } } }
+                }
+            }
+        }
+    }
+}
+
+fn date_path(date: chrono::NaiveDate) -> String {
+    date.format("/%Y/%m/%d").to_string()
+}
+
+const SWEDEN_TZ: chrono_tz::Tz = chrono_tz::Europe::Stockholm;
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct ReportDayView {
+    pub date: chrono::NaiveDate,
+    pub groups_count: u32,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct GroupChangeView {
+    pub group_id: Id<ReportGroup>,
+    pub size: u32,
+    /// number of entries of the largest ancestor, none when the group is new
+    pub previous_size: Option<u32>,
+}
+
+impl GroupChangeView {
+    pub fn is_new(&self) -> bool {
+        self.previous_size.is_none()
+    }
+
+    pub fn has_grown(&self) -> bool {
+        self.previous_size
+            .is_some_and(|previous_size| self.size > previous_size)
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct GroupEntryView {
+    pub entry_id: Id<feeds::Entry>,
+    pub group_id: Id<clustering::ReportGroup>,
+    pub is_center: bool,
+    pub title: String,
+    pub group_title: Option<String>,
+    pub category: Option<clustering::Category>,
+    pub href: String,
+    pub published_at: chrono::DateTime<chrono::Utc>,
+    pub feed_id: Id<feeds::Feed>,
+    #[sqlx(default)]
+    pub is_breaking: bool,
+    /// entry is a near-duplicate of an entry of another feed, such as a copy of a wire story
+    pub is_duplicate: bool,
+    pub image_href: Option<String>,
+    pub is_paywalled: bool,
+    #[sqlx(default)]
+    pub clicks: u32,
+}
+
+impl GroupEntryView {
+    fn outbound_href(&self, count_clicks: bool) -> String {
+        if count_clicks {
+            format!("/out/{}", self.entry_id)
+        } else {
+            self.href.clone()
+        }
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct WeekGroupView {
+    pub group_id: Id<clustering::ReportGroup>,
+    pub date: chrono::NaiveDate,
+    pub size: u32,
+    pub title: String,
+    pub group_title: Option<String>,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct FieldRevisionView {
+    pub name: feeds::FieldName,
+    pub lang_code: feeds::LanguageCode,
+    pub value: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct GroupTextView {
+    pub group_id: Id<clustering::ReportGroup>,
+    pub title: String,
+    pub summary: Option<String>,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct SearchResultView {
+    pub title: String,
+    pub href: String,
+    pub published_at: chrono::DateTime<chrono::Utc>,
+    pub feed_id: Id<feeds::Feed>,
+    pub group_id: Option<Id<clustering::ReportGroup>>,
+    #[sqlx(default)]
+    pub snippet: Option<String>,
+}
+
+/// marks start and end of matches in search result snippets, control characters do not occur
+/// in titles and descriptions
+pub const SNIPPET_MATCH_START: &str = "\u{2}";
+pub const SNIPPET_MATCH_END: &str = "\u{3}";
+
+fn highlight(snippet: &str) -> maud::Markup {
+    maud::html! {
+        @for (i, part) in snippet.split(SNIPPET_MATCH_START).enumerate() {
+            @if i == 0 {
+                (part)
+            } @else if let Some((matched, rest)) = part.split_once(SNIPPET_MATCH_END) {
+                mark { (matched) }
+                (rest)
+            } @else {
+                (part)
+            }
+        }
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct EntryView {
+    pub title: String,
+    pub href: String,
+    pub published_at: chrono::DateTime<chrono::Utc>,
+    pub feed_id: Id<feeds::Feed>,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct RelatedGroupView {
+    pub group_id: Id<clustering::ReportGroup>,
+    pub title: String,
+}
+
+fn group_centers(entries: &[GroupEntryView]) -> Vec<(&GroupEntryView, usize)> {
+    let entries_by_group_id = entries.iter().fold(
+        std::collections::BTreeMap::<Id<ReportGroup>, Vec<&GroupEntryView>>::new(),
+        |mut map, entry| {
+            map.entry(entry.group_id).or_default().push(entry);
+            map
+        },
+    );
+
+    let mut centers = entries_by_group_id
+        .values()
+        .filter_map(|entries| {
+            entries
+                .iter()
+                .find(|entry| entry.is_center)
+                .map(|center| (*center, entries.len()))
+        })
+        .collect::<Vec<_>>();
+    centers.sort_by_key(|(_, size)| std::cmp::Reverse(*size));
+    centers
+}
+
+fn feed_title(feed_id: Id<feeds::Feed>) -> String {
+    feeds::LIST
+        .iter()
+        .find(|f| f.id == feed_id)
+        .map(|feed| feed.value.title.clone())
+        .expect("feed must exist")
+}
+
+#[derive(serde::Deserialize)]
+struct EntryParams {
+    id: Id<feeds::Entry>,
+}
+
+async fn render_entry(
+    State(state): State<AppState>,
+    Path(params): Path<EntryParams>,
+    Language(language): Language,
+) -> Result<Page, ErrorPage> {
+    let (entry, fields, group_id, revisions) = futures::try_join!(
+        state.db.find_entry_by_id(&params.id),
+        state.db.list_field_values_by_entry_id(params.id),
+        state.db.find_latest_report_group_id_by_entry_id(params.id),
+        state.db.list_field_revisions_by_entry_id(params.id),
+    )?;
+    let headlines = revisions
+        .iter()
+        .filter(|revision| revision.name == feeds::FieldName::Title)
+        .collect::<Vec<_>>();
+
+    let field = |name: &feeds::FieldName, lang_code: &feeds::LanguageCode| {
+        fields
+            .iter()
+            .find(|(field_name, field_lang_code, _)| {
+                field_name == name && field_lang_code == lang_code
+            })
+            .map(|(_, _, value)| value.as_str())
+    };
+    let title = field(&feeds::FieldName::Title, &language)
+        .or_else(|| field(&feeds::FieldName::Title, &feeds::LanguageCode::SV))
+        .unwrap_or_default()
+        .to_string();
+    let published_at = entry.value.published_at.with_timezone(&SWEDEN_TZ);
+
+    let page = maud::html! {
+        header {
+            nav {
+                ul {
+                    li { small { a href=(date_path(published_at.date_naive())) { "Back to the day" } } }
+                }
+                @if let Some(group_id) = group_id {
+                    ul {
+                        li { small { a href=(format!("/groups/{group_id}")) { "See the story" } } }
+                    }
+                }
+            }
+            h2 { (title) }
+            p {
+                small {
+                    time datetime=(published_at.to_rfc3339()) { (published_at.format("%Y-%m-%d %H:%M")) }
+                    " by "
+                    (feed_title(entry.value.feed_id))
+                    " · "
+                    a href=(entry.value.href) { "original" }
+                }
+            }
+        }
+        @for lang_code in language::available() {
+            @let title = field(&feeds::FieldName::Title, lang_code);
+            @let description = field(&feeds::FieldName::Description, lang_code);
+            @if title.is_some() || description.is_some() {
+                section lang=(lang_code) dir=(language::direction(lang_code)) {
+                    h3 { (lang_code.name()) }
+                    @if let Some(title) = title {
+                        p { strong { (title) } }
+                    }
+                    @if let Some(description) = description {
+                        p { (description) }
+                    }
+                }
+            }
+        }
+        @if !headlines.is_empty() {
+            (render_headline_history(&headlines))
+        }
+    };
+
+    let description = field(&feeds::FieldName::Description, &language)
+        .or_else(|| field(&feeds::FieldName::Description, &feeds::LanguageCode::SV));
+    let canonical_href = state
+        .base_url
+        .join(&format!("entries/{}", params.id))
+        .expect("valid url");
+    Ok(Page::new(&title, page)
+        .with_language(language)
+        .with_description(description)
+        .with_image_href(entry.value.image_href.as_ref().map(ToString::to_string))
+        .with_canonical_href(canonical_href))
+}
+
+fn render_headline_history(headlines: &[&FieldRevisionView]) -> maud::Markup {
+    maud::html! {
+        section {
+            h3 { "Headline history" }
+            ol {
+                @for (i, headline) in headlines.iter().enumerate() {
+                    @let created_at = headline.created_at.with_timezone(&SWEDEN_TZ);
+                    li lang=(headline.lang_code) {
+                        small { time datetime=(created_at.to_rfc3339()) { (created_at.format("%Y-%m-%d %H:%M")) } }
+                        br;
+                        @if let Some(previous) = i.checked_sub(1).and_then(|i| headlines.get(i)) {
+                            @for change in diff::words(&previous.value, &headline.value) {
+                                @match change {
+                                    diff::Change::Kept(word) => { (word) }
+                                    diff::Change::Removed(word) => { del { (word) } }
+                                    diff::Change::Added(word) => { ins { (word) } }
+                                }
+                                " "
+                            }
+                        } @else {
+                            (headline.value)
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+async fn render_atom_feed(State(state): State<AppState>) -> Result<atom::Feed, ErrorPage> {
+    let entries = state
+        .db
+        .list_report_group_entries_by_date_lang_code(today(), &feeds::LanguageCode::EN)
+        .await?;
+
+    let href = state.base_url.join("feed.xml").expect("valid url");
+    let updated = entries
+        .iter()
+        .map(|entry| entry.published_at)
+        .max()
+        .unwrap_or_else(chrono::Utc::now);
+    let entries = group_centers(&entries)
+        .into_iter()
+        .map(|(center, _)| {
+            let href = state
+                .base_url
+                .join(&format!("groups/{}", center.group_id))
+                .expect("valid url");
+            atom::Entry {
+                id: href.to_string(),
+                title: center.title.clone(),
+                href: href.to_string(),
+                author: feed_title(center.feed_id),
+                updated: center.published_at,
+            }
+        })
+        .collect();
+
+    Ok(atom::Feed {
+        id: href.to_string(),
+        title: "Today in Sweden".to_string(),
+        href,
+        alternate_href: state.base_url.clone(),
+        updated,
+        entries,
+    })
+}
+
+async fn render_json_feed(
+    Query(params): Query<IndexParams>,
+    State(state): State<AppState>,
+) -> Result<json_feed::Feed, ErrorPage> {
+    let entries = state
+        .db
+        .list_report_group_entries_by_date_lang_code(today(), &feeds::LanguageCode::EN)
+        .await?
+        .into_iter()
+        .filter(|entry| params.matches(entry))
+        .collect::<Vec<_>>();
+
+    Ok(json_feed::Feed {
+        version: json_feed::VERSION,
+        title: "Today in Sweden".to_string(),
+        home_page_url: state.base_url.clone(),
+        self_url: state.base_url.join("feed.json").expect("valid url"),
+        items: group_json_feed_items(&state.base_url, &entries),
+    })
+}
+
+async fn render_breaking_json_feed(
+    State(state): State<AppState>,
+) -> Result<json_feed::Feed, ErrorPage> {
+    let entries = state
+        .db
+        .list_report_group_entries_by_date_lang_code(today(), &feeds::LanguageCode::EN)
+        .await?
+        .into_iter()
+        .filter(|entry| entry.is_breaking)
+        .collect::<Vec<_>>();
+
+    Ok(json_feed::Feed {
+        version: json_feed::VERSION,
+        title: "Breaking news in Sweden".to_string(),
+        home_page_url: state.base_url.clone(),
+        self_url: state
+            .base_url
+            .join("breaking/feed.json")
+            .expect("valid url"),
+        items: group_json_feed_items(&state.base_url, &entries),
+    })
+}
+
+fn group_json_feed_items(base_url: &url::Url, entries: &[GroupEntryView]) -> Vec<json_feed::Item> {
+    group_centers(entries)
+        .into_iter()
+        .map(|(center, _)| {
+            let url = base_url
+                .join(&format!("groups/{}", center.group_id))
+                .expect("valid url");
+            json_feed::Item {
+                id: url.to_string(),
+                url: url.to_string(),
+                external_url: center.href.clone(),
+                title: center.title.clone(),
+                content_text: center.title.clone(),
+                date_published: center.published_at,
+                authors: vec![json_feed::Author {
+                    name: feed_title(center.feed_id),
+                }],
+                tags: center
+                    .category
+                    .iter()
+                    .map(|category| category.as_str().to_string())
+                    .collect(),
+            }
+        })
+        .collect()
+}
+
+#[derive(serde::Deserialize)]
+struct SearchParams {
+    q: Option<String>,
+    #[serde(default)]
+    mode: SearchMode,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum SearchMode {
+    #[default]
+    Semantic,
+    Keyword,
+}
+
+const MAX_SEARCH_RESULTS: usize = 20;
+
+async fn render_search(
+    Query(params): Query<SearchParams>,
+    Language(language): Language,
+    State(state): State<AppState>,
+) -> Result<Page, ErrorPage> {
+    let query = params.q.as_deref().map(str::trim).unwrap_or_default();
+
+    let results = if query.is_empty() {
+        vec![]
+    } else if params.mode == SearchMode::Keyword {
+        let keywords = query.split_whitespace().collect::<Vec<_>>();
+        state
+            .db
+            .list_search_results_by_keywords_lang_code(
+                &keywords,
+                &language,
+                MAX_SEARCH_RESULTS.try_into().expect("usize -> u32 failed"),
+            )
+            .await?
+    } else {
+        search(&state, query, &language).await?
+    };
+
+    let page = maud::html! {
+        header {
+            nav {
+                ul {
+                    li { small { a href= "/" { "Back to main page" } } }
+                }
+            }
+            h2 { "Search" }
+            form method="get" action="/search" {
+                input type="search" name="q" value=(query) placeholder="What happened?";
+                fieldset {
+                    label {
+                        input type="radio" name="mode" value="semantic" checked[params.mode == SearchMode::Semantic];
+                        "Similar stories"
+                    }
+                    label {
+                        input type="radio" name="mode" value="keyword" checked[params.mode == SearchMode::Keyword];
+                        "Exact words"
+                    }
+                }
+            }
+        }
+        @if !query.is_empty() && results.is_empty() {
+            p { "Nothing found." }
+        }
+        ol {
+            @for result in &results {
+                li {
+                    a href=(result.href) { (result.title) }
+                    p {
+                        time datetime=(result.published_at.to_rfc3339()) { (result.published_at.with_timezone(&SWEDEN_TZ).format("%Y-%m-%d %H:%M")) }
+                        " by "
+                        (feed_title(result.feed_id))
+                        @if let Some(group_id) = result.group_id {
+                            " in "
+                            a href=(format!("/groups/{group_id}")) { "a story" }
+                        }
+                    }
+                    @if let Some(snippet) = &result.snippet {
+                        p { small { (highlight(snippet)) } }
+                    }
+                }
+            }
+        }
+    };
+
+    Ok(Page::new("Search", page).with_language(language))
+}
+
+async fn search(
+    state: &AppState,
+    query: &str,
+    language: &feeds::LanguageCode,
+) -> Result<Vec<SearchResultView>, Box<dyn std::error::Error + Send + Sync>> {
+    let Some(embedder) = &state.embedder else {
+        return Ok(vec![]);
+    };
+    let now = chrono::Utc::now();
+    let window = now - state.search_window..now;
+    let model = embedder.model();
+    let (query_embedding, (sv_embeddings, en_embeddings)) =
+        futures::try_join!(embedder.embed(query), async {
+            futures::try_join!(
+                state
+                    .db
+                    .list_embeddings_by_field_name_lang_code_published_at_model(
+                        feeds::FieldName::Description,
+                        feeds::LanguageCode::SV,
+                        &window,
+                        model,
+                    ),
+                state
+                    .db
+                    .list_embeddings_by_field_name_lang_code_published_at_model(
+                        feeds::FieldName::Description,
+                        feeds::LanguageCode::EN,
+                        &window,
+                        model,
+                    ),
+            )
+            .map_err(Into::into)
+        },)?;
+    let embeddings = sv_embeddings
+        .into_iter()
+        .chain(en_embeddings)
+        .collect::<Vec<_>>();
+
+    let nearest = clustering::nearest(&embeddings, &query_embedding, MAX_SEARCH_RESULTS);
+    let results = futures::future::try_join_all(nearest.into_iter().map(|(i, _)| {
+        state
+            .db
+            .list_search_results_by_embedding_id_lang_code(embeddings[i].id, language)
+    }))
+    .await?;
+
+    Ok(results.into_iter().flatten().collect())
+}
+
+async fn render_lattlast(State(state): State<AppState>) -> Result<Page, ErrorPage> {
+    let groups = state
+        .db
+        .list_group_texts_by_date_lang_code(today(), &feeds::LanguageCode::Lattlast)
+        .await?;
+
+    let page = maud::html! {
+        header {
+            nav {
+                ul {
+                    li { small { a href= "/" { "Back to main page" } } }
+                }
+            }
+            h2 { "Nyheter på lättläst svenska" }
+            p { small { "Dagens viktigaste nyheter, skrivna med enkla ord och korta meningar." } }
+        }
+        @if groups.is_empty() {
+            p { "Det finns inga lättlästa nyheter i dag än." }
+        }
+        @for group in &groups {
+            article {
+                h3 { (group.title) }
+                @if let Some(summary) = &group.summary {
+                    p { (summary) }
+                }
+                small { a href=(format!("/groups/{}", group.group_id)) { "Läs mer" } }
+            }
+        }
+    };
+
+    Ok(Page::new("Lättläst", page).with_language(feeds::LanguageCode::Lattlast))
+}
+
+const WEEK_DAYS: u32 = 7;
+
+const WEEK_TOPICS: usize = 30;
+
+struct WeekTopic<'a> {
+    groups: Vec<&'a WeekGroupView>,
+    size: u32,
+    days: std::collections::BTreeSet<chrono::NaiveDate>,
+}
+
+fn week_topics<'a>(
+    groups: &'a [WeekGroupView],
+    embeddings: &[db::GroupEmbedding],
+) -> Vec<WeekTopic<'a>> {
+    let group_embeddings = groups
+        .iter()
+        .map(|group| {
+            (
+                group.date,
+                embeddings
+                    .iter()
+                    .filter(|(group_id, _)| *group_id == group.group_id)
+                    .map(|(_, embedding)| embedding)
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let mut topics = clustering::link_across_days(&group_embeddings)
+        .into_iter()
+        .map(|indices| {
+            let groups = indices.into_iter().map(|i| &groups[i]).collect::<Vec<_>>();
+            WeekTopic {
+                size: groups.iter().map(|group| group.size).sum(),
+                days: groups.iter().map(|group| group.date).collect(),
+                groups,
+            }
+        })
+        .collect::<Vec<_>>();
+    // topics that recur on more days win ties
+    topics.sort_by_key(|topic| std::cmp::Reverse((topic.size, topic.days.len())));
+    topics.truncate(WEEK_TOPICS);
+    topics
+}
+
+async fn render_week(
+    Language(language): Language,
+    State(state): State<AppState>,
+) -> Result<Page, ErrorPage> {
+    let to = today();
+    let from = to - chrono::Duration::days((WEEK_DAYS - 1).into());
+    let (groups, embeddings) = futures::try_join!(
+        state
+            .db
+            .list_report_groups_by_dates_lang_code(from, to, &language),
+        state.db.list_report_group_embeddings_by_dates(from, to),
+    )?;
+    let topics = week_topics(&groups, &embeddings);
+
+    let page = maud::html! {
+        header {
+            nav {
+                ul {
+                    li { small { a href= "/" { "Back to main page" } } }
+                }
+            }
+            h2 { "This week in Sweden" }
+            p { small { "Stories of the past seven days, the most covered first." } }
+        }
+        @if topics.is_empty() {
+            p { "There are no reports this week yet." }
+        }
+        ol {
+            @for topic in &topics {
+                @if let Some(latest) = topic.groups.last() {
+                    li {
+                        a href=(format!("/groups/{}", latest.group_id)) {
+                            (latest.group_title.as_ref().unwrap_or(&latest.title))
+                        }
+                        p {
+                            (topic.size) " entries on "
+                            @for (i, date) in topic.days.iter().enumerate() {
+                                @if i > 0 { ", " }
+                                a href=(date_path(*date)) { (date.format("%A")) }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    };
+
+    Ok(Page::new("This week in Sweden", page).with_language(language))
+}
+
+async fn render_other(
+    Language(language): Language,
+    State(state): State<AppState>,
+) -> Result<Page, ErrorPage> {
+    let entries = state
+        .db
+        .list_ungrouped_entries_by_date_lang_code(today(), &language)
+        .await?;
+
+    let page = maud::html! {
+        header {
+            nav {
+                ul {
+                    li { small { a href= "/" { "Back to main page" } } }
+                }
+            }
+            h2 { "Other news" }
+            p { small { "Stories that were reported by a single source today." } }
+        }
+        ol {
+            @for entry in &entries {
+                li {
+                    a href=(entry.href) { (entry.title) }
+                    p {
+                        time datetime=(entry.published_at.to_rfc3339()) { (entry.published_at.with_timezone(&SWEDEN_TZ).format("%H:%M")) }
+                        " by "
+                        (feed_title(entry.feed_id))
+                    }
+                }
+            }
+        }
+    };
+
+    Ok(Page::new("Other news", page).with_language(language))
+}
+
+async fn render_other_json_feed(
+    State(state): State<AppState>,
+) -> Result<json_feed::Feed, ErrorPage> {
+    let entries = state
+        .db
+        .list_ungrouped_entries_by_date_lang_code(today(), &feeds::LanguageCode::EN)
+        .await?;
+
+    let items = entries
+        .into_iter()
+        .map(|entry| json_feed::Item {
+            id: entry.href.clone(),
+            url: entry.href.clone(),
+            external_url: entry.href,
+            content_text: entry.title.clone(),
+            title: entry.title,
+            date_published: entry.published_at,
+            authors: vec![json_feed::Author {
+                name: feed_title(entry.feed_id),
+            }],
+            tags: vec![],
+        })
+        .collect();
+
+    Ok(json_feed::Feed {
+        version: json_feed::VERSION,
+        title: "Other news in Sweden".to_string(),
+        home_page_url: state.base_url.join("other").expect("valid url"),
+        self_url: state.base_url.join("other/feed.json").expect("valid url"),
+        items,
+    })
+}
+
+async fn render_group(
+    State(state): State<AppState>,
+    Path(params): Path<GroupParams>,
+    Language(language): Language,
+) -> Result<Page, ErrorPage> {
+    let (groups, first_seen, summary, related_groups, latest_report) = futures::try_join!(
+        state
+            .db
+            .list_report_group_entries_by_id_lang_code(params.id, &language),
+        state.db.find_report_group_first_seen_by_id(params.id),
+        state
+            .db
+            .find_group_summary_by_report_group_id_lang_code(params.id, &language),
+        state
+            .db
+            .list_related_groups_by_id_lang_code(params.id, &language),
+        state.db.find_latest_report(),
+    )?;
+    let validators = latest_report.map(|report| caching::Validators::new(&report, &language));
+
+    let groups = groups
+        .into_iter()
+        .map(|group| {
+            let feed = feeds::LIST
+                .iter()
+                .find(|f| f.id == group.feed_id)
+                .expect("feed must exist");
+            (group, feed.value.title.clone())
+        })
+        .collect::<Vec<_>>();
+
+    let description = summary.clone();
+    let page = maud::html! {
+        header {
+            nav {
+                ul {
+                    li { small { a href= "/" { "Back to main page" } } }
+                }
+                ul {
+                    li { small { a href=(format!("/groups/{}/feed.xml", params.id)) { "Follow this story" } } }
+                }
+            }
+        }
+        @if let Some(first_seen) = first_seen {
+            p {
+                small {
+                    "Developing since "
+                    time datetime=(first_seen.to_rfc3339()) {
+                        @if first_seen.with_timezone(&SWEDEN_TZ).date_naive() == today() {
+                            (first_seen.with_timezone(&SWEDEN_TZ).format("%H:%M"))
+                        } @else {
+                            (first_seen.with_timezone(&SWEDEN_TZ).format("%Y-%m-%d %H:%M"))
+                        }
+                    }
+                }
+            }
+        }
+        @if let Some(summary) = summary {
+            p { (summary) }
+        }
+        @let (copies, originals): (Vec<_>, Vec<_>) = groups.iter().partition(|(group, _)| group.is_duplicate);
+        (render_group_entries(&originals, state.count_clicks))
+        @if !copies.is_empty() {
+            details {
+                summary {
+                    @if copies.len() == 1 {
+                        "1 syndicated copy"
+                    } @else {
+                        (copies.len()) " syndicated copies"
+                    }
+                }
+                (render_group_entries(&copies, state.count_clicks))
+            }
+        }
+        @if !related_groups.is_empty() {
+            section {
+                h3 { "Related stories" }
+                ul {
+                    @for related_group in &related_groups {
+                        li { a href=(format!("/groups/{}", related_group.group_id)) { (related_group.title) } }
+                    }
+                }
+            }
+        }
+    };
+
+    // every group has at least one entry, so there is no group without them
+    let title = groups
+        .last()
+        .map(|(entry, _)| entry.title.as_str())
+        .ok_or(NotFound)?;
+
+    let image_href = groups
+        .iter()
+        .filter(|(entry, _)| !entry.is_duplicate)
+        .find_map(|(entry, _)| entry.image_href.clone());
+    let canonical_href = state
+        .base_url
+        .join(&format!("groups/{}", params.id))
+        .expect("valid url");
+    let mut oembed_href = state.base_url.join("oembed").expect("valid url");
+    oembed_href
+        .query_pairs_mut()
+        .append_pair("url", canonical_href.as_str())
+        .append_pair("format", "json");
+    Ok(Page::new(title, page)
+        .with_language(language)
+        .with_description(description.as_deref())
+        .with_image_href(image_href)
+        .with_canonical_href(canonical_href)
+        .with_oembed_href(oembed_href)
+        .with_validators(validators))
+}
+
+fn render_group_entries(entries: &[&(GroupEntryView, String)], count_clicks: bool) -> maud::Markup {
+    maud::html! {
+        ol {
+            @for (group, feed_title) in entries {
+                li {
+                    a href=(group.outbound_href(count_clicks)) rel=[count_clicks.then_some("nofollow")] { (group.title) }
+                    @if group.is_paywalled {
+                        " "
+                        span title="Paywalled" { "🔒" }
+                    }
+                    p {
+                        time datetime=(group.published_at.to_rfc3339()) { (group.published_at.with_timezone(&SWEDEN_TZ).format("%H:%M")) }
+                        " by "
+                        (feed_title)
+                    }
+                }
+            }
+        }
+    }
+}
+
+async fn render_group_atom_feed(
+    State(state): State<AppState>,
+    Path(params): Path<GroupParams>,
+) -> Result<atom::Feed, ErrorPage> {
+    let entries = state
+        .db
+        .list_report_group_entries_by_id_lang_code(params.id, &feeds::LanguageCode::EN)
+        .await?;
+
+    let center = entries
+        .iter()
+        .find(|entry| entry.is_center)
+        .ok_or(NotFound)?;
+
+    let alternate_href = state
+        .base_url
+        .join(&format!("groups/{}", params.id))
+        .expect("valid url");
+    let href = state
+        .base_url
+        .join(&format!("groups/{}/feed.xml", params.id))
+        .expect("valid url");
+    let updated = entries
+        .iter()
+        .map(|entry| entry.published_at)
+        .max()
+        .unwrap_or(center.published_at);
+
+    Ok(atom::Feed {
+        id: href.to_string(),
+        title: center.title.clone(),
+        href,
+        alternate_href,
+        updated,
+        entries: entries
+            .iter()
+            .map(|entry| atom::Entry {
+                id: entry.href.clone(),
+                title: entry.title.clone(),
+                href: entry.href.clone(),
+                author: feed_title(entry.feed_id),
+                updated: entry.published_at,
+            })
+            .collect(),
+    })
+}
+
+/// minimal view of a group for iframes of other websites. groups are replaced with their
+/// descendants as reports are generated, so embeds keep following the story.
+async fn render_embedded_group(
+    State(state): State<AppState>,
+    Path(params): Path<GroupParams>,
+    Language(language): Language,
+) -> Result<Page, ErrorPage> {
+    let id = state
+        .db
+        .find_latest_descendant_report_group_id_by_id(params.id)
+        .await?
+        .ok_or(NotFound)?;
+    let (entries, latest_report) = futures::try_join!(
+        state
+            .db
+            .list_report_group_entries_by_id_lang_code(id, &language),
+        state.db.find_latest_report(),
+    )?;
+    let validators = latest_report.map(|report| caching::Validators::new(&report, &language));
+
+    let entries = entries
+        .into_iter()
+        .filter(|entry| !entry.is_duplicate)
+        .map(|entry| {
+            let feed_title = feed_title(entry.feed_id);
+            (entry, feed_title)
+        })
+        .collect::<Vec<_>>();
+    let title = entries
+        .iter()
+        .find(|(entry, _)| entry.is_center)
+        .or(entries.last())
+        .map(|(entry, _)| entry.title.clone())
+        .ok_or(NotFound)?;
+
+    let page = maud::html! {
+        div id="groups" {
+            h2 { a href=(format!("/groups/{id}")) { (title) } }
+            (render_group_entries(&entries.iter().collect::<Vec<_>>(), state.count_clicks))
+        }
+        p { small { a href="/" { "Today in Sweden" } } }
+    };
+    Ok(Page::new(&title, page)
+        .with_language(language)
+        .with_embedded(true)
+        .with_live(true)
+        .with_validators(validators))
+}
+
+#[derive(Debug, thiserror::Error)]
+enum OembedError {
+    #[error("format is not supported")]
+    NotImplemented,
+    #[error("url can not be embedded")]
+    NotFound,
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+}
+
+impl axum::response::IntoResponse for OembedError {
+    fn into_response(self) -> axum::response::Response {
+        match self {
+            Self::NotImplemented => StatusCode::NOT_IMPLEMENTED.into_response(),
+            Self::NotFound => StatusCode::NOT_FOUND.into_response(),
+            Self::Database(error) => {
+                tracing::error!(%error, "failed to render oembed");
+                StatusCode::INTERNAL_SERVER_ERROR.into_response()
+            }
+        }
+    }
+}
+
+async fn render_oembed(
+    State(state): State<AppState>,
+    Query(params): Query<oembed::Params>,
+) -> Result<oembed::Response, OembedError> {
+    if params
+        .format
+        .as_deref()
+        .is_some_and(|format| format != "json")
+    {
+        return Err(OembedError::NotImplemented);
+    }
+
+    let id = params
+        .url
+        .strip_prefix(state.base_url.as_str())
+        .and_then(|path| path.strip_prefix("groups/"))
+        .map(|id| id.trim_end_matches('/'))
+        .and_then(|id| id.parse::<u32>().ok())
+        .map(Id::<clustering::ReportGroup>::from)
+        .ok_or(OembedError::NotFound)?;
+    let entries = state
+        .db
+        .list_report_group_entries_by_id_lang_code(id, &feeds::LanguageCode::EN)
+        .await?;
+    let center = entries
+        .iter()
+        .find(|entry| entry.is_center)
+        .ok_or(OembedError::NotFound)?;
+
+    let width = params
+        .maxwidth
+        .map_or(oembed::WIDTH, |max| max.min(oembed::WIDTH));
+    let height = params
+        .maxheight
+        .map_or(oembed::HEIGHT, |max| max.min(oembed::HEIGHT));
+    let src = state
+        .base_url
+        .join(&format!("embed/groups/{id}"))
+        .expect("valid url");
+    let html = maud::html! {
+        iframe src=(src.as_str()) width=(width) height=(height) style="border:0" loading="lazy" title=(center.title) {}
+    };
+    Ok(oembed::Response {
+        version: oembed::VERSION,
+        kind: "rich",
+        title: center.title.clone(),
+        provider_name: "Today in Sweden",
+        provider_url: state.base_url.clone(),
+        html: html.into_string(),
+        width,
+        height,
+    })
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct FeedStatusView {
+    pub feed_id: Id<feeds::Feed>,
+    pub last_success_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub consecutive_failures: u32,
+    pub last_error: Option<String>,
+    pub average_duration_ms: f64,
+    pub entries_per_day: f64,
+}
+
+async fn render_status(State(state): State<AppState>) -> Result<Page, ErrorPage> {
+    let statuses = state.db.list_feed_statuses().await?;
+
+    let feeds = feeds::LIST
+        .iter()
+        .map(|feed| {
+            let status = statuses.iter().find(|status| status.feed_id == feed.id);
+            (feed, status)
+        })
+        .collect::<Vec<_>>();
+
+    let page = maud::html! {
+        header {
+            nav {
+                ul {
+                    li { small { a href= "/" { "Back to main page" } } }
+                    li { small { a href= "/status/jobs" { "Background jobs" } } }
+                    li { small { a href= "/status/filters" { "Filtered entries" } } }
+                    li { small { a href= "/status/popular" { "Most read" } } }
+                }
+            }
+            h2 { "Feeds status" }
+        }
+        table {
+            thead {
+                tr {
+                    th { "Feed" }
+                    th { "Last success" }
+                    th { "Consecutive failures" }
+                    th { "Average duration" }
+                    th { "Entries per day" }
+                    th { "Last error" }
+                }
+            }
+            tbody {
+                @for (feed, status) in feeds {
+                    tr {
+                        td { (feed.value.title) }
+                        @if let Some(status) = status {
+                            td {
+                                @if let Some(last_success_at) = status.last_success_at {
+                                    time datetime=(last_success_at.to_rfc3339()) { (last_success_at.with_timezone(&SWEDEN_TZ).format("%Y-%m-%d %H:%M")) }
+                                } @else {
+                                    "never"
+                                }
+                            }
+                            td { (status.consecutive_failures) }
+                            td { (format!("{:.0}ms", status.average_duration_ms)) }
+                            td { (format!("{:.1}", status.entries_per_day)) }
+                            td { small { (status.last_error.as_deref().unwrap_or_default()) } }
+                        } @else {
+                            td colspan="5" { "not crawled yet" }
+                        }
+                    }
+                }
+            }
+        }
+    };
+
+    Ok(Page::new("Feeds status", page))
+}
+
+const POPULAR_DAYS: i64 = 7;
+
+const POPULAR_LIMIT: u32 = 50;
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct PopularEntryView {
+    pub entry_id: Id<feeds::Entry>,
+    pub feed_id: Id<feeds::Feed>,
+    pub title: String,
+    pub clicks: u32,
+}
+
+async fn render_popular(
+    State(state): State<AppState>,
+    Language(language): Language,
+) -> Result<Page, ErrorPage> {
+    let since = today() - chrono::Duration::days(POPULAR_DAYS - 1);
+    let entries = state
+        .db
+        .list_popular_entries_since_date_lang_code(since, &language, POPULAR_LIMIT)
+        .await?;
+
+    let page = maud::html! {
+        header {
+            nav {
+                ul {
+                    li { small { a href= "/status" { "Back to status" } } }
+                }
+            }
+            h2 { "Most read" }
+            p { small { "Clicks on articles in the past " (POPULAR_DAYS) " days. Only the number of clicks per day is counted, not who clicked." } }
+        }
+        table {
+            thead {
+                tr {
+                    th { "Article" }
+                    th { "Feed" }
+                    th { "Clicks" }
+                }
+            }
+            tbody {
+                @for entry in &entries {
+                    tr {
+                        td { a href=(format!("/entries/{}", entry.entry_id)) { (entry.title) } }
+                        td { (feed_title(entry.feed_id)) }
+                        td { (entry.clicks) }
+                    }
+                }
+            }
+        }
+    };
+
+    Ok(Page::new("Most read", page).with_language(language))
+}
+
+async fn follow_entry(
+    State(state): State<AppState>,
+    Path(params): Path<EntryParams>,
+) -> Result<impl IntoResponse, ErrorPage> {
+    let entry = state.db.find_entry_by_id(&params.id).await?;
+    // readers get to the article even when the click is not counted
+    if let Err(error) = state.db.increment_entry_clicks(params.id, today()).await {
+        tracing::error!(%error, "failed to count click");
+    }
+    Ok((
+        StatusCode::FOUND,
+        [
+            (LOCATION, entry.value.href.to_string()),
+            // every click must reach the server to be counted
+            (CACHE_CONTROL, "no-store".to_string()),
+        ],
+    ))
+}
+
+async fn stream_events(
+    State(state): State<AppState>,
+) -> Sse<impl futures::Stream<Item = Result<Event, std::convert::Infallible>>> {
+    let mut receiver = state.report_updates.clone();
+    // only changes made after the reader connected are sent
+    receiver.borrow_and_update();
+    let events = futures::stream::unfold(receiver, |mut receiver| async move {
+        receiver.changed().await.ok()?;
+        let updated_at = *receiver.borrow_and_update();
+        let event = Event::default()
+            .event("report")
+            .data(updated_at.map(|at| at.to_rfc3339()).unwrap_or_default());
+        Some((Ok(event), receiver))
+    })
+    // open streams would keep the server from shutting down
+    .take_until(shutdown::signal());
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
+
+async fn render_preferences(hidden_feeds: HiddenFeeds) -> Page {
+    let mut feeds = feeds::LIST.iter().collect::<Vec<_>>();
+    feeds.sort_by(|a, b| a.value.title.cmp(&b.value.title));
+    Page::new(
+        "Preferences",
+        maud::html! {
+            header {
+                nav {
+                    ul {
+                        li { small { a href= "/" { "Back to main page" } } }
+                    }
+                }
+                h2 { "Preferences" }
+            }
+            form method="post" action="/preferences" {
+                fieldset {
+                    legend { "Hide stories from" }
+                    @for feed in &feeds {
+                        label {
+                            input type="checkbox" name="hidden" value=(feed.id) checked[hidden_feeds.contains(feed.id)];
+                            (feed.value.title)
+                        }
+                    }
+                }
+                button type="submit" { "Save" }
+            }
+        },
+    )
+}
+
+async fn save_preferences(
+    State(state): State<AppState>,
+    body: axum::body::Bytes,
+) -> impl IntoResponse {
+    let hidden_feeds = HiddenFeeds(
+        url::form_urlencoded::parse(&body)
+            .filter(|(name, _)| name == "hidden")
+            .filter_map(|(_, value)| value.parse::<u32>().ok())
+            .map(Id::from)
+            .filter(|id| feeds::LIST.iter().any(|feed| feed.id == *id))
+            .collect(),
+    );
+    let mut response = axum::response::Redirect::to("/preferences").into_response();
+    if let Some(cookie) = hidden_feeds.cookie(&state.preferences_key) {
+        response.headers_mut().append(SET_COOKIE, cookie);
+    }
+    response
+}
+
+/// subscriptions are not stored beyond this many, every one of them is sent breaking news
+const MAX_PUSH_SUBSCRIPTIONS: u32 = 10_000;
+
+async fn subscribe_push(
+    State(state): State<AppState>,
+    axum::Json(subscription): axum::Json<push::Subscription>,
+) -> Result<StatusCode, ErrorPage> {
+    if state.vapid_public_key.is_none() {
+        return Err(ErrorPage::NotFound);
+    }
+    if !push::is_service_endpoint(&subscription.endpoint) {
+        return Ok(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+    let stored = state
+        .db
+        .upsert_push_subscription(&subscription, MAX_PUSH_SUBSCRIPTIONS)
+        .await?;
+    if !stored {
+        return Ok(StatusCode::SERVICE_UNAVAILABLE);
+    }
+    Ok(StatusCode::CREATED)
+}
+
+#[derive(serde::Deserialize)]
+struct UnsubscribeParams {
+    endpoint: String,
+}
+
+async fn unsubscribe_push(
+    State(state): State<AppState>,
+    axum::Json(params): axum::Json<UnsubscribeParams>,
+) -> Result<StatusCode, ErrorPage> {
+    state.db.delete_push_subscription(&params.endpoint).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+const JOB_RUNS_LIMIT: u32 = 100;
+
+async fn retry_failed_tasks(State(state): State<AppState>) -> Result<String, ErrorPage> {
+    let count = state.db.retry_failed_tasks().await?;
+    Ok(format!("{count} tasks queued again\n"))
+}
+
+async fn render_job_runs(State(state): State<AppState>) -> Result<Page, ErrorPage> {
+    let runs = state.db.list_job_runs(JOB_RUNS_LIMIT).await?;
+    let task_counts = state.db.count_tasks_by_kind_status().await?;
+    let failed_tasks = state.db.list_failed_tasks(JOB_RUNS_LIMIT).await?;
+
+    let duration = |ms: Option<u32>| ms.map(|ms| format!("{ms}ms")).unwrap_or_default();
+    let count = |count: Option<u32>| count.map(|count| count.to_string()).unwrap_or_default();
+
+    let page = maud::html! {
+        header {
+            nav {
+                ul {
+                    li { small { a href= "/status" { "Back to status" } } }
+                }
+            }
+            h2 { "Background jobs" }
+        }
+        table {
+            thead {
+                tr {
+                    th { "Job" }
+                    th { "Started" }
+                    th { "Duration" }
+                    th { "Crawl" }
+                    th { "Embed" }
+                    th { "Report" }
+                    th { "Entries" }
+                    th { "Embeddings" }
+                    th { "Groups" }
+                    th { "Error" }
+                }
+            }
+            tbody {
+                @for run in runs {
+                    tr {
+                        td { (run.value.name) }
+                        td { time datetime=(run.value.started_at.to_rfc3339()) { (run.value.started_at.with_timezone(&SWEDEN_TZ).format("%Y-%m-%d %H:%M")) } }
+                        td { (format!("{}s", (run.value.finished_at - run.value.started_at).num_seconds())) }
+                        td { (duration(run.value.crawl_ms)) }
+                        td { (duration(run.value.embed_ms)) }
+                        td { (duration(run.value.report_ms)) }
+                        td { (count(run.value.entries_count)) }
+                        td { (count(run.value.embeddings_count)) }
+                        td { (count(run.value.groups_count)) }
+                        td { small { (run.value.error.as_deref().unwrap_or_default()) } }
+                    }
+                }
+            }
+        }
+        h3 { "Tasks" }
+        table {
+            thead {
+                tr {
+                    th { "Kind" }
+                    th { "Status" }
+                    th { "Count" }
+                }
+            }
+            tbody {
+                @for (kind, status, count) in task_counts {
+                    tr {
+                        td { (kind) }
+                        td { (status) }
+                        td { (count) }
+                    }
+                }
+            }
+        }
+        @if !failed_tasks.is_empty() {
+            h3 { "Failed tasks" }
+            table {
+                thead {
+                    tr {
+                        th { "Kind" }
+                        th { "Target" }
+                        th { "Status" }
+                        th { "Attempts" }
+                        th { "Error" }
+                    }
+                }
+                tbody {
+                    @for task in failed_tasks {
+                        tr {
+                            td { (task.kind) }
+                            td { (task.target_id) }
+                            td { (task.status) }
+                            td { (task.attempts) }
+                            td { small { (task.error.as_deref().unwrap_or_default()) } }
+                        }
+                    }
+                }
+            }
+        }
+    };
+
+    Ok(Page::new("Background jobs", page))
+}
+
+const FILTER_HITS_LIMIT: u32 = 200;
+
+async fn render_filter_hits(State(state): State<AppState>) -> Result<Page, ErrorPage> {
+    let hits = state.db.list_filter_hits(FILTER_HITS_LIMIT).await?;
+
+    let page = maud::html! {
+        header {
+            nav {
+                ul {
+                    li { small { a href= "/status" { "Back to status" } } }
+                }
+            }
+            h2 { "Filtered entries" }
+        }
+        @if hits.is_empty() {
+            p { "No entries were skipped by keywords." }
+        } @else {
+            table {
+                thead {
+                    tr {
+                        th { "Skipped" }
+                        th { "Feed" }
+                        th { "Keyword" }
+                        th { "Title" }
+                    }
+                }
+                tbody {
+                    @for hit in hits {
+                        tr {
+                            td { time datetime=(hit.created_at.to_rfc3339()) { (hit.created_at.with_timezone(&SWEDEN_TZ).format("%Y-%m-%d %H:%M")) } }
+                            td { (feed_title(hit.value.feed_id)) }
+                            td { (hit.value.keyword) }
+                            td { a href=(hit.value.href.to_string()) { (hit.value.title) } }
+                        }
+                    }
+                }
+            }
+        }
+    };
+
+    Ok(Page::new("Filtered entries", page))
+}
+
+#[derive(RustEmbed)]
+#[folder = "assets"]
+struct Assets;
+
+const ASSET_HASH_LEN: usize = 8;
+
+fn asset_hash(asset: &rust_embed::EmbeddedFile) -> String {
+    use std::fmt::Write;
+
+    asset.metadata.sha256_hash()[..ASSET_HASH_LEN]
+        .iter()
+        .fold(String::new(), |mut hex, byte| {
+            let _ = write!(hex, "{byte:02x}");
+            hex
+        })
+}
+
+/// absolute path of the asset with the hash of its content before the extension, for example
+/// `/css/pico.classless.yellow.min.0123456789abcdef.css`. the path changes along with the content,
+/// so responses can be cached forever
+fn asset_href(path: &str) -> String {
+    if let (Some(asset), Some((stem, extension))) = (Assets::get(path), path.rsplit_once('.')) {
+        format!("/{stem}.{}.{extension}", asset_hash(&asset))
+    } else {
+        format!("/{path}")
+    }
+}
+
+fn find_asset(path: &str) -> Option<(rust_embed::EmbeddedFile, bool)> {
+    if let Some(asset) = Assets::get(path) {
+        return Some((asset, false));
+    }
+    let (rest, extension) = path.rsplit_once('.')?;
+    let (stem, hash) = rest.rsplit_once('.')?;
+    let asset = Assets::get(&format!("{stem}.{extension}"))?;
+    (asset_hash(&asset) == hash).then_some((asset, true))
+}
 
 async fn serve_asset(uri: Uri) -> Result<impl IntoResponse, ErrorPage> {
-    let Some(asset) = Assets::get(uri.path().trim_start_matches('/')) else {
+    let Some((asset, hashed)) = find_asset(uri.path().trim_start_matches('/')) else {
         return Err(ErrorPage::from(NotFound));
     };
     let content_type = asset.metadata.mimetype();
     let bytes = asset.data.to_vec();
-    Ok(([(CONTENT_TYPE, content_type.to_string())], bytes))
+    // plain paths, such as the one of about.html, keep their content only until the next deploy
+    let cache_control = if hashed {
+        "public, max-age=31536000, immutable"
+    } else {
+        "public, max-age=3600"
+    };
+    Ok((
+        [
+            (CONTENT_TYPE, content_type.to_string()),
+            (CACHE_CONTROL, cache_control.to_string()),
+        ],
+        bytes,
+    ))
 }
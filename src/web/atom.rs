@@ -0,0 +1,50 @@
+use axum::http::header::CONTENT_TYPE;
+use axum::response::IntoResponse;
+
+/// Atom syndication feed, see <https://www.rfc-editor.org/rfc/rfc4287>
+pub struct Feed {
+    pub id: String,
+    pub title: String,
+    pub href: url::Url,
+    pub alternate_href: url::Url,
+    pub updated: chrono::DateTime<chrono::Utc>,
+    pub entries: Vec<Entry>,
+}
+
+pub struct Entry {
+    pub id: String,
+    pub title: String,
+    pub href: String,
+    pub author: String,
+    pub updated: chrono::DateTime<chrono::Utc>,
+}
+
+impl IntoResponse for Feed {
+    fn into_response(self) -> axum::response::Response {
+        // maud renders `link ;` as an unclosed html tag, so xml tags always have a body.
+        let feed = maud::html! {
+            (maud::PreEscaped(r#"<?xml version="1.0" encoding="utf-8"?>"#))
+            feed xmlns="http://www.w3.org/2005/Atom" {
+                id { (self.id) }
+                title { (self.title) }
+                link rel="self" href=(self.href) {}
+                link rel="alternate" href=(self.alternate_href) {}
+                updated { (self.updated.to_rfc3339()) }
+                @for entry in &self.entries {
+                    entry {
+                        id { (entry.id) }
+                        title { (entry.title) }
+                        link rel="alternate" href=(entry.href) {}
+                        author { name { (entry.author) } }
+                        updated { (entry.updated.to_rfc3339()) }
+                    }
+                }
+            }
+        };
+        (
+            [(CONTENT_TYPE, "application/atom+xml; charset=utf-8")],
+            feed.into_string(),
+        )
+            .into_response()
+    }
+}
@@ -0,0 +1,113 @@
+use axum::extract::{Request, State};
+use axum::http::header::{AUTHORIZATION, WWW_AUTHENTICATE};
+use axum::http::StatusCode;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+
+use super::proxy::Client;
+
+#[derive(Clone)]
+pub struct ApiKey {
+    name: String,
+    key: String,
+}
+
+impl std::fmt::Debug for ApiKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // keys must not end up in logs
+        f.debug_struct("ApiKey")
+            .field("name", &self.name)
+            .finish_non_exhaustive()
+    }
+}
+
+impl std::str::FromStr for ApiKey {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (name, key) = value
+            .split_once('=')
+            .ok_or_else(|| "expected <name>=<key>".to_string())?;
+        let (name, key) = (name.trim(), key.trim());
+        if name.is_empty() || key.is_empty() {
+            return Err("name and key must not be empty".to_string());
+        }
+        Ok(Self {
+            name: name.to_string(),
+            key: key.to_string(),
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ApiKeys(Vec<ApiKey>);
+
+impl ApiKeys {
+    pub fn new(keys: Vec<ApiKey>) -> Self {
+        Self(keys)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// returns the name of the key. every key is compared in full, so that the time it takes
+    /// does not tell how much of a key was guessed right
+    fn find(&self, key: &str) -> Option<&str> {
+        self.0.iter().fold(None, |found, api_key| {
+            if constant_time_eq(api_key.key.as_bytes(), key.as_bytes()) {
+                Some(api_key.name.as_str())
+            } else {
+                found
+            }
+        })
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    let mut difference = a.len() ^ b.len();
+    for i in 0..a.len().max(b.len()) {
+        let (a, b) = (
+            a.get(i).copied().unwrap_or_default(),
+            b.get(i).copied().unwrap_or_default(),
+        );
+        difference |= usize::from(a ^ b);
+    }
+    difference == 0
+}
+
+pub async fn require_key(
+    State(keys): State<std::sync::Arc<ApiKeys>>,
+    client: Client,
+    request: Request,
+    next: Next,
+) -> Response {
+    let key = request
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim);
+    let Some(name) = key.and_then(|key| keys.find(key)) else {
+        tracing::warn!(
+            client = %client.address,
+            method = %request.method(),
+            uri = %request.uri(),
+            "unauthorized admin request"
+        );
+        return (
+            StatusCode::UNAUTHORIZED,
+            [(WWW_AUTHENTICATE, "Bearer")],
+            "Unauthorized",
+        )
+            .into_response();
+    };
+    tracing::info!(
+        key = name,
+        client = %client.address,
+        method = %request.method(),
+        uri = %request.uri(),
+        "admin request"
+    );
+    next.run(request).await
+}
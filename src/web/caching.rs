@@ -0,0 +1,112 @@
+use axum::extract::Request;
+use axum::http::header::{CACHE_CONTROL, ETAG, IF_NONE_MATCH, LAST_MODIFIED};
+use axum::http::{HeaderMap, HeaderValue, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+
+use crate::{clustering, feeds, persisted::Persisted};
+
+/// how long pages can be used without revalidating them, in seconds. short, so that new reports
+/// are seen soon
+const MAX_AGE: u32 = 60;
+
+#[derive(Debug, Clone)]
+pub struct Validators {
+    etag: String,
+    last_modified: chrono::DateTime<chrono::Utc>,
+    /// only browsers can cache pages rendered for their readers
+    private: bool,
+}
+
+pub fn report_updated_at(report: &Persisted<clustering::Report>) -> chrono::DateTime<chrono::Utc> {
+    report.value.updated_at.unwrap_or(report.created_at)
+}
+
+impl Validators {
+    pub fn new(report: &Persisted<clustering::Report>, language: &feeds::LanguageCode) -> Self {
+        let updated_at = report_updated_at(report);
+        Self {
+            etag: format!("\"{}-{}-{language}\"", report.id, updated_at.timestamp()),
+            last_modified: updated_at,
+            private: false,
+        }
+    }
+
+    pub fn with_suffix(mut self, suffix: Option<&str>) -> Self {
+        if let Some(suffix) = suffix {
+            self.etag = format!("{}-{suffix}\"", self.etag.trim_end_matches('"'));
+        }
+        self
+    }
+
+    pub fn with_private(mut self, private: bool) -> Self {
+        self.private = private;
+        self
+    }
+
+    pub fn apply(&self, headers: &mut HeaderMap) {
+        let scope = if self.private { "private" } else { "public" };
+        let values = [
+            (CACHE_CONTROL, format!("{scope}, max-age={MAX_AGE}")),
+            (ETAG, self.etag.clone()),
+            (
+                LAST_MODIFIED,
+                self.last_modified
+                    .format("%a, %d %b %Y %H:%M:%S GMT")
+                    .to_string(),
+            ),
+        ];
+        for (name, value) in values {
+            if let Ok(value) = HeaderValue::from_str(&value) {
+                headers.insert(name, value);
+            }
+        }
+    }
+}
+
+pub fn set_private(headers: &mut HeaderMap) {
+    if let Ok(value) = HeaderValue::from_str(&format!("private, max-age={MAX_AGE}")) {
+        headers.insert(CACHE_CONTROL, value);
+    }
+}
+
+pub async fn not_modified(request: Request, next: Next) -> Response {
+    let if_none_match = request
+        .headers()
+        .get(IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .map(ToString::to_string);
+    let response = next.run(request).await;
+
+    let Some(if_none_match) = if_none_match else {
+        return response;
+    };
+    let Some(etag) = response
+        .headers()
+        .get(ETAG)
+        .and_then(|value| value.to_str().ok())
+    else {
+        return response;
+    };
+    let matches = if_none_match.trim() == "*"
+        || if_none_match
+            .split(',')
+            .map(|tag| tag.trim().trim_start_matches("W/"))
+            .any(|tag| tag == etag);
+    if !matches {
+        return response;
+    }
+
+    let mut headers = HeaderMap::new();
+    for name in [CACHE_CONTROL, ETAG, LAST_MODIFIED] {
+        if let Some(value) = response.headers().get(&name) {
+            headers.insert(name, value.clone());
+        }
+    }
+    for name in [axum::http::header::VARY, axum::http::header::SET_COOKIE] {
+        for value in response.headers().get_all(&name) {
+            headers.append(name.clone(), value.clone());
+        }
+    }
+    (StatusCode::NOT_MODIFIED, headers).into_response()
+}
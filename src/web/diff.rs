@@ -0,0 +1,41 @@
+pub enum Change<'a> {
+    Kept(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+pub fn words<'a>(old: &'a str, new: &'a str) -> Vec<Change<'a>> {
+    let old = old.split_whitespace().collect::<Vec<_>>();
+    let new = new.split_whitespace().collect::<Vec<_>>();
+
+    // lengths[i][j] is the length of the longest common sequence of old[i..] and new[j..]
+    let mut lengths = vec![vec![0_usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i][j] = if old[i] == new[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut changes = vec![];
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            changes.push(Change::Kept(old[i]));
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            changes.push(Change::Removed(old[i]));
+            i += 1;
+        } else {
+            changes.push(Change::Added(new[j]));
+            j += 1;
+        }
+    }
+    changes.extend(old[i..].iter().map(|word| Change::Removed(word)));
+    changes.extend(new[j..].iter().map(|word| Change::Added(word)));
+    changes
+}
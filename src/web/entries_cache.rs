@@ -0,0 +1,82 @@
+use crate::{clustering, feeds, id::Id, persisted::Persisted};
+
+use super::{caching, GroupEntryView};
+
+const MAX_LEN: usize = 64;
+
+type Key = (chrono::NaiveDate, feeds::LanguageCode);
+
+type Version = (Id<clustering::Report>, chrono::DateTime<chrono::Utc>);
+
+fn version(report: &Persisted<clustering::Report>) -> Version {
+    (report.id, caching::report_updated_at(report))
+}
+
+struct Cached {
+    version: Version,
+    inserted_at: std::time::Instant,
+    entries: std::sync::Arc<Vec<GroupEntryView>>,
+}
+
+pub struct Cache {
+    ttl: std::time::Duration,
+    cached: std::sync::Mutex<std::collections::HashMap<Key, Cached>>,
+}
+
+impl Cache {
+    /// zero `ttl` disables the cache
+    pub fn new(ttl: std::time::Duration) -> Self {
+        Self {
+            ttl,
+            cached: std::sync::Mutex::new(std::collections::HashMap::new()),
+        }
+    }
+
+    pub fn get(
+        &self,
+        date: chrono::NaiveDate,
+        language: &feeds::LanguageCode,
+        latest_report: &Persisted<clustering::Report>,
+    ) -> Option<std::sync::Arc<Vec<GroupEntryView>>> {
+        let cached = self.cached.lock().expect("failed to lock cache");
+        cached
+            .get(&(date, language.clone()))
+            .filter(|cached| cached.version == version(latest_report))
+            .filter(|cached| cached.inserted_at.elapsed() < self.ttl)
+            .map(|cached| cached.entries.clone())
+    }
+
+    pub fn insert(
+        &self,
+        date: chrono::NaiveDate,
+        language: &feeds::LanguageCode,
+        latest_report: &Persisted<clustering::Report>,
+        entries: std::sync::Arc<Vec<GroupEntryView>>,
+    ) {
+        if self.ttl.is_zero() {
+            return;
+        }
+        let version = version(latest_report);
+        let mut cached = self.cached.lock().expect("failed to lock cache");
+        cached.retain(|_, cached| {
+            cached.version == version && cached.inserted_at.elapsed() < self.ttl
+        });
+        if cached.len() >= MAX_LEN {
+            let oldest = cached
+                .iter()
+                .min_by_key(|(_, cached)| cached.inserted_at)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                cached.remove(&oldest);
+            }
+        }
+        cached.insert(
+            (date, language.clone()),
+            Cached {
+                version,
+                inserted_at: std::time::Instant::now(),
+                entries,
+            },
+        );
+    }
+}
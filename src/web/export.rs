@@ -0,0 +1,110 @@
+//! static copy of the site. pages are written as `index.html` files of directories named after
+//! their paths, so that links between them work on hosts that serve directory indexes, such as
+//! object storage websites.
+
+use axum::extract::{Path, State};
+
+use super::{
+    asset_href, render_entries, render_group, today, AppState, Assets, ErrorPage, GroupParams,
+    HiddenFeeds, IndexParams, Language, Page,
+};
+use crate::feeds;
+
+type Error = Box<dyn std::error::Error>;
+
+pub async fn run(state: &AppState, out: &std::path::Path, days: u32) -> Result<usize, Error> {
+    // there is no server to count clicks of static pages, links go straight to articles
+    let state = &AppState {
+        count_clicks: false,
+        ..state.clone()
+    };
+
+    // pages link to assets by their hashed paths, other files such as about.html by plain ones
+    for path in Assets::iter() {
+        let asset = Assets::get(&path).expect("asset exists");
+        write(&out.join(path.as_ref()), &asset.data)?;
+        let hashed = asset_href(&path);
+        write(&out.join(hashed.trim_start_matches('/')), &asset.data)?;
+    }
+
+    // static pages can not negotiate languages, titles are shown in english
+    let language = feeds::LanguageCode::EN;
+    let params = IndexParams::default();
+
+    // there are no events to refresh static pages with, nor a server to subscribe to
+    let index = render_entries(
+        state.clone(),
+        today(),
+        &params,
+        language.clone(),
+        &HiddenFeeds::default(),
+        None,
+    )
+    .await
+    .map(|page| page.with_live(false).with_vapid_public_key(None));
+    write_page(out, "", index)?;
+    let mut count = 1;
+
+    let from = today() - chrono::Duration::days(i64::from(days.saturating_sub(1)));
+    let mut group_ids = std::collections::BTreeSet::new();
+    for day in state.db.list_report_days(from, today()).await? {
+        let page = render_entries(
+            state.clone(),
+            day.date,
+            &params,
+            language.clone(),
+            &HiddenFeeds::default(),
+            None,
+        )
+        .await
+        .map(|page| page.with_live(false).with_vapid_public_key(None));
+        write_page(out, &super::date_path(day.date), page)?;
+        count += 1;
+
+        if let Some(audio) = state.db.find_briefing_audio_by_date(day.date).await? {
+            let path = format!("{}/briefing.mp3", super::date_path(day.date));
+            write(&out.join(path.trim_start_matches('/')), &audio)?;
+        }
+
+        let entries = state
+            .db
+            .list_report_group_entries_by_date_lang_code(day.date, &language)
+            .await?;
+        group_ids.extend(entries.iter().map(|entry| entry.group_id));
+    }
+
+    for group_id in group_ids {
+        let page = render_group(
+            State(state.clone()),
+            Path(GroupParams { id: group_id }),
+            Language(language.clone()),
+        )
+        .await;
+        write_page(out, &format!("/groups/{group_id}"), page)?;
+        count += 1;
+    }
+
+    Ok(count)
+}
+
+fn write_page(
+    out: &std::path::Path,
+    path: &str,
+    page: Result<Page, ErrorPage>,
+) -> Result<(), Error> {
+    let page = match page {
+        Ok(page) => page,
+        Err(ErrorPage::NotFound) => return Err(format!("{path} is not found").into()),
+        Err(ErrorPage::Internal(error)) => return Err(error),
+    };
+    let file = out.join(path.trim_start_matches('/')).join("index.html");
+    write(&file, page.render().into_string().as_bytes())
+}
+
+fn write(file: &std::path::Path, bytes: &[u8]) -> Result<(), Error> {
+    if let Some(dir) = file.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(file, bytes)?;
+    Ok(())
+}
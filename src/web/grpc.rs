@@ -0,0 +1,172 @@
+//! grpc service of groups, for bots and pipelines. see `proto/news.proto`.
+
+use futures::StreamExt;
+use tonic::{Request, Response, Status};
+
+use super::{feed_title, group_centers, today, AppState, GroupEntryView};
+use crate::{feeds, shutdown};
+
+mod proto {
+    tonic::include_proto!("news.v1");
+}
+
+use proto::news_server::{News, NewsServer};
+
+#[derive(Clone)]
+struct Service {
+    state: AppState,
+}
+
+pub async fn serve(state: AppState, address: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let address = tokio::net::lookup_host(address)
+        .await?
+        .next()
+        .ok_or_else(|| format!("{address} does not resolve to any address"))?;
+    tracing::info!("grpc listening on {address}");
+    tonic::transport::Server::builder()
+        .add_service(NewsServer::new(Service { state }))
+        .serve_with_shutdown(address, shutdown::signal())
+        .await?;
+    Ok(())
+}
+
+fn parse_language(language: &str) -> Result<feeds::LanguageCode, Status> {
+    if language.is_empty() {
+        return Ok(feeds::LanguageCode::EN);
+    }
+    language
+        .parse()
+        .map_err(|_| Status::invalid_argument(format!("unknown language {language}")))
+}
+
+fn internal(error: sqlx::Error) -> Status {
+    tracing::error!(%error, "grpc request failed");
+    Status::internal("internal error")
+}
+
+fn groups(entries: &[GroupEntryView]) -> Vec<proto::Group> {
+    group_centers(entries)
+        .into_iter()
+        .map(|(center, _)| {
+            let mut members = entries
+                .iter()
+                .filter(|entry| entry.group_id == center.group_id)
+                .collect::<Vec<_>>();
+            members.sort_by_key(|entry| !entry.is_center);
+            proto::Group {
+                id: center.group_id.into(),
+                title: center.group_title.clone().unwrap_or_default(),
+                category: center
+                    .category
+                    .map(|category| category.as_str().to_string())
+                    .unwrap_or_default(),
+                breaking: center.is_breaking,
+                entries: members.into_iter().map(entry).collect(),
+            }
+        })
+        .collect()
+}
+
+fn entry(entry: &GroupEntryView) -> proto::Entry {
+    proto::Entry {
+        title: entry.title.clone(),
+        href: entry.href.clone(),
+        feed_id: entry.feed_id.into(),
+        feed_title: feed_title(entry.feed_id),
+        published_at: entry.published_at.to_rfc3339(),
+        center: entry.is_center,
+        paywalled: entry.is_paywalled,
+    }
+}
+
+impl Service {
+    async fn groups_of_day(
+        &self,
+        date: chrono::NaiveDate,
+        language: &feeds::LanguageCode,
+    ) -> Result<proto::ListGroupsResponse, Status> {
+        let entries = self
+            .state
+            .db
+            .list_report_group_entries_by_date_lang_code(date, language)
+            .await
+            .map_err(internal)?;
+        Ok(proto::ListGroupsResponse {
+            date: date.to_string(),
+            groups: groups(&entries),
+        })
+    }
+}
+
+type WatchGroupsStream =
+    futures::stream::BoxStream<'static, Result<proto::ListGroupsResponse, Status>>;
+
+#[tonic::async_trait]
+impl News for Service {
+    async fn list_groups(
+        &self,
+        request: Request<proto::ListGroupsRequest>,
+    ) -> Result<Response<proto::ListGroupsResponse>, Status> {
+        let request = request.into_inner();
+        let date = if request.date.is_empty() {
+            today()
+        } else {
+            request
+                .date
+                .parse()
+                .map_err(|_| Status::invalid_argument("date must be YYYY-MM-DD"))?
+        };
+        let language = parse_language(&request.language)?;
+        Ok(Response::new(self.groups_of_day(date, &language).await?))
+    }
+
+    async fn get_group(
+        &self,
+        request: Request<proto::GetGroupRequest>,
+    ) -> Result<Response<proto::Group>, Status> {
+        let request = request.into_inner();
+        let language = parse_language(&request.language)?;
+        let entries = self
+            .state
+            .db
+            .list_report_group_entries_by_id_lang_code(request.id.into(), &language)
+            .await
+            .map_err(internal)?;
+        groups(&entries)
+            .into_iter()
+            .next()
+            .map(Response::new)
+            .ok_or_else(|| Status::not_found(format!("group {} is not found", request.id)))
+    }
+
+    type WatchGroupsStream = WatchGroupsStream;
+
+    async fn watch_groups(
+        &self,
+        request: Request<proto::WatchGroupsRequest>,
+    ) -> Result<Response<Self::WatchGroupsStream>, Status> {
+        let language = parse_language(&request.into_inner().language)?;
+        let service = self.clone();
+        let mut receiver = self.state.report_updates.clone();
+        receiver.borrow_and_update();
+
+        // current groups first, then again after every change of the report
+        let current = service.groups_of_day(today(), &language).await?;
+        let changes = futures::stream::unfold(receiver, move |mut receiver| {
+            let service = service.clone();
+            let language = language.clone();
+            async move {
+                receiver.changed().await.ok()?;
+                receiver.borrow_and_update();
+                let groups = service.groups_of_day(today(), &language).await;
+                Some((groups, receiver))
+            }
+        });
+        let stream = futures::stream::once(async { Ok(current) })
+            .chain(changes)
+            // open streams would keep the server from shutting down
+            .take_until(shutdown::signal())
+            .boxed();
+        Ok(Response::new(stream))
+    }
+}
@@ -0,0 +1,43 @@
+use axum::http::header::CONTENT_TYPE;
+use axum::response::IntoResponse;
+
+/// JSON Feed, see <https://www.jsonfeed.org/version/1.1/>
+#[derive(serde::Serialize)]
+pub struct Feed {
+    pub version: &'static str,
+    pub title: String,
+    pub home_page_url: url::Url,
+    #[serde(rename = "feed_url")]
+    pub self_url: url::Url,
+    pub items: Vec<Item>,
+}
+
+#[derive(serde::Serialize)]
+pub struct Item {
+    pub id: String,
+    pub url: String,
+    pub external_url: String,
+    pub title: String,
+    pub content_text: String,
+    pub date_published: chrono::DateTime<chrono::Utc>,
+    pub authors: Vec<Author>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+#[derive(serde::Serialize)]
+pub struct Author {
+    pub name: String,
+}
+
+pub static VERSION: &str = "https://jsonfeed.org/version/1.1";
+
+impl IntoResponse for Feed {
+    fn into_response(self) -> axum::response::Response {
+        (
+            [(CONTENT_TYPE, "application/feed+json; charset=utf-8")],
+            serde_json::to_string(&self).expect("failed to serialize json feed"),
+        )
+            .into_response()
+    }
+}
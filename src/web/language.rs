@@ -0,0 +1,116 @@
+use axum::extract::{FromRequestParts, Request};
+use axum::http::header::{ACCEPT_LANGUAGE, COOKIE, SET_COOKIE};
+use axum::http::request::Parts;
+use axum::http::HeaderValue;
+use axum::middleware::Next;
+use axum::response::Response;
+
+use crate::feeds;
+
+const NAME: &str = "lang";
+
+const MAX_AGE: u32 = 365 * 24 * 60 * 60;
+
+static AVAILABLE: once_cell::sync::OnceCell<Vec<feeds::LanguageCode>> =
+    once_cell::sync::OnceCell::new();
+
+pub fn init(languages: Vec<feeds::LanguageCode>) {
+    let _ = AVAILABLE.set(languages);
+}
+
+pub fn available() -> &'static [feeds::LanguageCode] {
+    AVAILABLE.get_or_init(|| vec![feeds::LanguageCode::SV, feeds::LanguageCode::EN])
+}
+
+fn is_available(lang_code: &feeds::LanguageCode) -> bool {
+    available().contains(lang_code)
+}
+
+pub fn direction(lang_code: &feeds::LanguageCode) -> &'static str {
+    match lang_code {
+        feeds::LanguageCode::AR => "rtl",
+        _ => "ltr",
+    }
+}
+
+pub struct Language(pub feeds::LanguageCode);
+
+fn from_query(parts: &Parts) -> Option<feeds::LanguageCode> {
+    let query = parts.uri.query()?;
+    url::form_urlencoded::parse(query.as_bytes())
+        .find(|(name, _)| name == NAME)
+        .and_then(|(_, value)| value.parse().ok())
+        .filter(is_available)
+}
+
+fn from_cookie(parts: &Parts) -> Option<feeds::LanguageCode> {
+    parts
+        .headers
+        .get_all(COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .filter_map(|cookie| cookie.trim().split_once('='))
+        .find(|(name, _)| *name == NAME)
+        .and_then(|(_, value)| value.parse().ok())
+        .filter(is_available)
+}
+
+/// the most preferred of the supported languages, `sv-SE` counts as `sv`
+fn from_accept_language(parts: &Parts) -> Option<feeds::LanguageCode> {
+    parts
+        .headers
+        .get_all(ACCEPT_LANGUAGE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|range| {
+            let mut params = range.split(';');
+            let tag = params.next()?.trim();
+            let primary = tag.split('-').next()?.to_ascii_lowercase();
+            let language = primary
+                .parse::<feeds::LanguageCode>()
+                .ok()
+                .filter(is_available)?;
+            let quality = params
+                .find_map(|param| param.trim().strip_prefix("q="))
+                .map_or(Some(1.0), |quality| quality.trim().parse::<f32>().ok())?;
+            (quality > 0.0).then_some((language, quality))
+        })
+        // first of the equally preferred wins
+        .fold(
+            None,
+            |best: Option<(feeds::LanguageCode, f32)>, (language, quality)| match best {
+                Some((_, best_quality)) if best_quality >= quality => best,
+                _ => Some((language, quality)),
+            },
+        )
+        .map(|(language, _)| language)
+}
+
+#[async_trait::async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for Language {
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(Self(
+            from_query(parts)
+                .or_else(|| from_cookie(parts))
+                .or_else(|| from_accept_language(parts))
+                .unwrap_or(feeds::LanguageCode::EN),
+        ))
+    }
+}
+
+pub async fn remember(request: Request, next: Next) -> Response {
+    let (parts, body) = request.into_parts();
+    let picked = from_query(&parts);
+    let mut response = next.run(Request::from_parts(parts, body)).await;
+    if let Some(language) = picked {
+        let cookie = format!("{NAME}={language}; Path=/; Max-Age={MAX_AGE}; SameSite=Lax");
+        if let Ok(value) = HeaderValue::from_str(&cookie) {
+            response.headers_mut().append(SET_COOKIE, value);
+        }
+    }
+    response
+}
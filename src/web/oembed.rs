@@ -0,0 +1,39 @@
+use axum::http::header::CONTENT_TYPE;
+use axum::response::IntoResponse;
+
+/// oEmbed rich response, see <https://oembed.com/#section2.3>
+#[derive(serde::Serialize)]
+pub struct Response {
+    pub version: &'static str,
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    pub title: String,
+    pub provider_name: &'static str,
+    pub provider_url: url::Url,
+    pub html: String,
+    pub width: u32,
+    pub height: u32,
+}
+
+pub static VERSION: &str = "1.0";
+
+pub const WIDTH: u32 = 560;
+pub const HEIGHT: u32 = 420;
+
+#[derive(Debug, Default, serde::Deserialize)]
+pub struct Params {
+    pub url: String,
+    pub maxwidth: Option<u32>,
+    pub maxheight: Option<u32>,
+    pub format: Option<String>,
+}
+
+impl IntoResponse for Response {
+    fn into_response(self) -> axum::response::Response {
+        (
+            [(CONTENT_TYPE, "application/json; charset=utf-8")],
+            serde_json::to_string(&self).expect("failed to serialize oembed response"),
+        )
+            .into_response()
+    }
+}
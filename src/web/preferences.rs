@@ -0,0 +1,134 @@
+use axum::extract::FromRequestParts;
+use axum::http::header::COOKIE;
+use axum::http::request::Parts;
+use axum::http::HeaderValue;
+use hmac::Mac;
+
+use crate::feeds;
+use crate::id::Id;
+
+const NAME: &str = "hidden_feeds";
+
+const MAX_AGE: u32 = 365 * 24 * 60 * 60;
+
+type Hmac = hmac::Hmac<sha2::Sha256>;
+
+#[derive(Clone)]
+pub struct Key(Vec<u8>);
+
+impl Key {
+    /// cookies signed with a random key are forgotten when the server restarts
+    pub fn new(secret: Option<&str>) -> Self {
+        match secret {
+            Some(secret) => Self(secret.as_bytes().to_vec()),
+            None => Self(rand::random::<[u8; 32]>().to_vec()),
+        }
+    }
+
+    fn mac(&self, value: &str) -> Hmac {
+        let mut mac = Hmac::new_from_slice(&self.0).expect("hmac takes keys of any size");
+        mac.update(value.as_bytes());
+        mac
+    }
+
+    fn sign(&self, value: &str) -> String {
+        use std::fmt::Write;
+
+        let signature = self.mac(value).finalize().into_bytes();
+        let signature = signature.iter().fold(String::new(), |mut hex, byte| {
+            let _ = write!(hex, "{byte:02x}");
+            hex
+        });
+        format!("{value}.{signature}")
+    }
+
+    fn verify<'a>(&self, signed: &'a str) -> Option<&'a str> {
+        let (value, signature) = signed.rsplit_once('.')?;
+        let signature = (0..signature.len())
+            .step_by(2)
+            .map(|i| {
+                signature
+                    .get(i..i + 2)
+                    .and_then(|byte| u8::from_str_radix(byte, 16).ok())
+            })
+            .collect::<Option<Vec<_>>>()?;
+        self.mac(value).verify_slice(&signature).ok()?;
+        Some(value)
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct HiddenFeeds(pub std::collections::BTreeSet<Id<feeds::Feed>>);
+
+impl HiddenFeeds {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn contains(&self, feed_id: Id<feeds::Feed>) -> bool {
+        self.0.contains(&feed_id)
+    }
+
+    /// ids of the feeds separated with `-`, commas are not allowed in cookies
+    fn to_value(&self) -> String {
+        self.0
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("-")
+    }
+
+    fn from_value(value: &str) -> Self {
+        Self(
+            value
+                .split('-')
+                .filter_map(|id| id.parse::<u32>().ok())
+                .map(Id::from)
+                .filter(|id| feeds::LIST.iter().any(|feed| feed.id == *id))
+                .collect(),
+        )
+    }
+
+    pub fn etag_suffix(&self) -> Option<String> {
+        (!self.is_empty()).then(|| self.to_value())
+    }
+
+    pub fn cookie(&self, key: &Key) -> Option<HeaderValue> {
+        let cookie = if self.is_empty() {
+            format!("{NAME}=; Path=/; Max-Age=0; SameSite=Lax; HttpOnly")
+        } else {
+            format!(
+                "{NAME}={}; Path=/; Max-Age={MAX_AGE}; SameSite=Lax; HttpOnly",
+                key.sign(&self.to_value())
+            )
+        };
+        HeaderValue::from_str(&cookie).ok()
+    }
+
+    /// reads the cookie, feeds are not hidden when its signature does not match
+    fn from_parts(parts: &Parts, key: &Key) -> Self {
+        parts
+            .headers
+            .get_all(COOKIE)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(';'))
+            .filter_map(|cookie| cookie.trim().split_once('='))
+            .find(|(name, _)| *name == NAME)
+            .and_then(|(_, value)| key.verify(value))
+            .map(Self::from_value)
+            .unwrap_or_default()
+    }
+}
+
+#[async_trait::async_trait]
+impl FromRequestParts<super::AppState> for HiddenFeeds {
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &super::AppState,
+    ) -> Result<Self, Self::Rejection> {
+        Ok(Self::from_parts(parts, &state.preferences_key))
+    }
+}
@@ -0,0 +1,173 @@
+use std::net::{IpAddr, SocketAddr};
+
+use axum::extract::{ConnectInfo, FromRequestParts, Request, State};
+use axum::http::{request::Parts, HeaderMap, StatusCode};
+use axum::middleware::Next;
+use axum::response::Response;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Network {
+    address: IpAddr,
+    prefix_len: u8,
+}
+
+impl Network {
+    fn contains(&self, address: &IpAddr) -> bool {
+        let (network, address, bits) = match (self.address, canonical(*address)) {
+            (IpAddr::V4(network), IpAddr::V4(address)) => (
+                u128::from(u32::from(network)),
+                u128::from(u32::from(address)),
+                32,
+            ),
+            (IpAddr::V6(network), IpAddr::V6(address)) => {
+                (u128::from(network), u128::from(address), 128)
+            }
+            _ => return false,
+        };
+        let host_bits = bits - u32::from(self.prefix_len);
+        network.checked_shr(host_bits).unwrap_or(0) == address.checked_shr(host_bits).unwrap_or(0)
+    }
+}
+
+impl std::str::FromStr for Network {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (address, prefix_len) = match value.split_once('/') {
+            Some((address, prefix_len)) => (address, Some(prefix_len)),
+            None => (value, None),
+        };
+        let address = address
+            .parse::<IpAddr>()
+            .map_err(|error| format!("invalid address {address}: {error}"))?;
+        let address = canonical(address);
+        let max_len = if address.is_ipv4() { 32 } else { 128 };
+        let prefix_len = match prefix_len {
+            Some(prefix_len) => prefix_len
+                .parse::<u8>()
+                .ok()
+                .filter(|prefix_len| *prefix_len <= max_len)
+                .ok_or_else(|| format!("invalid prefix length {prefix_len}"))?,
+            None => max_len,
+        };
+        Ok(Self {
+            address,
+            prefix_len,
+        })
+    }
+}
+
+/// ipv4 addresses of dual stack sockets are seen as ipv6 ones
+fn canonical(address: IpAddr) -> IpAddr {
+    match address {
+        IpAddr::V6(v6) => v6.to_ipv4_mapped().map_or(address, IpAddr::V4),
+        IpAddr::V4(_) => address,
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TrustedProxies {
+    networks: Vec<Network>,
+    scheme: Scheme,
+}
+
+impl TrustedProxies {
+    pub fn new(networks: Vec<Network>, scheme: Scheme) -> Self {
+        Self { networks, scheme }
+    }
+
+    fn is_trusted(&self, address: &IpAddr) -> bool {
+        self.networks
+            .iter()
+            .any(|network| network.contains(address))
+    }
+
+    /// resolves the client the request came from. every proxy appends the address it was
+    /// connected from to `X-Forwarded-For`, so the client is the last address that is not of a
+    /// trusted proxy, anything before it could have been sent by the client itself.
+    fn client(&self, headers: &HeaderMap, peer: SocketAddr) -> Client {
+        let peer = canonical(peer.ip());
+        if !self.is_trusted(&peer) {
+            return Client {
+                address: peer,
+                scheme: self.scheme,
+            };
+        }
+
+        let forwarded_for = headers
+            .get_all("x-forwarded-for")
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(|address| address.trim().parse::<IpAddr>().ok().map(canonical))
+            .collect::<Vec<_>>();
+        let mut address = peer;
+        for forwarded in forwarded_for.into_iter().rev() {
+            let Some(forwarded) = forwarded else {
+                break;
+            };
+            address = forwarded;
+            if !self.is_trusted(&forwarded) {
+                break;
+            }
+        }
+
+        let scheme = headers
+            .get("x-forwarded-proto")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.split(',').next())
+            .map_or(self.scheme, |value| {
+                if value.trim().eq_ignore_ascii_case("https") {
+                    Scheme::Https
+                } else {
+                    Scheme::Http
+                }
+            });
+        Client { address, scheme }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scheme {
+    Http,
+    Https,
+}
+
+impl std::fmt::Display for Scheme {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Scheme::Http => write!(f, "http"),
+            Scheme::Https => write!(f, "https"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Client {
+    pub address: IpAddr,
+    pub scheme: Scheme,
+}
+
+pub async fn resolve(
+    State(proxies): State<std::sync::Arc<TrustedProxies>>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let client = proxies.client(request.headers(), peer);
+    request.extensions_mut().insert(client);
+    next.run(request).await
+}
+
+#[async_trait::async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for Client {
+    type Rejection = StatusCode;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<Client>()
+            .copied()
+            .ok_or(StatusCode::INTERNAL_SERVER_ERROR)
+    }
+}
@@ -0,0 +1,144 @@
+use std::time::{Duration, Instant};
+
+use axum::extract::{Request, State};
+use axum::http::header::RETRY_AFTER;
+use axum::http::StatusCode;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+
+use super::proxy::Client;
+
+const CLEANUP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// ipv6 clients usually get a whole /64, they are limited by it instead of each of its addresses
+fn network(address: std::net::IpAddr) -> std::net::IpAddr {
+    match address.to_canonical() {
+        std::net::IpAddr::V6(address) => {
+            std::net::Ipv6Addr::from(u128::from(address) & !u128::from(u64::MAX)).into()
+        }
+        address @ std::net::IpAddr::V4(_) => address,
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    available: f64,
+    refilled_at: Instant,
+}
+
+#[derive(Debug)]
+struct Buckets {
+    by_network: std::collections::HashMap<std::net::IpAddr, Bucket>,
+    cleaned_at: Instant,
+}
+
+#[derive(Debug)]
+pub struct Limiter {
+    per_second: f64,
+    burst: f64,
+    buckets: std::sync::Mutex<Buckets>,
+}
+
+impl Limiter {
+    pub fn new(per_second: f64, burst: u32) -> Self {
+        Self {
+            per_second,
+            burst: f64::from(burst.max(1)),
+            buckets: std::sync::Mutex::new(Buckets {
+                by_network: std::collections::HashMap::new(),
+                cleaned_at: Instant::now(),
+            }),
+        }
+    }
+
+    fn acquire(&self, address: std::net::IpAddr) -> Result<(), Duration> {
+        let now = Instant::now();
+        let buckets = &mut *self.buckets.lock().expect("failed to lock buckets");
+
+        if now.duration_since(buckets.cleaned_at) >= CLEANUP_INTERVAL {
+            // buckets that are full again are the same as new ones
+            let (per_second, burst) = (self.per_second, self.burst);
+            buckets.by_network.retain(|_, bucket| {
+                bucket.available + now.duration_since(bucket.refilled_at).as_secs_f64() * per_second
+                    < burst
+            });
+            buckets.cleaned_at = now;
+        }
+
+        let bucket = buckets
+            .by_network
+            .entry(network(address))
+            .or_insert(Bucket {
+                available: self.burst,
+                refilled_at: now,
+            });
+        let elapsed = now.duration_since(bucket.refilled_at).as_secs_f64();
+        bucket.available = (bucket.available + elapsed * self.per_second).min(self.burst);
+        bucket.refilled_at = now;
+
+        if bucket.available >= 1.0 {
+            bucket.available -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64(
+                (1.0 - bucket.available) / self.per_second,
+            ))
+        }
+    }
+}
+
+pub async fn limit(
+    State(limiter): State<std::sync::Arc<Limiter>>,
+    client: Client,
+    request: Request,
+    next: Next,
+) -> Response {
+    let address = client.address;
+    match limiter.acquire(address) {
+        Ok(()) => next.run(request).await,
+        Err(wait_time) => {
+            tracing::debug!(%address, ?wait_time, "rate limited");
+            (
+                StatusCode::TOO_MANY_REQUESTS,
+                [(RETRY_AFTER, wait_time.as_secs().max(1).to_string())],
+                "Too many requests, try again later.",
+            )
+                .into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limits_ipv6_clients_by_network() {
+        let limiter = Limiter::new(1.0, 2);
+        for address in ["2001:db8::1", "2001:db8::2:3"] {
+            assert!(limiter
+                .acquire(address.parse().expect("valid address"))
+                .is_ok());
+        }
+        assert!(limiter
+            .acquire("2001:db8::ffff".parse().expect("valid address"))
+            .is_err());
+        assert!(limiter
+            .acquire("2001:db8:0:1::1".parse().expect("valid address"))
+            .is_ok());
+    }
+
+    #[test]
+    fn limits_ipv4_clients_by_address() {
+        let limiter = Limiter::new(1.0, 1);
+        assert!(limiter
+            .acquire("192.0.2.1".parse().expect("valid address"))
+            .is_ok());
+        assert!(limiter
+            .acquire("::ffff:192.0.2.1".parse().expect("valid address"))
+            .is_err());
+        assert!(limiter
+            .acquire("192.0.2.2".parse().expect("valid address"))
+            .is_ok());
+    }
+}
@@ -0,0 +1,35 @@
+#[derive(Debug, Clone)]
+pub struct Tls {
+    pub cert: std::path::PathBuf,
+    pub key: std::path::PathBuf,
+}
+
+/// serves the router over https until the process is stopped. certificates are read once, so the
+/// process must be restarted when they are renewed.
+pub async fn serve(
+    address: &str,
+    tls: &Tls,
+    router: axum::Router,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let config = axum_server::tls_rustls::RustlsConfig::from_pem_file(&tls.cert, &tls.key).await?;
+    let address = tokio::net::lookup_host(address)
+        .await?
+        .next()
+        .ok_or_else(|| format!("{address} does not resolve to any address"))?;
+
+    let handle = axum_server::Handle::new();
+    tokio::spawn({
+        let handle = handle.clone();
+        async move {
+            crate::shutdown::signal().await;
+            handle.graceful_shutdown(None);
+        }
+    });
+
+    tracing::info!("listening on https://{address}");
+    axum_server::bind_rustls(address, config)
+        .handle(handle)
+        .serve(router.into_make_service_with_connect_info::<std::net::SocketAddr>())
+        .await?;
+    Ok(())
+}
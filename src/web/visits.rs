@@ -0,0 +1,83 @@
+use axum::extract::FromRequestParts;
+use axum::http::header::COOKIE;
+use axum::http::request::Parts;
+use axum::http::HeaderValue;
+
+use crate::clustering;
+use crate::id::Id;
+
+const NAME: &str = "visit";
+
+const MAX_AGE: u32 = 30 * 24 * 60 * 60;
+
+/// minutes without requests after which the next one starts a new visit
+const GAP_MINUTES: i64 = 30;
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Visit {
+    pub since_report_id: Option<Id<clustering::Report>>,
+    seen_report_id: Option<Id<clustering::Report>>,
+    seen_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl Visit {
+    /// cookie value is `<since report id>-<seen report id>-<seen at unix timestamp>`
+    fn from_value(value: &str) -> Option<Self> {
+        let mut parts = value.split('-');
+        let since_report_id = parts.next()?.parse::<u32>().ok()?;
+        let seen_report_id = parts.next()?.parse::<u32>().ok()?;
+        let seen_at = parts.next()?.parse::<i64>().ok()?;
+        Some(Self {
+            since_report_id: Some(Id::from(since_report_id)),
+            seen_report_id: Some(Id::from(seen_report_id)),
+            seen_at: chrono::DateTime::from_timestamp(seen_at, 0),
+        })
+    }
+
+    fn from_parts(parts: &Parts, now: chrono::DateTime<chrono::Utc>) -> Self {
+        let previous = parts
+            .headers
+            .get_all(COOKIE)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(';'))
+            .filter_map(|cookie| cookie.trim().split_once('='))
+            .find(|(name, _)| *name == NAME)
+            .and_then(|(_, value)| Self::from_value(value))
+            .unwrap_or_default();
+        let is_ongoing = previous
+            .seen_at
+            .is_some_and(|seen_at| now - seen_at < chrono::Duration::minutes(GAP_MINUTES));
+        if is_ongoing {
+            previous
+        } else {
+            // reports seen during the previous visit are not new anymore
+            Self {
+                since_report_id: previous.seen_report_id,
+                ..previous
+            }
+        }
+    }
+
+    pub fn cookie(
+        &self,
+        report_id: Id<clustering::Report>,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> Option<HeaderValue> {
+        let since_report_id = self.since_report_id.unwrap_or(report_id);
+        let cookie = format!(
+            "{NAME}={since_report_id}-{report_id}-{}; Path=/; Max-Age={MAX_AGE}; SameSite=Lax; HttpOnly",
+            now.timestamp()
+        );
+        HeaderValue::from_str(&cookie).ok()
+    }
+}
+
+#[async_trait::async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for Visit {
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(Self::from_parts(parts, chrono::Utc::now()))
+    }
+}